@@ -11,8 +11,9 @@ pub use model::{
     default_openai_models, get_model_cache,
 };
 pub use provider::{
-    ConversationId, LlmProvider, ProviderConfig, ProviderError, ProviderEventStream,
-    ProviderMessage, ProviderResult, ProviderStreamHandle, ProviderWorker, Role, StreamEventMapped,
+    AbortOnDropHandle, BoxFuture, ContentPart, ConversationId, ImageDetail, LlmProvider,
+    MessageContent, OpenAiApi, ProviderConfig, ProviderError, ProviderEventStream, ProviderMessage,
+    ProviderResult, ProviderStreamHandle, ProviderWorker, Role, StreamEventMapped,
     StreamEventPayload, StreamRequest, StreamSessionId, StreamTarget,
 };
 pub use rig_adapter::{RIG_OPENAI_PROVIDER_ID, RigProviderAdapter};
@@ -21,6 +22,7 @@ pub fn create_provider(mut config: ProviderConfig) -> ProviderResult<Arc<dyn Llm
     if config.provider_id.trim().is_empty() {
         config.provider_id = RIG_OPENAI_PROVIDER_ID.to_string();
     }
+    config.validate()?;
 
     match config.provider_id.as_str() {
         "openai" | "rig-openai" => {
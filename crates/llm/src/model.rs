@@ -1,14 +1,20 @@
-use std::collections::HashMap;
-use std::sync::{Arc, OnceLock};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, Instant};
 
 use tokio::sync::RwLock;
 
+use super::provider::{LlmProvider, ProviderResult};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Model {
     pub id: String,
     pub name: String,
     pub description: Option<String>,
+    pub context_window: Option<u32>,
+    pub supports_vision: bool,
+    pub supports_tools: bool,
+    pub max_output_tokens: Option<u32>,
 }
 
 impl Model {
@@ -17,26 +23,136 @@ impl Model {
             id: id.into(),
             name: name.into(),
             description: None,
+            context_window: None,
+            supports_vision: false,
+            supports_tools: false,
+            max_output_tokens: None,
         }
     }
 
+    /// Builds a model from a bare provider id, filling capabilities for known model families
+    /// because the OpenAI `/models` payload does not report them.
     pub fn from_id(id: impl Into<String>) -> Self {
         let id = id.into();
-        Self::new(id.clone(), id)
+        let model = Self::new(id.clone(), id);
+        match known_capabilities(&model.id) {
+            Some(capabilities) => model.with_capabilities(capabilities),
+            None => model,
+        }
     }
 
     pub fn with_description(mut self, description: impl Into<String>) -> Self {
         self.description = Some(description.into());
         self
     }
+
+    fn with_capabilities(mut self, capabilities: KnownCapabilities) -> Self {
+        self.context_window = Some(capabilities.context_window);
+        self.supports_vision = capabilities.supports_vision;
+        self.supports_tools = capabilities.supports_tools;
+        self.max_output_tokens = Some(capabilities.max_output_tokens);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct KnownCapabilities {
+    context_window: u32,
+    supports_vision: bool,
+    supports_tools: bool,
+    max_output_tokens: u32,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+// Ordered so more specific prefixes win, e.g. `gpt-4o-mini` before `gpt-4o`.
+const KNOWN_MODEL_CAPABILITIES: &[(&str, KnownCapabilities)] = &[
+    (
+        "gpt-4o-mini",
+        KnownCapabilities {
+            context_window: 128_000,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 16_384,
+        },
+    ),
+    (
+        "gpt-4o",
+        KnownCapabilities {
+            context_window: 128_000,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 16_384,
+        },
+    ),
+    (
+        "gpt-4.1",
+        KnownCapabilities {
+            context_window: 1_047_576,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 32_768,
+        },
+    ),
+    (
+        "o3-mini",
+        KnownCapabilities {
+            context_window: 200_000,
+            supports_vision: false,
+            supports_tools: true,
+            max_output_tokens: 100_000,
+        },
+    ),
+    (
+        "o3",
+        KnownCapabilities {
+            context_window: 200_000,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 100_000,
+        },
+    ),
+    (
+        "gpt-3.5-turbo",
+        KnownCapabilities {
+            context_window: 16_385,
+            supports_vision: false,
+            supports_tools: true,
+            max_output_tokens: 4_096,
+        },
+    ),
+];
+
+fn known_capabilities(model_id: &str) -> Option<KnownCapabilities> {
+    KNOWN_MODEL_CAPABILITIES
+        .iter()
+        .find(|(prefix, _)| model_id.starts_with(prefix))
+        .map(|(_, capabilities)| *capabilities)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ModelCatalogSource {
     ProviderApi,
     CacheFresh,
     CacheStaleFallback,
     StaticFallback,
+    /// Catalog published at a standalone URL rather than the provider's `/models` endpoint.
+    Remote {
+        url: String,
+    },
+}
+
+impl ModelCatalogSource {
+    /// Remote catalogs are fetched with the provider's credentials, so their entries stay
+    /// scoped to the provider and are evicted along with it.
+    fn cache_key(&self, provider_cache_key: &str) -> String {
+        match self {
+            Self::Remote { url } => format!("{}{url}", remote_cache_key_prefix(provider_cache_key)),
+            _ => provider_cache_key.to_string(),
+        }
+    }
+}
+
+fn remote_cache_key_prefix(provider_cache_key: &str) -> String {
+    format!("{provider_cache_key}:remote:")
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -71,6 +187,14 @@ impl ModelCatalog {
         }
     }
 
+    pub fn from_remote(models: Vec<Model>, url: impl Into<String>) -> Self {
+        Self {
+            models,
+            source: ModelCatalogSource::Remote { url: url.into() },
+            warning: None,
+        }
+    }
+
     pub fn from_static_fallback(models: Vec<Model>, warning: String) -> Self {
         Self {
             models,
@@ -78,6 +202,14 @@ impl ModelCatalog {
             warning: Some(warning),
         }
     }
+
+    pub fn find_by_id(&self, model_id: &str) -> Option<&Model> {
+        self.models.iter().find(|model| model.id == model_id)
+    }
+
+    pub fn is_available(&self, model_id: &str) -> bool {
+        self.find_by_id(model_id).is_some()
+    }
 }
 
 struct CacheEntry {
@@ -88,6 +220,7 @@ struct CacheEntry {
 pub struct ModelCache {
     entries: RwLock<HashMap<String, CacheEntry>>,
     ttl: Duration,
+    refreshing: Mutex<HashSet<String>>,
 }
 
 impl ModelCache {
@@ -95,9 +228,14 @@ impl ModelCache {
         Self {
             entries: RwLock::new(HashMap::new()),
             ttl,
+            refreshing: Mutex::new(HashSet::new()),
         }
     }
 
+    pub fn cache_ttl(&self) -> Duration {
+        self.ttl
+    }
+
     pub fn with_default_ttl() -> Self {
         Self::new(Duration::from_secs(60 * 60))
     }
@@ -128,6 +266,115 @@ impl ModelCache {
             },
         );
     }
+
+    /// Drops every entry cached for `provider`, remote catalogs included, so the next lookup
+    /// goes back to the network.
+    pub async fn invalidate(&self, provider: &dyn LlmProvider) -> bool {
+        let provider_cache_key = provider.cache_key();
+        let remote_prefix = remote_cache_key_prefix(provider_cache_key);
+        let mut entries = self.entries.write().await;
+        let previous_len = entries.len();
+        entries.retain(|key, _| key != provider_cache_key && !key.starts_with(&remote_prefix));
+        entries.len() != previous_len
+    }
+
+    pub async fn clear(&self) {
+        self.entries.write().await.clear();
+    }
+
+    /// Serves `source` from cache while it is within the TTL. Once it expires the stale entry is
+    /// returned straight away and refreshed in the background; only a cold cache waits on the
+    /// network.
+    pub async fn get_or_fetch(
+        self: &Arc<Self>,
+        source: &ModelCatalogSource,
+        provider: Arc<dyn LlmProvider>,
+    ) -> ProviderResult<ModelCatalog> {
+        let cache_key = source.cache_key(provider.cache_key());
+        if let Some(models) = self.get_fresh(&cache_key).await {
+            return Ok(ModelCatalog::from_cache_fresh(models));
+        }
+
+        if let Some(models) = self.get_any(&cache_key).await {
+            self.spawn_refresh(cache_key, source.clone(), provider);
+            return Ok(ModelCatalog::from_cache_stale(
+                models,
+                "model catalog expired; refreshing in the background".to_string(),
+            ));
+        }
+
+        let catalog = fetch_catalog(source, provider.as_ref()).await?;
+        if catalog_is_cacheable(&catalog) {
+            self.set(&cache_key, catalog.models.clone()).await;
+        }
+        Ok(catalog)
+    }
+
+    fn spawn_refresh(
+        self: &Arc<Self>,
+        cache_key: String,
+        source: ModelCatalogSource,
+        provider: Arc<dyn LlmProvider>,
+    ) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            tracing::debug!(cache_key = %cache_key, "no tokio runtime; skipping model refresh");
+            return;
+        };
+        // Concurrent callers keep reading the stale entry, so one refresh per key is enough.
+        let newly_refreshing = self
+            .refreshing
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(cache_key.clone());
+        if !newly_refreshing {
+            return;
+        }
+
+        let cache = self.clone();
+        runtime.spawn(async move {
+            match fetch_catalog(&source, provider.as_ref()).await {
+                Ok(catalog) if catalog_is_cacheable(&catalog) => {
+                    cache.set(&cache_key, catalog.models).await;
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    tracing::warn!(
+                        cache_key = %cache_key,
+                        error = %error,
+                        "background model refresh failed; keeping stale cache"
+                    );
+                }
+            }
+            cache
+                .refreshing
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .remove(&cache_key);
+        });
+    }
+}
+
+async fn fetch_catalog(
+    source: &ModelCatalogSource,
+    provider: &dyn LlmProvider,
+) -> ProviderResult<ModelCatalog> {
+    match source {
+        ModelCatalogSource::Remote { url } => {
+            let models = provider.fetch_models_from_url(url).await?;
+            Ok(ModelCatalog::from_remote(models, url.clone()))
+        }
+        _ => provider.fetch_models().await,
+    }
+}
+
+/// Fallback catalogs must not be cached, or a transient outage would pin them for a full TTL.
+fn catalog_is_cacheable(catalog: &ModelCatalog) -> bool {
+    matches!(
+        catalog.source,
+        ModelCatalogSource::ProviderApi
+            | ModelCatalogSource::CacheFresh
+            | ModelCatalogSource::Remote { .. }
+    )
 }
 
 static MODEL_CACHE: OnceLock<Arc<ModelCache>> = OnceLock::new();
@@ -148,3 +395,204 @@ pub fn default_openai_models() -> Vec<Model> {
         Model::from_id("o3").with_description("Advanced reasoning model"),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use tokio::sync::Notify;
+
+    use super::{Model, ModelCache, ModelCatalog, ModelCatalogSource, default_openai_models};
+    use crate::provider::{
+        BoxFuture, LlmProvider, ProviderError, ProviderResult, ProviderStreamHandle, StreamRequest,
+    };
+
+    /// Returns a catalog tagged with how many times it has been fetched.
+    struct CountingProvider {
+        fetches: AtomicUsize,
+        fetched: Notify,
+    }
+
+    impl CountingProvider {
+        fn new() -> Self {
+            Self {
+                fetches: AtomicUsize::new(0),
+                fetched: Notify::new(),
+            }
+        }
+    }
+
+    impl LlmProvider for CountingProvider {
+        fn id(&self) -> &str {
+            "counting"
+        }
+
+        fn name(&self) -> &str {
+            "Counting"
+        }
+
+        fn default_model(&self) -> &str {
+            "fetch-1"
+        }
+
+        fn fallback_models(&self) -> &[Model] {
+            &[]
+        }
+
+        fn cache_key(&self) -> &str {
+            "counting:configured"
+        }
+
+        fn fetch_models<'a>(&'a self) -> BoxFuture<'a, ProviderResult<ModelCatalog>> {
+            Box::pin(async move {
+                let fetch = self.fetches.fetch_add(1, Ordering::SeqCst) + 1;
+                self.fetched.notify_one();
+                Ok(ModelCatalog::from_provider_api(vec![Model::from_id(
+                    format!("fetch-{fetch}"),
+                )]))
+            })
+        }
+
+        fn fetch_models_from_url<'a>(
+            &'a self,
+            url: &'a str,
+        ) -> BoxFuture<'a, ProviderResult<Vec<Model>>> {
+            Box::pin(async move {
+                let fetch = self.fetches.fetch_add(1, Ordering::SeqCst) + 1;
+                Ok(vec![Model::from_id(format!("{url}#{fetch}"))])
+            })
+        }
+
+        fn stream_chat(&self, _request: StreamRequest) -> ProviderResult<ProviderStreamHandle> {
+            Err(ProviderError::UnsupportedProvider {
+                stage: "counting-provider-stream",
+                provider_id: self.id().to_string(),
+            })
+        }
+    }
+
+    #[::core::prelude::v1::test]
+    fn get_or_fetch_uses_cache_then_refreshes_after_ttl() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build();
+        let Ok(runtime) = runtime else {
+            panic!("test runtime should build");
+        };
+
+        runtime.block_on(async {
+            let cache = Arc::new(ModelCache::new(Duration::from_millis(50)));
+            let provider = Arc::new(CountingProvider::new());
+            let source = ModelCatalogSource::ProviderApi;
+
+            let first = cache.get_or_fetch(&source, provider.clone()).await;
+            let second = cache.get_or_fetch(&source, provider.clone()).await;
+            assert!(first.is_ok_and(|catalog| catalog.source == ModelCatalogSource::ProviderApi));
+            assert!(second.is_ok_and(|catalog| {
+                catalog.source == ModelCatalogSource::CacheFresh && catalog.is_available("fetch-1")
+            }));
+            assert_eq!(provider.fetches.load(Ordering::SeqCst), 1);
+
+            tokio::time::sleep(Duration::from_millis(80)).await;
+            let stale = cache.get_or_fetch(&source, provider.clone()).await;
+            assert!(stale.is_ok_and(|catalog| {
+                catalog.source == ModelCatalogSource::CacheStaleFallback
+                    && catalog.is_available("fetch-1")
+            }));
+
+            // The first fetch left a permit behind; the second one comes from the background
+            // refresh, which stores its result without yielding once the fetch returns.
+            let permits = async {
+                provider.fetched.notified().await;
+                provider.fetched.notified().await;
+            };
+            let refresh = tokio::time::timeout(Duration::from_secs(5), permits).await;
+            assert!(refresh.is_ok(), "background refresh never fetched");
+            assert_eq!(provider.fetches.load(Ordering::SeqCst), 2);
+            tokio::task::yield_now().await;
+            let refreshed = cache.get_fresh(provider.cache_key()).await;
+            assert!(refreshed.is_some_and(|models| models[0].id == "fetch-2"));
+        });
+    }
+
+    #[::core::prelude::v1::test]
+    fn invalidate_forces_the_next_lookup_to_fetch() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build();
+        let Ok(runtime) = runtime else {
+            panic!("test runtime should build");
+        };
+
+        runtime.block_on(async {
+            let cache = Arc::new(ModelCache::with_default_ttl());
+            let provider = Arc::new(CountingProvider::new());
+            let source = ModelCatalogSource::ProviderApi;
+
+            let first = cache.get_or_fetch(&source, provider.clone()).await;
+            assert!(first.is_ok_and(|catalog| catalog.is_available("fetch-1")));
+            assert!(cache.invalidate(provider.as_ref()).await);
+            let after_invalidate = cache.get_or_fetch(&source, provider.clone()).await;
+            assert!(after_invalidate.is_ok_and(|catalog| catalog.is_available("fetch-2")));
+        });
+    }
+
+    #[::core::prelude::v1::test]
+    fn invalidate_evicts_remote_catalogs_fetched_for_the_provider() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build();
+        let Ok(runtime) = runtime else {
+            panic!("test runtime should build");
+        };
+
+        runtime.block_on(async {
+            let cache = Arc::new(ModelCache::with_default_ttl());
+            let provider = Arc::new(CountingProvider::new());
+            let remote = ModelCatalogSource::Remote {
+                url: "https://models.example/catalog.json".to_string(),
+            };
+
+            let first = cache.get_or_fetch(&remote, provider.clone()).await;
+            let cached = cache.get_or_fetch(&remote, provider.clone()).await;
+            assert!(first.is_ok_and(|catalog| catalog.source == remote));
+            assert!(cached.is_ok_and(|catalog| catalog.source == ModelCatalogSource::CacheFresh));
+            assert_eq!(provider.fetches.load(Ordering::SeqCst), 1);
+
+            assert!(cache.invalidate(provider.as_ref()).await);
+            assert!(!cache.invalidate(provider.as_ref()).await);
+            let refetched = cache.get_or_fetch(&remote, provider.clone()).await;
+            assert!(refetched.is_ok_and(|catalog| catalog.source == remote));
+            assert_eq!(provider.fetches.load(Ordering::SeqCst), 2);
+        });
+    }
+
+    #[::core::prelude::v1::test]
+    fn find_by_id_returns_known_capabilities() {
+        let catalog = ModelCatalog::from_provider_api(default_openai_models());
+
+        let gpt_4o = catalog.find_by_id("gpt-4o");
+        assert!(gpt_4o.is_some_and(|model| model.supports_vision && model.supports_tools));
+        assert_eq!(gpt_4o.and_then(|model| model.context_window), Some(128_000));
+        assert!(catalog.is_available("o3"));
+        assert!(!catalog.is_available("gpt-unknown"));
+    }
+
+    #[::core::prelude::v1::test]
+    fn o3_mini_does_not_inherit_o3_vision() {
+        let o3_mini = Model::from_id("o3-mini-2025-01-31");
+        assert!(!o3_mini.supports_vision);
+        assert!(o3_mini.supports_tools);
+        assert!(Model::from_id("o3").supports_vision);
+    }
+
+    #[::core::prelude::v1::test]
+    fn unknown_model_ids_have_no_capabilities() {
+        let model = Model::from_id("custom-local-model");
+        assert!(!model.supports_vision);
+        assert_eq!(model.context_window, None);
+        assert_eq!(model.max_output_tokens, None);
+    }
+}
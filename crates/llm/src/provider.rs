@@ -1,9 +1,16 @@
+use std::collections::HashMap;
 use std::future::Future;
 use std::hash::{Hash, Hasher};
 use std::pin::Pin;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 
-use snafu::Snafu;
+use snafu::{Snafu, ensure};
+use tiktoken_rs::CoreBPE;
+use tiktoken_rs::tokenizer::Tokenizer;
 use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
 
 use super::model::{Model, ModelCatalog};
 
@@ -53,6 +60,7 @@ pub enum StreamEventPayload {
     ReasoningDelta(String),
     Done,
     Error(String),
+    RateLimited { retry_after_seconds: Option<u64> },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -61,11 +69,29 @@ pub struct StreamEventMapped {
     pub payload: StreamEventPayload,
 }
 
+/// The OpenAI endpoint a provider streams completions from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OpenAiApi {
+    /// `/responses`. It has no `stop` parameter, so stop sequences are only enforced by
+    /// truncating the streamed text client-side.
+    #[default]
+    Responses,
+    /// `/chat/completions`, for OpenAI-compatible servers without the Responses API. Stop
+    /// sequences are sent as `stop` and still enforced client-side.
+    ChatCompletions,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ProviderConfig {
     pub provider_id: String,
     pub api_key: String,
     pub endpoint: String,
+    /// Sent with every request, e.g. `X-Org-Id` for enterprise gateways.
+    pub extra_headers: HashMap<String, String>,
+    /// Applied to every stream the provider opens; see [`ProviderStreamHandle::with_timeout`].
+    pub stream_timeout: Option<Duration>,
+    /// Endpoint used by `stream_chat`; the request never switches it on its own.
+    pub api: OpenAiApi,
 }
 
 impl ProviderConfig {
@@ -78,29 +104,148 @@ impl ProviderConfig {
             provider_id: provider_id.into().trim().to_string(),
             api_key: api_key.into().trim().to_string(),
             endpoint: endpoint.into().trim().to_string(),
+            extra_headers: HashMap::new(),
+            stream_timeout: None,
+            api: OpenAiApi::default(),
         }
     }
 
+    pub fn with_stream_timeout(mut self, timeout: Duration) -> Self {
+        self.stream_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_api(mut self, api: OpenAiApi) -> Self {
+        self.api = api;
+        self
+    }
+
+    pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra_headers.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_headers(mut self, headers: HashMap<String, String>) -> Self {
+        self.extra_headers.extend(headers);
+        self
+    }
+
     pub fn cache_key(&self) -> String {
         let mut hasher = std::collections::hash_map::DefaultHasher::new();
         self.provider_id.hash(&mut hasher);
         self.endpoint.hash(&mut hasher);
         self.api_key.hash(&mut hasher);
+        // Clients bake headers in at build time, so configs that differ only by headers must not
+        // share a cached client.
+        let mut headers: Vec<_> = self.extra_headers.iter().collect();
+        headers.sort();
+        headers.hash(&mut hasher);
         format!("{}:{:016x}", self.provider_id, hasher.finish())
     }
+
+    pub fn validate(&self) -> ProviderResult<()> {
+        ensure!(
+            !self.provider_id.trim().is_empty(),
+            InvalidConfigSnafu {
+                stage: "provider-config-validate-provider-id",
+                field: "provider_id",
+                reason: "provider id must not be empty".to_string(),
+            }
+        );
+        ensure!(
+            !self.api_key.trim().is_empty(),
+            InvalidConfigSnafu {
+                stage: "provider-config-validate-api-key",
+                field: "api_key",
+                reason: "API key must not be empty".to_string(),
+            }
+        );
+        if let Err(error) = self.endpoint.parse::<url::Url>() {
+            return InvalidConfigSnafu {
+                stage: "provider-config-validate-endpoint",
+                field: "endpoint",
+                reason: format!("endpoint '{}' is not a valid URL: {error}", self.endpoint),
+            }
+            .fail();
+        }
+        ensure!(
+            self.stream_timeout != Some(Duration::ZERO),
+            InvalidConfigSnafu {
+                stage: "provider-config-validate-stream-timeout",
+                field: "stream_timeout",
+                reason: "stream timeout must be greater than zero".to_string(),
+            }
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageDetail {
+    Low,
+    High,
+    #[default]
+    Auto,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentPart {
+    Text(String),
+    ImageUrl { url: String, detail: ImageDetail },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl MessageContent {
+    /// Returns only the textual content, for consumers that cannot carry images.
+    pub fn text(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Parts(parts) => parts
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text(text) => Some(text.as_str()),
+                    ContentPart::ImageUrl { .. } => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ProviderMessage {
     pub role: Role,
-    pub content: String,
+    pub content: MessageContent,
 }
 
 impl ProviderMessage {
     pub fn new(role: Role, content: impl Into<String>) -> Self {
         Self {
             role,
-            content: content.into(),
+            content: MessageContent::Text(content.into()),
+        }
+    }
+
+    pub fn new_multipart(
+        role: Role,
+        text: impl Into<String>,
+        image_url: impl Into<String>,
+    ) -> Self {
+        Self {
+            role,
+            content: MessageContent::Parts(vec![
+                ContentPart::Text(text.into()),
+                ContentPart::ImageUrl {
+                    url: image_url.into(),
+                    detail: ImageDetail::default(),
+                },
+            ]),
         }
     }
 }
@@ -113,6 +258,7 @@ pub struct StreamRequest {
     pub preamble: Option<String>,
     pub temperature: Option<f64>,
     pub max_tokens: Option<u64>,
+    pub stop_sequences: Vec<String>,
 }
 
 impl StreamRequest {
@@ -128,6 +274,7 @@ impl StreamRequest {
             preamble: None,
             temperature: None,
             max_tokens: None,
+            stop_sequences: Vec::new(),
         }
     }
 
@@ -145,6 +292,79 @@ impl StreamRequest {
         self.max_tokens = Some(max_tokens);
         self
     }
+
+    pub fn with_stop_sequences(
+        mut self,
+        stop_sequences: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.stop_sequences = stop_sequences.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum StopSequenceOutcome {
+    Continue(String),
+    Stopped(String),
+}
+
+/// Truncates streamed text at the first stop sequence.
+///
+/// Text that could be the start of a stop sequence is held back until the next delta decides
+/// it, so sequences split across chunks are still caught.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StopSequenceFilter {
+    stop_sequences: Vec<String>,
+    held_back: String,
+}
+
+impl StopSequenceFilter {
+    pub(crate) fn new(stop_sequences: &[String]) -> Self {
+        Self {
+            stop_sequences: stop_sequences
+                .iter()
+                .filter(|sequence| !sequence.is_empty())
+                .cloned()
+                .collect(),
+            held_back: String::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, delta: &str) -> StopSequenceOutcome {
+        if self.stop_sequences.is_empty() {
+            return StopSequenceOutcome::Continue(delta.to_string());
+        }
+
+        self.held_back.push_str(delta);
+        let stop_index = self
+            .stop_sequences
+            .iter()
+            .filter_map(|sequence| self.held_back.find(sequence.as_str()))
+            .min();
+        if let Some(stop_index) = stop_index {
+            self.held_back.truncate(stop_index);
+            return StopSequenceOutcome::Stopped(std::mem::take(&mut self.held_back));
+        }
+
+        let emit_len = self.held_back.len() - self.partial_match_len();
+        StopSequenceOutcome::Continue(self.held_back.drain(..emit_len).collect())
+    }
+
+    pub(crate) fn finish(&mut self) -> String {
+        std::mem::take(&mut self.held_back)
+    }
+
+    fn partial_match_len(&self) -> usize {
+        self.stop_sequences
+            .iter()
+            .filter_map(|sequence| {
+                (1..sequence.len()).rev().find(|len| {
+                    sequence.is_char_boundary(*len) && self.held_back.ends_with(&sequence[..*len])
+                })
+            })
+            .max()
+            .unwrap_or(0)
+    }
 }
 
 pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
@@ -159,6 +379,12 @@ pub enum ProviderError {
         stage: &'static str,
         provider_id: String,
     },
+    #[snafu(display("invalid provider config field '{field}': {reason}"))]
+    InvalidConfig {
+        stage: &'static str,
+        field: &'static str,
+        reason: String,
+    },
     #[snafu(display("provider '{provider_id}' is not supported"))]
     UnsupportedProvider {
         stage: &'static str,
@@ -179,6 +405,11 @@ pub enum ProviderError {
         stage: &'static str,
         message: String,
     },
+    #[snafu(display("provider rate limited the request (retry after {retry_after_seconds:?}s)"))]
+    RateLimited {
+        stage: &'static str,
+        retry_after_seconds: Option<u64>,
+    },
     #[snafu(display("provider model endpoint returned status {status}: {body}"))]
     ModelFetchStatus {
         stage: &'static str,
@@ -190,6 +421,8 @@ pub enum ProviderError {
         stage: &'static str,
         details: String,
     },
+    #[snafu(display("stream {stream_id} is not active"))]
+    UnknownStream { stage: &'static str, stream_id: u64 },
     #[snafu(display("completions failed on `{stage}`, {source}"))]
     CompletionsFailed {
         stage: &'static str,
@@ -204,8 +437,129 @@ pub struct ProviderEventStream {
 }
 
 pub struct ProviderStreamHandle {
+    pub stream_id: u64,
     pub stream: ProviderEventStream,
     pub worker: ProviderWorker,
+    /// Shared with the worker; cancelling it makes the worker drop its upstream connection.
+    pub cancel_token: CancellationToken,
+}
+
+impl ProviderStreamHandle {
+    /// Wraps the handle so dropping it tears down the worker's HTTP connection, even when the
+    /// task running the worker is not cancelled at the same time.
+    pub fn abort_on_drop(self) -> AbortOnDropHandle {
+        AbortOnDropHandle {
+            stream_id: self.stream_id,
+            stream: Some(self.stream),
+            worker: Some(self.worker),
+            cancel_token: self.cancel_token,
+        }
+    }
+
+    /// Fails the stream with an error event when no event arrives within `duration`, then
+    /// cancels the worker so it releases the upstream connection.
+    pub fn with_timeout(mut self, duration: Duration) -> Self {
+        let (relay_tx, relay_rx) = mpsc::unbounded_channel();
+        let upstream = std::mem::replace(&mut self.stream.events, relay_rx);
+        let target = self.stream.target;
+        let cancel_token = self.cancel_token.clone();
+        let worker = self.worker;
+        // The timer rides along with the worker so it runs on the worker's tokio runtime and
+        // `ProviderEventStream::recv` keeps working from any executor.
+        self.worker = Box::pin(async move {
+            futures::join!(
+                worker,
+                relay_with_idle_timeout(upstream, relay_tx, target, duration, cancel_token)
+            );
+        });
+        self
+    }
+}
+
+async fn relay_with_idle_timeout(
+    mut upstream: mpsc::UnboundedReceiver<StreamEventMapped>,
+    relay_tx: mpsc::UnboundedSender<StreamEventMapped>,
+    target: StreamTarget,
+    duration: Duration,
+    cancel_token: CancellationToken,
+) {
+    loop {
+        let next = tokio::select! {
+            // Nobody is listening any more, so stop the worker now rather than at the timeout.
+            _ = relay_tx.closed() => {
+                cancel_token.cancel();
+                return;
+            }
+            next = tokio::time::timeout(duration, upstream.recv()) => next,
+        };
+        match next {
+            Ok(Some(event)) => {
+                if relay_tx.send(event).is_err() {
+                    cancel_token.cancel();
+                    return;
+                }
+            }
+            Ok(None) => return,
+            Err(_) => {
+                let timed_out = StreamEventMapped {
+                    target,
+                    payload: StreamEventPayload::Error(format!(
+                        "provider sent no response within {duration:?}"
+                    )),
+                };
+                if relay_tx.send(timed_out).is_err() {
+                    tracing::debug!(target = ?target, "stream receiver dropped before idle timeout");
+                }
+                cancel_token.cancel();
+                return;
+            }
+        }
+    }
+}
+
+/// RAII guard returned by [`ProviderStreamHandle::abort_on_drop`].
+///
+/// The stream and worker can be taken out to run them elsewhere; the guard keeps ownership of
+/// the cancellation and fires it when dropped.
+pub struct AbortOnDropHandle {
+    stream_id: u64,
+    stream: Option<ProviderEventStream>,
+    worker: Option<ProviderWorker>,
+    cancel_token: CancellationToken,
+}
+
+impl AbortOnDropHandle {
+    pub fn stream_id(&self) -> u64 {
+        self.stream_id
+    }
+
+    pub fn take_stream(&mut self) -> Option<ProviderEventStream> {
+        self.stream.take()
+    }
+
+    pub fn take_worker(&mut self) -> Option<ProviderWorker> {
+        self.worker.take()
+    }
+
+    pub fn abort(&self) {
+        self.cancel_token.cancel();
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.cancel_token.is_cancelled()
+    }
+}
+
+impl Drop for AbortOnDropHandle {
+    fn drop(&mut self) {
+        self.cancel_token.cancel();
+    }
+}
+
+/// Allocates process-unique ids so `cancel_stream` can address a stream after its handle moved.
+pub(crate) fn next_stream_id() -> u64 {
+    static NEXT_STREAM_ID: AtomicU64 = AtomicU64::new(1);
+    NEXT_STREAM_ID.fetch_add(1, Ordering::Relaxed)
 }
 
 impl ProviderEventStream {
@@ -255,7 +609,111 @@ pub trait LlmProvider: Send + Sync {
     fn default_model(&self) -> &str;
     fn fallback_models(&self) -> &[Model];
     fn fetch_models<'a>(&'a self) -> BoxFuture<'a, ProviderResult<ModelCatalog>>;
+
+    /// Fetches the model list like [`LlmProvider::fetch_models`] but skips any still-fresh
+    /// cached copy, for explicit user refreshes.
+    fn refresh_models<'a>(&'a self) -> BoxFuture<'a, ProviderResult<ModelCatalog>> {
+        self.fetch_models()
+    }
+
     fn stream_chat(&self, request: StreamRequest) -> ProviderResult<ProviderStreamHandle>;
+
+    /// Key this provider's models are stored under in the shared model cache.
+    ///
+    /// Providers whose catalog depends on more than their id (endpoint, credentials) must
+    /// override this so differently configured instances do not share entries.
+    fn cache_key(&self) -> &str {
+        self.id()
+    }
+
+    /// Fetches an OpenAI-style model list from a standalone catalog `url`, sending the same
+    /// credentials and headers as this provider's own requests.
+    fn fetch_models_from_url<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> BoxFuture<'a, ProviderResult<Vec<Model>>> {
+        Box::pin(async move {
+            InvalidConfigSnafu {
+                stage: "fetch-models-from-url",
+                field: "model_catalog_url",
+                reason: format!(
+                    "provider '{}' cannot fetch the remote model catalog at {url}",
+                    self.id()
+                ),
+            }
+            .fail()
+        })
+    }
+
+    /// Estimates the prompt tokens `messages` would cost on `model_id`, for showing before a
+    /// request is sent.
+    fn estimate_prompt_tokens(&self, model_id: &str, messages: &[ProviderMessage]) -> usize {
+        if messages.is_empty() {
+            return 0;
+        }
+        self.estimate_message_tokens(model_id, messages) + TOKENS_PER_REPLY
+    }
+
+    /// Like [`estimate_prompt_tokens`](Self::estimate_prompt_tokens) without the per-request
+    /// reply priming, so estimates of consecutive message slices add up.
+    ///
+    /// The default counts with `cl100k_base`, which most OpenAI-compatible chat models share.
+    fn estimate_message_tokens(&self, _model_id: &str, messages: &[ProviderMessage]) -> usize {
+        estimate_message_tokens_with(cached_tokenizer(Tokenizer::Cl100kBase), messages)
+    }
+
+    /// Asks the provider to tear down the upstream request for `stream_id`.
+    ///
+    /// Providers without explicit cancellation rely on the stream being dropped, so the default
+    /// is a no-op.
+    fn cancel_stream(&self, _stream_id: u64) -> ProviderResult<()> {
+        Ok(())
+    }
+}
+
+/// Framing tokens around each chat message (`<|start|>{role}<|message|>...<|end|>`).
+const TOKENS_PER_MESSAGE: usize = 3;
+/// Every reply is primed with `<|start|>assistant<|message|>`.
+const TOKENS_PER_REPLY: usize = 3;
+/// Average characters per token for English text, used when no tokenizer is available.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Returns a process-wide tokenizer for the chat encodings, or `None` for other encodings or
+/// when the bundled vocabulary fails to load.
+pub(crate) fn cached_tokenizer(tokenizer: Tokenizer) -> Option<&'static CoreBPE> {
+    static CL100K_BASE: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    static O200K_BASE: OnceLock<Option<CoreBPE>> = OnceLock::new();
+
+    let cell = match tokenizer {
+        Tokenizer::Cl100kBase => &CL100K_BASE,
+        Tokenizer::O200kBase => &O200K_BASE,
+        _ => return None,
+    };
+    cell.get_or_init(|| tiktoken_rs::get_bpe_from_tokenizer(tokenizer).ok())
+        .as_ref()
+}
+
+/// Counts the tokens `messages` add to a chat prompt, excluding the reply priming, with `bpe`
+/// or a characters-per-token heuristic when no tokenizer is available.
+pub(crate) fn estimate_message_tokens_with(
+    bpe: Option<&CoreBPE>,
+    messages: &[ProviderMessage],
+) -> usize {
+    let count = |text: &str| match bpe {
+        Some(bpe) => bpe.encode_ordinary(text).len(),
+        None => text.chars().count().div_ceil(CHARS_PER_TOKEN),
+    };
+    messages
+        .iter()
+        .map(|message| {
+            let role = match message.role {
+                Role::System => "system",
+                Role::User => "user",
+                Role::Assistant => "assistant",
+            };
+            TOKENS_PER_MESSAGE + count(role) + count(&message.content.text())
+        })
+        .sum()
 }
 
 pub(crate) fn make_event_stream(
@@ -273,3 +731,106 @@ pub(crate) fn make_event_stream(
         cancel_rx,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::sync::mpsc;
+    use tokio_util::sync::CancellationToken;
+
+    use super::{
+        ConversationId, ProviderConfig, ProviderError, StopSequenceFilter, StopSequenceOutcome,
+        StreamSessionId, StreamTarget, relay_with_idle_timeout,
+    };
+
+    fn invalid_field(config: &ProviderConfig) -> Option<&'static str> {
+        match config.validate() {
+            Err(ProviderError::InvalidConfig { field, .. }) => Some(field),
+            _ => None,
+        }
+    }
+
+    #[::core::prelude::v1::test]
+    fn validate_reports_the_offending_field() {
+        let valid = ProviderConfig::new("openai", "sk-test", "https://api.openai.com/v1");
+        assert!(valid.validate().is_ok());
+
+        let empty_provider = ProviderConfig::new("  ", "sk-test", "https://api.openai.com/v1");
+        assert_eq!(invalid_field(&empty_provider), Some("provider_id"));
+
+        let empty_key = ProviderConfig::new("openai", "   ", "https://api.openai.com/v1");
+        assert_eq!(invalid_field(&empty_key), Some("api_key"));
+
+        let bad_endpoint = ProviderConfig::new("openai", "sk-test", "not a url");
+        assert_eq!(invalid_field(&bad_endpoint), Some("endpoint"));
+    }
+
+    #[::core::prelude::v1::test]
+    fn stop_sequence_split_across_deltas_truncates_content() {
+        let mut filter = StopSequenceFilter::new(&["<END>".to_string()]);
+        let mut content = String::new();
+        let mut stopped = false;
+
+        for delta in ["fn main() {}", " <E", "ND> trailing"] {
+            match filter.push(delta) {
+                StopSequenceOutcome::Continue(text) => content.push_str(&text),
+                StopSequenceOutcome::Stopped(text) => {
+                    content.push_str(&text);
+                    stopped = true;
+                    break;
+                }
+            }
+        }
+
+        assert!(stopped);
+        assert_eq!(content, "fn main() {} ");
+    }
+
+    #[::core::prelude::v1::test]
+    fn held_back_prefix_is_flushed_when_stream_ends() {
+        let mut filter = StopSequenceFilter::new(&["STOP".to_string()]);
+
+        assert_eq!(
+            filter.push("all ST"),
+            StopSequenceOutcome::Continue("all ".to_string())
+        );
+        assert_eq!(filter.finish(), "ST");
+    }
+
+    #[::core::prelude::v1::test]
+    fn idle_relay_stops_once_the_receiver_is_dropped() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build();
+        let Ok(runtime) = runtime else {
+            panic!("test runtime should build");
+        };
+        let target = StreamTarget {
+            conversation_id: ConversationId::new(1),
+            session_id: StreamSessionId::new(1),
+        };
+        // The upstream sender stays open, so only the dropped receiver can end the relay.
+        let (_upstream_tx, upstream) = mpsc::unbounded_channel();
+        let (relay_tx, relay_rx) = mpsc::unbounded_channel();
+        drop(relay_rx);
+        let cancel_token = CancellationToken::new();
+
+        let relayed = runtime.block_on(async {
+            tokio::time::timeout(
+                Duration::from_secs(5),
+                relay_with_idle_timeout(
+                    upstream,
+                    relay_tx,
+                    target,
+                    Duration::from_secs(3600),
+                    cancel_token.clone(),
+                ),
+            )
+            .await
+        });
+
+        assert!(relayed.is_ok(), "relay kept waiting for the idle timeout");
+        assert!(cancel_token.is_cancelled());
+    }
+}
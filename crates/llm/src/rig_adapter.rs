@@ -1,41 +1,211 @@
 use std::collections::HashMap;
-use std::sync::{Arc, OnceLock, RwLock};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 
+use bytes::Bytes;
 use futures::StreamExt;
+use rig::OneOrMany;
+use rig::completion::message::{ImageDetail as RigImageDetail, UserContent};
 use rig::completion::{CompletionModel, Message as RigMessage};
-use rig::http_client::{self, HttpClientExt, NoBody};
+use rig::http_client::{self, HttpClientExt, NoBody, Request};
 use rig::prelude::CompletionClient;
 use rig::providers::openai;
 use rig::streaming::StreamedAssistantContent;
-use snafu::{ResultExt, ensure};
+use rig::wasm_compat::WasmCompatSend;
+use snafu::{OptionExt, ResultExt, ensure};
 use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
 
 use super::model::{
     DEFAULT_OPENAI_MODEL, Model, ModelCache, ModelCatalog, default_openai_models, get_model_cache,
 };
 use super::provider::{
-    BoxFuture, CompletionsFailedSnafu, EmptyMessageSetSnafu, HttpClientSnafu, LlmProvider,
-    MissingApiKeySnafu, ModelFetchStatusSnafu, ModelPayloadParseSnafu, ProviderConfig,
-    ProviderError, ProviderResult, ProviderStreamHandle, ProviderWorker, Role, StreamEventMapped,
-    StreamEventPayload, StreamRequest, StreamTarget, make_event_stream,
+    BoxFuture, ContentPart, EmptyMessageSetSnafu, HttpClientSnafu, ImageDetail, LlmProvider,
+    MessageContent, MissingApiKeySnafu, ModelFetchStatusSnafu, ModelPayloadParseSnafu, OpenAiApi,
+    ProviderConfig, ProviderError, ProviderResult, ProviderStreamHandle, ProviderWorker, Role,
+    StopSequenceFilter, StopSequenceOutcome, StreamEventMapped, StreamEventPayload, StreamRequest,
+    StreamTarget, UnknownStreamSnafu, cached_tokenizer, estimate_message_tokens_with,
+    make_event_stream, next_stream_id,
 };
 
 pub const RIG_OPENAI_PROVIDER_ID: &str = "openai";
 
-type RigStreamingResponse = rig::streaming::StreamingCompletionResponse<
+const HTTP_STATUS_TOO_MANY_REQUESTS: u16 = 429;
+
+/// Abort senders for in-flight streams, keyed by `ProviderStreamHandle::stream_id`.
+type ActiveStreams = Arc<Mutex<HashMap<u64, oneshot::Sender<()>>>>;
+
+type RigResponsesStream = rig::streaming::StreamingCompletionResponse<
     rig::providers::openai::responses_api::streaming::StreamingCompletionResponse,
 >;
+type RigCompletionsStream = rig::streaming::StreamingCompletionResponse<
+    rig::providers::openai::completion::streaming::StreamingCompletionResponse,
+>;
+
+/// A Rig stream from whichever OpenAI endpoint served the request.
+enum RigStreamingResponse {
+    Responses(RigResponsesStream),
+    Completions(RigCompletionsStream),
+}
+
+impl RigStreamingResponse {
+    async fn next_event(
+        &mut self,
+        target: StreamTarget,
+    ) -> Option<Result<Option<StreamEventMapped>, rig::completion::CompletionError>> {
+        match self {
+            Self::Responses(stream) => stream
+                .next()
+                .await
+                .map(|item| item.map(|item| RigProviderAdapter::map_stream_item(target, item))),
+            Self::Completions(stream) => stream
+                .next()
+                .await
+                .map(|item| item.map(|item| RigProviderAdapter::map_stream_item(target, item))),
+        }
+    }
+
+    fn cancel(&self) {
+        match self {
+            Self::Responses(stream) => stream.cancel(),
+            Self::Completions(stream) => stream.cancel(),
+        }
+    }
+}
+
+type OpenAiClient = openai::Client<RigHttpClient>;
+
+/// The HTTP client behind every [`OpenAiClient`].
+///
+/// Rig turns each part of a user message into its own Responses API input item, so a
+/// text+image turn would reach OpenAI as two messages. Bodies bound for `/responses` are
+/// rewritten here so adjacent user message items share one `content` array.
+#[derive(Debug, Clone, Default)]
+struct RigHttpClient(http_client::ReqwestClient);
+
+impl RigHttpClient {
+    fn rewrite_body<T: Into<Bytes>>(request: Request<T>) -> Request<Bytes> {
+        let (parts, body) = request.into_parts();
+        let body = body.into();
+        if !parts.uri.path().ends_with("/responses") {
+            return Request::from_parts(parts, body);
+        }
+        let body = match serde_json::from_slice::<serde_json::Value>(&body) {
+            Ok(mut json) => {
+                merge_adjacent_user_input_items(&mut json);
+                match serde_json::to_vec(&json) {
+                    Ok(rewritten) => Bytes::from(rewritten),
+                    Err(_) => body,
+                }
+            }
+            Err(_) => body,
+        };
+        Request::from_parts(parts, body)
+    }
+}
+
+impl HttpClientExt for RigHttpClient {
+    fn send<T, U>(
+        &self,
+        request: Request<T>,
+    ) -> impl Future<Output = http_client::Result<http_client::Response<http_client::LazyBody<U>>>>
+    + WasmCompatSend
+    + 'static
+    where
+        T: Into<Bytes> + WasmCompatSend,
+        U: From<Bytes> + WasmCompatSend + 'static,
+    {
+        self.0.send(Self::rewrite_body(request))
+    }
+
+    fn send_multipart<U>(
+        &self,
+        request: Request<http_client::MultipartForm>,
+    ) -> impl Future<Output = http_client::Result<http_client::Response<http_client::LazyBody<U>>>>
+    + WasmCompatSend
+    + 'static
+    where
+        U: From<Bytes> + WasmCompatSend + 'static,
+    {
+        self.0.send_multipart(request)
+    }
+
+    fn send_streaming<T>(
+        &self,
+        request: Request<T>,
+    ) -> impl Future<Output = http_client::Result<http_client::StreamingResponse>> + WasmCompatSend
+    where
+        T: Into<Bytes>,
+    {
+        self.0.send_streaming(Self::rewrite_body(request))
+    }
+}
+
+/// Joins runs of adjacent user `message` items in a Responses API body into one item.
+///
+/// Back-to-back user turns carry no boundary the model relies on, so they merge too.
+fn merge_adjacent_user_input_items(body: &mut serde_json::Value) {
+    fn is_user_message(item: &serde_json::Value) -> bool {
+        item.get("type").and_then(serde_json::Value::as_str) == Some("message")
+            && item.get("role").and_then(serde_json::Value::as_str) == Some("user")
+    }
+
+    let Some(input) = body
+        .get_mut("input")
+        .and_then(serde_json::Value::as_array_mut)
+    else {
+        return;
+    };
+    let mut merged: Vec<serde_json::Value> = Vec::with_capacity(input.len());
+    for item in input.drain(..) {
+        if is_user_message(&item)
+            && let Some(previous) = merged.last_mut()
+            && is_user_message(previous)
+            && let Some(previous_content) = previous
+                .get_mut("content")
+                .and_then(serde_json::Value::as_array_mut)
+            && let Some(content) = item.get("content").and_then(serde_json::Value::as_array)
+        {
+            previous_content.extend(content.iter().cloned());
+            continue;
+        }
+        merged.push(item);
+    }
+    *input = merged;
+}
+
+/// Removes a stream's `active_streams` entry once its worker finishes or is dropped.
+struct ActiveStreamGuard {
+    active_streams: ActiveStreams,
+    stream_id: u64,
+}
+
+impl Drop for ActiveStreamGuard {
+    fn drop(&mut self) {
+        self.active_streams
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&self.stream_id);
+    }
+}
+
+/// The client and endpoint a stream worker sends its request through.
+struct StreamEndpoint {
+    provider_id: String,
+    client: Arc<OpenAiClient>,
+    api: OpenAiApi,
+}
 
 pub struct RigProviderAdapter {
     config: ProviderConfig,
     fallback_models: Vec<Model>,
     model_cache: Arc<ModelCache>,
     model_cache_key: String,
-    client: Arc<openai::Client>,
+    client: Arc<OpenAiClient>,
+    active_streams: ActiveStreams,
 }
 
 struct HttpClientCache {
-    clients: RwLock<HashMap<String, Arc<openai::Client>>>,
+    clients: RwLock<HashMap<String, Arc<OpenAiClient>>>,
 }
 
 impl HttpClientCache {
@@ -45,7 +215,7 @@ impl HttpClientCache {
         }
     }
 
-    fn get_or_create(&self, config: &ProviderConfig) -> ProviderResult<Arc<openai::Client>> {
+    fn get_or_create(&self, config: &ProviderConfig) -> ProviderResult<Arc<OpenAiClient>> {
         let cache_key = config.cache_key();
 
         if let Some(client) = self
@@ -98,14 +268,26 @@ impl RigProviderAdapter {
             model_cache: get_model_cache(),
             model_cache_key,
             client,
+            active_streams: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
-    fn build_client(config: &ProviderConfig) -> ProviderResult<openai::Client> {
-        let mut builder = openai::Client::builder().api_key(config.api_key.as_str());
+    fn build_client(config: &ProviderConfig) -> ProviderResult<OpenAiClient> {
+        let mut builder = OpenAiClient::builder().api_key(config.api_key.as_str());
         if !config.endpoint.is_empty() {
             builder = builder.base_url(config.endpoint.as_str());
         }
+        if !config.extra_headers.is_empty() {
+            let headers =
+                http_client::HeaderMap::try_from(&config.extra_headers).map_err(|error| {
+                    ProviderError::InvalidConfig {
+                        stage: "build-client-extra-headers",
+                        field: "extra_headers",
+                        reason: format!("custom headers are not valid HTTP headers: {error}"),
+                    }
+                })?;
+            builder = builder.http_headers(headers);
+        }
         builder.build().context(HttpClientSnafu {
             stage: "build-client",
         })
@@ -127,7 +309,38 @@ impl RigProviderAdapter {
         let response = self.client.send(request).await.context(HttpClientSnafu {
             stage: "send-model-request",
         })?;
+        Self::models_from_response(response).await
+    }
+
+    async fn fetch_models_from_catalog(&self, url: &str) -> ProviderResult<Vec<Model>> {
+        // Start from a provider request so auth and custom headers carry over, then point it
+        // at the absolute catalog URL instead of the endpoint.
+        let request = self
+            .client
+            .get("/models")
+            .context(HttpClientSnafu {
+                stage: "build-remote-catalog-request",
+            })?
+            .uri(url)
+            .body(NoBody)
+            .map_err(|source| ProviderError::BuildHttpRequestBody {
+                stage: "build-remote-catalog-request-body",
+                message: source.to_string(),
+            })?;
+
+        let response = self.client.send(request).await.context(HttpClientSnafu {
+            stage: "send-remote-catalog-request",
+        })?;
+        Self::models_from_response(response).await
+    }
+
+    async fn models_from_response(
+        response: http_client::Response<http_client::LazyBody<Vec<u8>>>,
+    ) -> ProviderResult<Vec<Model>> {
         let status = response.status();
+        if let Some(error) = rate_limit_from_response(status.as_u16(), response.headers()) {
+            return Err(error);
+        }
         let payload = http_client::text(response).await.context(HttpClientSnafu {
             stage: "read-model-response",
         })?;
@@ -178,10 +391,34 @@ impl RigProviderAdapter {
     }
 
     fn to_rig_message(message: &super::provider::ProviderMessage) -> Option<RigMessage> {
-        match message.role {
-            Role::System => None,
-            Role::User => Some(RigMessage::user(message.content.clone())),
-            Role::Assistant => Some(RigMessage::assistant(message.content.clone())),
+        match (message.role, &message.content) {
+            (Role::System, _) => None,
+            (Role::User, MessageContent::Parts(parts)) => {
+                let content = parts
+                    .iter()
+                    .map(Self::to_rig_user_content)
+                    .collect::<Vec<_>>();
+                OneOrMany::many(content)
+                    .ok()
+                    .map(|content| RigMessage::User { content })
+            }
+            (Role::User, content) => Some(RigMessage::user(content.text())),
+            // Assistant turns cannot carry input images, so only their text is replayed.
+            (Role::Assistant, content) => Some(RigMessage::assistant(content.text())),
+        }
+    }
+
+    fn to_rig_user_content(part: &ContentPart) -> UserContent {
+        match part {
+            ContentPart::Text(text) => UserContent::text(text.clone()),
+            ContentPart::ImageUrl { url, detail } => {
+                let detail = match detail {
+                    ImageDetail::Low => RigImageDetail::Low,
+                    ImageDetail::High => RigImageDetail::High,
+                    ImageDetail::Auto => RigImageDetail::Auto,
+                };
+                UserContent::image_url(url.clone(), None, Some(detail))
+            }
         }
     }
 
@@ -197,8 +434,9 @@ impl RigProviderAdapter {
         // Rig exposes a single preamble field, so system-role messages are folded into it
         // to preserve caller intent while still sending user/assistant turns as chat messages.
         for message in &request.messages {
-            if matches!(message.role, Role::System) && !message.content.trim().is_empty() {
-                preamble_parts.push(message.content.clone());
+            let content = message.content.text();
+            if matches!(message.role, Role::System) && !content.trim().is_empty() {
+                preamble_parts.push(content);
             }
         }
 
@@ -210,10 +448,10 @@ impl RigProviderAdapter {
     }
 
     async fn open_stream(
-        client: &openai::Client,
+        endpoint: &StreamEndpoint,
         request: &StreamRequest,
     ) -> ProviderResult<RigStreamingResponse> {
-        let model = client.completion_model(request.model_id.clone());
+        let model = endpoint.client.completion_model(request.model_id.clone());
 
         let mut messages = request
             .messages
@@ -269,8 +507,37 @@ impl RigProviderAdapter {
             builder = builder.max_tokens(max_tokens);
         }
 
-        builder.stream().await.context(CompletionsFailedSnafu {
-            stage: "open-stream",
+        let opened = match endpoint.api {
+            OpenAiApi::Responses => builder.stream().await.map(RigStreamingResponse::Responses),
+            OpenAiApi::ChatCompletions => {
+                // Rig's Chat Completions request has no `max_tokens` or `stop` field, so both
+                // ride along as raw parameters.
+                let mut completion_request = builder.build();
+                let mut params = serde_json::Map::new();
+                if let Some(max_tokens) = request.max_tokens {
+                    params.insert("max_tokens".to_string(), max_tokens.into());
+                }
+                if !request.stop_sequences.is_empty() {
+                    params.insert("stop".to_string(), request.stop_sequences.clone().into());
+                }
+                if !params.is_empty() {
+                    completion_request.additional_params = Some(params.into());
+                }
+                model
+                    .completions_api()
+                    .stream(completion_request)
+                    .await
+                    .map(RigStreamingResponse::Completions)
+            }
+        };
+
+        opened.map_err(|source| {
+            completion_rate_limit(&source, "open-stream-rate-limited").unwrap_or_else(|| {
+                ProviderError::CompletionsFailed {
+                    stage: "open-stream",
+                    source,
+                }
+            })
         })
     }
 
@@ -279,10 +546,16 @@ impl RigProviderAdapter {
         target: StreamTarget,
         error: ProviderError,
     ) {
-        let _ = event_tx.send(StreamEventMapped {
-            target,
-            payload: StreamEventPayload::Error(error.to_string()),
-        });
+        let payload = match error {
+            ProviderError::RateLimited {
+                retry_after_seconds,
+                ..
+            } => StreamEventPayload::RateLimited {
+                retry_after_seconds,
+            },
+            error => StreamEventPayload::Error(error.to_string()),
+        };
+        let _ = event_tx.send(StreamEventMapped { target, payload });
     }
 
     fn map_stream_item<R>(
@@ -317,19 +590,28 @@ impl RigProviderAdapter {
     }
 
     async fn run_stream_worker(
-        provider_id: String,
-        client: Arc<openai::Client>,
+        endpoint: StreamEndpoint,
         request: StreamRequest,
         event_tx: mpsc::UnboundedSender<StreamEventMapped>,
         mut cancel_rx: oneshot::Receiver<()>,
+        mut abort_rx: oneshot::Receiver<()>,
+        cancel_token: CancellationToken,
     ) {
         let target = request.target;
-        let mut stream = match Self::open_stream(&client, &request).await {
+        let opened = tokio::select! {
+            // Prefer an already-requested cancellation over starting the HTTP request.
+            biased;
+            _ = &mut cancel_rx => return,
+            _ = &mut abort_rx => return,
+            _ = cancel_token.cancelled() => return,
+            opened = Self::open_stream(&endpoint, &request) => opened,
+        };
+        let mut stream = match opened {
             Ok(stream) => stream,
             Err(error) => {
                 tracing::error!(
                     target = ?target,
-                    provider_id = %provider_id,
+                    provider_id = %endpoint.provider_id,
                     model_id = %request.model_id,
                     error = %error,
                     "failed to open provider stream"
@@ -341,6 +623,9 @@ impl RigProviderAdapter {
 
         let mut cancelled = false;
         let mut stream_failed = false;
+        // The Responses API has no `stop`, and Chat Completions servers may ignore it or stream
+        // past it, so stop sequences are always enforced here.
+        let mut stop_filter = StopSequenceFilter::new(&request.stop_sequences);
 
         loop {
             tokio::select! {
@@ -351,14 +636,48 @@ impl RigProviderAdapter {
                     stream.cancel();
                     break;
                 }
-                next_item = stream.next() => {
+                _ = &mut abort_rx => {
+                    cancelled = true;
+                    tracing::debug!(target = ?target, "provider stream aborted via cancel_stream");
+                    stream.cancel();
+                    break;
+                }
+                _ = cancel_token.cancelled() => {
+                    cancelled = true;
+                    tracing::debug!(target = ?target, "provider stream aborted via dropped handle");
+                    stream.cancel();
+                    break;
+                }
+                next_item = stream.next_event(target) => {
                     match next_item {
-                        Some(Ok(item)) => {
-                            if let Some(mapped) = Self::map_stream_item(target, item)
-                                && event_tx.send(mapped).is_err()
-                            {
+                        Some(Ok(mapped)) => {
+                            let Some(mut mapped) = mapped else {
+                                continue;
+                            };
+                            let mut reached_stop = false;
+                            if let StreamEventPayload::Delta(text) = &mapped.payload {
+                                let filtered = match stop_filter.push(text) {
+                                    StopSequenceOutcome::Continue(filtered) => filtered,
+                                    StopSequenceOutcome::Stopped(filtered) => {
+                                        reached_stop = true;
+                                        filtered
+                                    }
+                                };
+                                mapped.payload = StreamEventPayload::Delta(filtered);
+                            }
+
+                            let is_empty_delta = matches!(
+                                &mapped.payload,
+                                StreamEventPayload::Delta(text) if text.is_empty()
+                            );
+                            if !is_empty_delta && event_tx.send(mapped).is_err() {
                                 return;
                             }
+                            if reached_stop {
+                                tracing::debug!(target = ?target, "provider stream reached a stop sequence");
+                                stream.cancel();
+                                break;
+                            }
                         }
                         Some(Err(source)) => {
                             stream_failed = true;
@@ -367,10 +686,11 @@ impl RigProviderAdapter {
                                 error = %source,
                                 "provider stream emitted an error chunk"
                             );
-                            let error = ProviderError::CompletionsFailed {
-                                stage: "stream-chunk",
-                                source,
-                            };
+                            let error = completion_rate_limit(&source, "stream-chunk-rate-limited")
+                                .unwrap_or_else(|| ProviderError::CompletionsFailed {
+                                    stage: "stream-chunk",
+                                    source,
+                                });
                             Self::emit_error_event(&event_tx, target, error);
                             break;
                         }
@@ -381,6 +701,13 @@ impl RigProviderAdapter {
         }
 
         if !cancelled && !stream_failed {
+            let held_back = stop_filter.finish();
+            if !held_back.is_empty() {
+                let _ = event_tx.send(StreamEventMapped {
+                    target,
+                    payload: StreamEventPayload::Delta(held_back),
+                });
+            }
             let _ = event_tx.send(StreamEventMapped {
                 target,
                 payload: StreamEventPayload::Done,
@@ -389,6 +716,46 @@ impl RigProviderAdapter {
     }
 }
 
+fn rate_limit_from_response(
+    status: u16,
+    headers: &http_client::HeaderMap,
+) -> Option<ProviderError> {
+    (status == HTTP_STATUS_TOO_MANY_REQUESTS).then(|| ProviderError::RateLimited {
+        stage: "model-http-rate-limited",
+        retry_after_seconds: parse_retry_after(headers),
+    })
+}
+
+/// Reads a delta-seconds `Retry-After` header; HTTP-date values are ignored.
+fn parse_retry_after(headers: &http_client::HeaderMap) -> Option<u64> {
+    headers
+        .get("retry-after")?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Rig drops response headers on streaming failures, so only the status survives here.
+fn completion_rate_limit(
+    error: &rig::completion::CompletionError,
+    stage: &'static str,
+) -> Option<ProviderError> {
+    let rig::completion::CompletionError::HttpError(
+        http_client::Error::InvalidStatusCode(status)
+        | http_client::Error::InvalidStatusCodeWithMessage(status, _),
+    ) = error
+    else {
+        return None;
+    };
+
+    (status.as_u16() == HTTP_STATUS_TOO_MANY_REQUESTS).then_some(ProviderError::RateLimited {
+        stage,
+        retry_after_seconds: None,
+    })
+}
+
 impl LlmProvider for RigProviderAdapter {
     fn id(&self) -> &str {
         &self.config.provider_id
@@ -411,12 +778,19 @@ impl LlmProvider for RigProviderAdapter {
             if let Some(models) = self.model_cache.get_fresh(&self.model_cache_key).await {
                 return Ok(ModelCatalog::from_cache_fresh(models));
             }
+            self.refresh_models().await
+        })
+    }
 
+    fn refresh_models<'a>(&'a self) -> BoxFuture<'a, ProviderResult<ModelCatalog>> {
+        Box::pin(async move {
             // Fallback order intentionally prefers availability over strict freshness:
             // provider API first, then stale cache, then static defaults.
             match self.fetch_models_from_provider().await {
                 Ok(models) => {
-                    self.model_cache.set(self.id(), models.clone()).await;
+                    self.model_cache
+                        .set(&self.model_cache_key, models.clone())
+                        .await;
                     Ok(ModelCatalog::from_provider_api(models))
                 }
                 Err(error) => {
@@ -448,6 +822,17 @@ impl LlmProvider for RigProviderAdapter {
         })
     }
 
+    fn cache_key(&self) -> &str {
+        &self.model_cache_key
+    }
+
+    fn fetch_models_from_url<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> BoxFuture<'a, ProviderResult<Vec<Model>>> {
+        Box::pin(self.fetch_models_from_catalog(url))
+    }
+
     fn stream_chat(&self, request: StreamRequest) -> ProviderResult<ProviderStreamHandle> {
         ensure!(
             !request.messages.is_empty(),
@@ -458,14 +843,953 @@ impl LlmProvider for RigProviderAdapter {
         );
 
         let (event_tx, stream, cancel_rx) = make_event_stream(request.target);
-        let worker: ProviderWorker = Box::pin(Self::run_stream_worker(
-            self.config.provider_id.clone(),
-            self.client.clone(),
-            request,
-            event_tx,
-            cancel_rx,
+        let stream_id = next_stream_id();
+        let (abort_tx, abort_rx) = oneshot::channel();
+        self.active_streams
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(stream_id, abort_tx);
+
+        let endpoint = StreamEndpoint {
+            provider_id: self.config.provider_id.clone(),
+            client: self.client.clone(),
+            api: self.config.api,
+        };
+        let active_streams = self.active_streams.clone();
+        let cancel_token = CancellationToken::new();
+        let worker_cancel_token = cancel_token.clone();
+        let active_stream = ActiveStreamGuard {
+            active_streams,
+            stream_id,
+        };
+        let worker: ProviderWorker = Box::pin(async move {
+            // Owned by the future, so the entry also goes when the worker is dropped unpolled.
+            let _active_stream = active_stream;
+            Self::run_stream_worker(
+                endpoint,
+                request,
+                event_tx,
+                cancel_rx,
+                abort_rx,
+                worker_cancel_token,
+            )
+            .await;
+        });
+
+        let handle = ProviderStreamHandle {
+            stream_id,
+            stream,
+            worker,
+            cancel_token,
+        };
+        Ok(match self.config.stream_timeout {
+            Some(timeout) => handle.with_timeout(timeout),
+            None => handle,
+        })
+    }
+
+    fn estimate_message_tokens(
+        &self,
+        model_id: &str,
+        messages: &[super::provider::ProviderMessage],
+    ) -> usize {
+        // Newer OpenAI models moved to o200k_base; model ids tiktoken does not recognize are
+        // counted with cl100k_base, which most OpenAI-compatible chat models share.
+        let tokenizer = tiktoken_rs::tokenizer::get_tokenizer(model_id)
+            .and_then(cached_tokenizer)
+            .or_else(|| cached_tokenizer(tiktoken_rs::tokenizer::Tokenizer::Cl100kBase));
+        estimate_message_tokens_with(tokenizer, messages)
+    }
+
+    fn cancel_stream(&self, stream_id: u64) -> ProviderResult<()> {
+        let abort_tx = self
+            .active_streams
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&stream_id)
+            .context(UnknownStreamSnafu {
+                stage: "cancel-stream",
+                stream_id,
+            })?;
+        // The worker may already be winding down; a closed receiver still means it is gone.
+        let _ = abort_tx.send(());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use rig::http_client::{HeaderMap, HeaderValue};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::{
+        ProviderError, RigProviderAdapter, merge_adjacent_user_input_items,
+        rate_limit_from_response,
+    };
+    use crate::model::{ModelCatalogSource, get_model_cache};
+    use crate::provider::{
+        ConversationId, LlmProvider, OpenAiApi, ProviderConfig, ProviderMessage,
+        StreamEventPayload, StreamRequest, StreamSessionId, StreamTarget,
+    };
+
+    #[::core::prelude::v1::test]
+    fn rate_limited_response_carries_retry_after_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert("Retry-After", HeaderValue::from_static("5"));
+
+        let error = rate_limit_from_response(429, &headers);
+        assert!(matches!(
+            error,
+            Some(ProviderError::RateLimited {
+                retry_after_seconds: Some(5),
+                ..
+            })
+        ));
+        assert!(rate_limit_from_response(500, &headers).is_none());
+    }
+
+    #[::core::prelude::v1::test]
+    fn rate_limited_response_without_header_has_no_delay() {
+        let error = rate_limit_from_response(429, &HeaderMap::new());
+        assert!(matches!(
+            error,
+            Some(ProviderError::RateLimited {
+                retry_after_seconds: None,
+                ..
+            })
+        ));
+    }
+
+    #[::core::prelude::v1::test]
+    fn cancel_stream_aborts_worker_and_rejects_repeat_cancel() {
+        let adapter = RigProviderAdapter::new(ProviderConfig::new(
+            "openai",
+            "test-key",
+            "http://127.0.0.1:9",
+        ));
+        let Ok(adapter) = adapter else {
+            panic!("adapter should build without network access");
+        };
+        let target = StreamTarget {
+            conversation_id: ConversationId::new(1),
+            session_id: StreamSessionId::new(1),
+        };
+        let request = StreamRequest::new(
+            target,
+            "gpt-4o-mini",
+            vec![ProviderMessage::new(crate::provider::Role::User, "hello")],
+        );
+        let Ok(mut handle) = adapter.stream_chat(request) else {
+            panic!("stream_chat should accept a non-empty request");
+        };
+
+        assert!(adapter.cancel_stream(handle.stream_id).is_ok());
+        assert!(matches!(
+            adapter.cancel_stream(handle.stream_id),
+            Err(ProviderError::UnknownStream { .. })
+        ));
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build();
+        let Ok(runtime) = runtime else {
+            panic!("test runtime should build");
+        };
+        runtime.block_on(handle.worker);
+        assert!(handle.stream.try_recv().is_none());
+    }
+
+    fn active_stream_count(adapter: &RigProviderAdapter) -> usize {
+        adapter
+            .active_streams
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .len()
+    }
+
+    #[::core::prelude::v1::test]
+    fn dropping_an_unpolled_handle_forgets_the_stream() {
+        let adapter = RigProviderAdapter::new(ProviderConfig::new(
+            "openai",
+            "test-key",
+            "http://127.0.0.1:9",
+        ));
+        let Ok(adapter) = adapter else {
+            panic!("adapter should build without network access");
+        };
+        let request = StreamRequest::new(
+            test_target(),
+            "gpt-4o-mini",
+            vec![ProviderMessage::new(crate::provider::Role::User, "hello")],
+        );
+        let Ok(handle) = adapter.stream_chat(request) else {
+            panic!("stream_chat should accept a non-empty request");
+        };
+        let stream_id = handle.stream_id;
+        assert_eq!(active_stream_count(&adapter), 1);
+
+        drop(handle);
+
+        assert_eq!(active_stream_count(&adapter), 0);
+        assert!(matches!(
+            adapter.cancel_stream(stream_id),
+            Err(ProviderError::UnknownStream { .. })
+        ));
+    }
+
+    /// Accepts connections, answers with an event-stream header that never ends, and tracks how
+    /// many connections the client still holds open.
+    async fn spawn_hanging_stream_server(open_connections: Arc<AtomicUsize>) -> String {
+        let Ok(listener) = TcpListener::bind("127.0.0.1:0").await else {
+            panic!("mock server should bind a local port");
+        };
+        let Ok(address) = listener.local_addr() else {
+            panic!("mock server should report its address");
+        };
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let open_connections = open_connections.clone();
+                open_connections.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut buffer = [0_u8; 4096];
+                    let _ = socket.read(&mut buffer).await;
+                    let _ = socket
+                        .write_all(
+                            b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n",
+                        )
+                        .await;
+                    while let Ok(read) = socket.read(&mut buffer).await {
+                        if read == 0 {
+                            break;
+                        }
+                    }
+                    open_connections.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+
+        format!("http://{address}")
+    }
+
+    async fn wait_for_open_connections(open_connections: &AtomicUsize, expected: usize) -> bool {
+        for _ in 0..200 {
+            if open_connections.load(Ordering::SeqCst) == expected {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        false
+    }
+
+    #[::core::prelude::v1::test]
+    fn dropping_abort_handle_closes_upstream_connection() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build();
+        let Ok(runtime) = runtime else {
+            panic!("test runtime should build");
+        };
+
+        runtime.block_on(async {
+            let open_connections = Arc::new(AtomicUsize::new(0));
+            let base_url = spawn_hanging_stream_server(open_connections.clone()).await;
+            let Ok(adapter) =
+                RigProviderAdapter::new(ProviderConfig::new("openai", "test-key", &base_url))
+            else {
+                panic!("adapter should build for the mock server");
+            };
+            let target = StreamTarget {
+                conversation_id: ConversationId::new(1),
+                session_id: StreamSessionId::new(1),
+            };
+            let request = StreamRequest::new(
+                target,
+                "gpt-4o-mini",
+                vec![ProviderMessage::new(crate::provider::Role::User, "hello")],
+            );
+            let Ok(handle) = adapter.stream_chat(request) else {
+                panic!("stream_chat should accept a non-empty request");
+            };
+
+            let mut handle = handle.abort_on_drop();
+            let Some(worker) = handle.take_worker() else {
+                panic!("a fresh handle should still own its worker");
+            };
+            // Keep the event stream alive so only the abort handle can cancel the worker.
+            let _stream = handle.take_stream();
+            let worker_task = tokio::spawn(worker);
+
+            assert!(wait_for_open_connections(&open_connections, 1).await);
+            drop(handle);
+            assert!(wait_for_open_connections(&open_connections, 0).await);
+            assert!(worker_task.await.is_ok());
+        });
+    }
+
+    #[::core::prelude::v1::test]
+    fn config_stream_timeout_fails_silent_stream() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build();
+        let Ok(runtime) = runtime else {
+            panic!("test runtime should build");
+        };
+
+        runtime.block_on(async {
+            let open_connections = Arc::new(AtomicUsize::new(0));
+            let base_url = spawn_hanging_stream_server(open_connections.clone()).await;
+            let config = ProviderConfig::new("openai", "test-key", &base_url)
+                .with_stream_timeout(Duration::from_millis(200));
+            let Ok(provider) = crate::create_provider(config) else {
+                panic!("provider should build for the mock server");
+            };
+            let target = StreamTarget {
+                conversation_id: ConversationId::new(1),
+                session_id: StreamSessionId::new(1),
+            };
+            let request = StreamRequest::new(
+                target,
+                "gpt-4o-mini",
+                vec![ProviderMessage::new(crate::provider::Role::User, "hello")],
+            );
+            let Ok(mut handle) = provider.stream_chat(request) else {
+                panic!("stream_chat should accept a non-empty request");
+            };
+            let worker_task = tokio::spawn(handle.worker);
+
+            let event = tokio::time::timeout(Duration::from_secs(5), handle.stream.recv()).await;
+            let Ok(Some(event)) = event else {
+                panic!("timed out stream should report an error event, got {event:?}");
+            };
+            assert_eq!(event.target, target);
+            assert!(
+                matches!(&event.payload, StreamEventPayload::Error(message) if message.contains("200ms")),
+                "unexpected payload: {:?}",
+                event.payload
+            );
+            assert!(handle.stream.recv().await.is_none());
+            assert!(wait_for_open_connections(&open_connections, 0).await);
+            assert!(worker_task.await.is_ok());
+        });
+    }
+
+    #[::core::prelude::v1::test]
+    fn extra_headers_are_sent_with_model_requests() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build();
+        let Ok(runtime) = runtime else {
+            panic!("test runtime should build");
+        };
+
+        runtime.block_on(async {
+            let Ok(listener) = TcpListener::bind("127.0.0.1:0").await else {
+                panic!("mock server should bind a local port");
+            };
+            let Ok(address) = listener.local_addr() else {
+                panic!("mock server should report its address");
+            };
+            // The mock only serves models when the gateway header is present, mirroring an
+            // enterprise proxy that rejects unauthenticated tenants.
+            let server = tokio::spawn(async move {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return false;
+                };
+                let mut buffer = [0_u8; 4096];
+                let read = socket.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]).to_ascii_lowercase();
+                let has_header = request.contains("\r\nx-custom: test\r\n");
+                let (status, body) = if has_header {
+                    ("200 OK", r#"{"data":[{"id":"gateway-model"}]}"#)
+                } else {
+                    ("400 Bad Request", r#"{"error":"missing header"}"#)
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                has_header
+            });
+
+            let config = ProviderConfig::new("openai", "test-key", format!("http://{address}"))
+                .with_header("X-Custom", "test");
+            let Ok(adapter) = RigProviderAdapter::new(config) else {
+                panic!("adapter should build with a valid custom header");
+            };
+            let catalog = adapter.fetch_models().await;
+
+            assert!(matches!(server.await, Ok(true)));
+            let Ok(catalog) = catalog else {
+                panic!("fetch_models should fall back instead of failing");
+            };
+            assert_eq!(catalog.source, ModelCatalogSource::ProviderApi);
+            assert_eq!(catalog.models.len(), 1);
+        });
+    }
+
+    #[::core::prelude::v1::test]
+    fn remote_catalogs_use_provider_credentials_and_cache_key() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build();
+        let Ok(runtime) = runtime else {
+            panic!("test runtime should build");
+        };
+
+        runtime.block_on(async {
+            let Ok(listener) = TcpListener::bind("127.0.0.1:0").await else {
+                panic!("mock server should bind a local port");
+            };
+            let Ok(address) = listener.local_addr() else {
+                panic!("mock server should report its address");
+            };
+            let server = tokio::spawn(async move {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return false;
+                };
+                let mut buffer = [0_u8; 4096];
+                let read = socket.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]).to_ascii_lowercase();
+                let authorized = request.starts_with("get /catalog.json ")
+                    && request.contains("\r\nx-custom: test\r\n")
+                    && request.contains("\r\nauthorization: bearer test-key\r\n");
+                let (status, body) = if authorized {
+                    ("200 OK", r#"{"data":[{"id":"catalog-model"}]}"#)
+                } else {
+                    ("401 Unauthorized", r#"{"error":"missing credentials"}"#)
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                authorized
+            });
+
+            // The catalog lives on a different host than the chat endpoint.
+            let config = ProviderConfig::new("openai", "test-key", "http://127.0.0.1:9")
+                .with_header("X-Custom", "test");
+            let Ok(adapter) = RigProviderAdapter::new(config) else {
+                panic!("adapter should build with a valid custom header");
+            };
+            let adapter = Arc::new(adapter);
+            let remote = ModelCatalogSource::Remote {
+                url: format!("http://{address}/catalog.json"),
+            };
+            let cache = get_model_cache();
+            let catalog = cache.get_or_fetch(&remote, adapter.clone()).await;
+
+            assert!(matches!(server.await, Ok(true)));
+            let Ok(catalog) = catalog else {
+                panic!("remote catalog should load: {catalog:?}");
+            };
+            assert_eq!(catalog.source, remote);
+            assert!(catalog.is_available("catalog-model"));
+            assert!(cache.invalidate(adapter.as_ref()).await);
+        });
+    }
+
+    #[::core::prelude::v1::test]
+    fn invalid_extra_header_is_rejected() {
+        let config = ProviderConfig::new("openai", "test-key", "http://127.0.0.1:9")
+            .with_header("bad header", "value");
+        assert!(matches!(
+            RigProviderAdapter::new(config),
+            Err(ProviderError::InvalidConfig {
+                field: "extra_headers",
+                ..
+            })
         ));
+    }
+
+    #[::core::prelude::v1::test]
+    fn multipart_user_message_serializes_to_openai_content_items() {
+        let message = ProviderMessage::new_multipart(
+            crate::provider::Role::User,
+            "What is in this picture?",
+            "https://example.com/cat.png",
+        );
+        let Some(rig_message) = RigProviderAdapter::to_rig_message(&message) else {
+            panic!("user messages should convert to rig messages");
+        };
+        let Ok(items) =
+            Vec::<rig::providers::openai::responses_api::InputItem>::try_from(rig_message)
+        else {
+            panic!("multipart user content should be accepted by the responses API");
+        };
+        let Ok(input) = serde_json::to_value(&items) else {
+            panic!("responses API input items should serialize");
+        };
+        let mut body = serde_json::json!({ "input": input });
+        merge_adjacent_user_input_items(&mut body);
+
+        assert_eq!(
+            body["input"],
+            serde_json::json!([{
+                "role": "user",
+                "type": "message",
+                "content": [
+                    { "type": "input_text", "text": "What is in this picture?" },
+                    {
+                        "type": "input_image",
+                        "image_url": "https://example.com/cat.png",
+                        "detail": "auto"
+                    }
+                ]
+            }])
+        );
+    }
+
+    #[::core::prelude::v1::test]
+    fn merging_user_input_items_keeps_assistant_turns_apart() {
+        let mut body = serde_json::json!({
+            "input": [
+                { "type": "message", "role": "user", "content": [{ "type": "input_text", "text": "a" }] },
+                { "type": "message", "role": "user", "content": [{ "type": "input_text", "text": "b" }] },
+                { "type": "message", "role": "assistant", "content": [{ "type": "output_text", "text": "c" }] },
+                { "type": "message", "role": "user", "content": [{ "type": "input_text", "text": "d" }] }
+            ]
+        });
+        merge_adjacent_user_input_items(&mut body);
+
+        let Some(input) = body["input"].as_array() else {
+            panic!("input should stay an array");
+        };
+        let roles = input
+            .iter()
+            .map(|item| item["role"].as_str().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(roles, ["user", "assistant", "user"]);
+        assert_eq!(
+            input[0]["content"],
+            serde_json::json!([
+                { "type": "input_text", "text": "a" },
+                { "type": "input_text", "text": "b" }
+            ])
+        );
+    }
+
+    /// Reads one HTTP request from the socket, waiting for the full `content-length` body.
+    async fn read_http_request(socket: &mut tokio::net::TcpStream) -> String {
+        let mut request = Vec::new();
+        let mut buffer = [0_u8; 4096];
+        loop {
+            let read = socket.read(&mut buffer).await.unwrap_or(0);
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request).to_ascii_lowercase();
+            let Some(header_end) = text.find("\r\n\r\n") else {
+                continue;
+            };
+            let content_length = text[..header_end]
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .and_then(|value| value.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+            if request.len() >= header_end + 4 + content_length {
+                break;
+            }
+        }
+        String::from_utf8_lossy(&request).into_owned()
+    }
+
+    /// Serves one request with the given SSE `data:` payloads and yields the raw request.
+    async fn spawn_sse_server(events: Vec<String>) -> (String, tokio::task::JoinHandle<String>) {
+        let Ok(listener) = TcpListener::bind("127.0.0.1:0").await else {
+            panic!("mock server should bind a local port");
+        };
+        let Ok(address) = listener.local_addr() else {
+            panic!("mock server should report its address");
+        };
+        let server = tokio::spawn(async move {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                return String::new();
+            };
+            let request = read_http_request(&mut socket).await;
+            let body = events
+                .iter()
+                .map(|event| format!("data: {event}\n\n"))
+                .collect::<String>();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nconnection: close\r\n\r\n{body}"
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+            request
+        });
+        (format!("http://{address}"), server)
+    }
+
+    fn chat_completions_delta(text: &str) -> String {
+        serde_json::json!({ "choices": [{ "delta": { "content": text }, "finish_reason": null }] })
+            .to_string()
+    }
+
+    fn responses_delta(sequence_number: u64, text: &str) -> String {
+        serde_json::json!({
+            "type": "response.output_text.delta",
+            "item_id": "msg_1",
+            "output_index": 0,
+            "content_index": 0,
+            "sequence_number": sequence_number,
+            "delta": text,
+        })
+        .to_string()
+    }
+
+    /// Runs `request` through `config` and collects every payload until the stream closes.
+    async fn collect_stream_payloads(
+        config: ProviderConfig,
+        request: StreamRequest,
+    ) -> Vec<StreamEventPayload> {
+        let target = request.target;
+        let Ok(adapter) = RigProviderAdapter::new(config) else {
+            panic!("adapter should build for the mock server");
+        };
+        let Ok(mut handle) = adapter.stream_chat(request) else {
+            panic!("stream_chat should accept a non-empty request");
+        };
+        let worker_task = tokio::spawn(handle.worker);
+
+        let mut payloads = Vec::new();
+        while let Ok(Some(event)) =
+            tokio::time::timeout(Duration::from_secs(5), handle.stream.recv()).await
+        {
+            assert_eq!(event.target, target);
+            payloads.push(event.payload);
+        }
+        assert!(worker_task.await.is_ok());
+        payloads
+    }
+
+    fn request_body_json(request: &str) -> serde_json::Value {
+        let Some((_, body)) = request.split_once("\r\n\r\n") else {
+            panic!("request should have a body: {request}");
+        };
+        let Ok(json) = serde_json::from_str(body) else {
+            panic!("request body should be JSON: {body}");
+        };
+        json
+    }
+
+    fn test_target() -> StreamTarget {
+        StreamTarget {
+            conversation_id: ConversationId::new(1),
+            session_id: StreamSessionId::new(1),
+        }
+    }
+
+    fn current_thread_runtime() -> tokio::runtime::Runtime {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build();
+        let Ok(runtime) = runtime else {
+            panic!("test runtime should build");
+        };
+        runtime
+    }
+
+    #[::core::prelude::v1::test]
+    fn stop_sequences_are_sent_and_held_back_text_is_flushed() {
+        current_thread_runtime().block_on(async {
+            // The last chunk ends in "EN", which could still become the "END" stop sequence.
+            let events = ["Hello ", "there", "EN"]
+                .into_iter()
+                .map(chat_completions_delta)
+                .chain(std::iter::once("[DONE]".to_string()))
+                .collect();
+            let (endpoint, server) = spawn_sse_server(events).await;
+
+            let config = ProviderConfig::new("openai", "test-key", endpoint)
+                .with_api(OpenAiApi::ChatCompletions);
+            let request = StreamRequest::new(
+                test_target(),
+                "gpt-4o-mini",
+                vec![ProviderMessage::new(crate::provider::Role::User, "hello")],
+            )
+            .with_stop_sequences(["END"]);
+            let payloads = collect_stream_payloads(config, request).await;
+
+            let Ok(request) = server.await else {
+                panic!("mock server task should finish");
+            };
+            assert!(request.starts_with("POST /chat/completions "), "{request}");
+            assert!(request.contains(r#""stop":["END"]"#), "{request}");
+            assert_eq!(
+                payloads,
+                vec![
+                    StreamEventPayload::Delta("Hello ".to_string()),
+                    StreamEventPayload::Delta("there".to_string()),
+                    StreamEventPayload::Delta("EN".to_string()),
+                    StreamEventPayload::Done,
+                ]
+            );
+        });
+    }
+
+    #[::core::prelude::v1::test]
+    fn responses_api_enforces_stop_sequences_client_side() {
+        current_thread_runtime().block_on(async {
+            let events = vec![
+                responses_delta(1, "Hello "),
+                responses_delta(2, "thereEND more"),
+            ];
+            let (endpoint, server) = spawn_sse_server(events).await;
+
+            let request = StreamRequest::new(
+                test_target(),
+                "gpt-4o-mini",
+                vec![ProviderMessage::new(crate::provider::Role::User, "hello")],
+            )
+            .with_stop_sequences(["END"]);
+            let payloads = collect_stream_payloads(
+                ProviderConfig::new("openai", "test-key", endpoint),
+                request,
+            )
+            .await;
+
+            let Ok(request) = server.await else {
+                panic!("mock server task should finish");
+            };
+            // Stop sequences alone never move a request off the configured endpoint.
+            assert!(request.starts_with("POST /responses "), "{request}");
+            assert!(!request.contains("\"stop\""), "{request}");
+            assert_eq!(
+                payloads,
+                vec![
+                    StreamEventPayload::Delta("Hello ".to_string()),
+                    StreamEventPayload::Delta("there".to_string()),
+                    StreamEventPayload::Done,
+                ]
+            );
+        });
+    }
+
+    #[::core::prelude::v1::test]
+    fn chat_completions_keeps_max_tokens_and_multipart_content() {
+        current_thread_runtime().block_on(async {
+            let events = vec![chat_completions_delta("A cat."), "[DONE]".to_string()];
+            let (endpoint, server) = spawn_sse_server(events).await;
+
+            let config = ProviderConfig::new("openai", "test-key", endpoint)
+                .with_api(OpenAiApi::ChatCompletions);
+            let request = StreamRequest::new(
+                test_target(),
+                "gpt-4o",
+                vec![ProviderMessage::new_multipart(
+                    crate::provider::Role::User,
+                    "What is in this picture?",
+                    "https://example.com/cat.png",
+                )],
+            )
+            .with_max_tokens(64);
+            let payloads = collect_stream_payloads(config, request).await;
+
+            let Ok(request) = server.await else {
+                panic!("mock server task should finish");
+            };
+            assert!(request.starts_with("POST /chat/completions "), "{request}");
+            let body = request_body_json(&request);
+            assert_eq!(body["max_tokens"], serde_json::json!(64));
+            assert_eq!(body.get("stop"), None);
+            assert_eq!(
+                body["messages"],
+                serde_json::json!([{
+                    "role": "user",
+                    "content": [
+                        { "type": "text", "text": "What is in this picture?" },
+                        {
+                            "type": "image_url",
+                            "image_url": { "url": "https://example.com/cat.png", "detail": "auto" }
+                        }
+                    ]
+                }])
+            );
+            assert_eq!(
+                payloads,
+                vec![
+                    StreamEventPayload::Delta("A cat.".to_string()),
+                    StreamEventPayload::Done,
+                ]
+            );
+        });
+    }
+
+    #[::core::prelude::v1::test]
+    fn responses_api_sends_multipart_turn_as_one_message() {
+        current_thread_runtime().block_on(async {
+            let (endpoint, server) = spawn_sse_server(vec![responses_delta(1, "A cat.")]).await;
+
+            let request = StreamRequest::new(
+                test_target(),
+                "gpt-4o",
+                vec![ProviderMessage::new_multipart(
+                    crate::provider::Role::User,
+                    "What is in this picture?",
+                    "https://example.com/cat.png",
+                )],
+            );
+            let payloads = collect_stream_payloads(
+                ProviderConfig::new("openai", "test-key", endpoint),
+                request,
+            )
+            .await;
+
+            let Ok(request) = server.await else {
+                panic!("mock server task should finish");
+            };
+            assert!(request.starts_with("POST /responses "), "{request}");
+            let body = request_body_json(&request);
+            let Some(input) = body["input"].as_array() else {
+                panic!("responses body should carry an input array: {body}");
+            };
+            assert_eq!(input.len(), 1, "{body}");
+            assert_eq!(
+                input[0]["content"].as_array().map(Vec::len),
+                Some(2),
+                "{body}"
+            );
+            assert_eq!(
+                payloads,
+                vec![
+                    StreamEventPayload::Delta("A cat.".to_string()),
+                    StreamEventPayload::Done,
+                ]
+            );
+        });
+    }
+
+    const HUNDRED_WORD_MESSAGE: &str = "The brown fox jumps over the dog while the \
+        farmer watches from the porch. Every morning he walks along the river to check the nets, \
+        counts the fish, and writes the numbers in a notebook. His daughter thinks the \
+        habit is strange, but she helps anyway because the walk is quiet and the air smells like \
+        pine. Later they cook breakfast together, talk about the weather, and plan the repairs \
+        the barn needs before winter arrives. When the work is finished, they sit on the steps, \
+        drink coffee, and listen to the birds calling across the empty fields until noon.";
+
+    fn exact_prompt_tokens(model: &str, message: &ProviderMessage) -> usize {
+        let request = tiktoken_rs::ChatCompletionRequestMessage {
+            role: "user".to_string(),
+            content: Some(message.content.text()),
+            ..Default::default()
+        };
+        let tokens = tiktoken_rs::num_tokens_from_messages(model, &[request]);
+        let Ok(tokens) = tokens else {
+            panic!("failed to count reference tokens: {tokens:?}");
+        };
+        tokens
+    }
+
+    fn assert_within_ten_percent(estimate: usize, actual: usize) {
+        let tolerance = actual.div_ceil(10);
+        assert!(
+            estimate.abs_diff(actual) <= tolerance,
+            "estimate {estimate} is not within 10% of {actual}"
+        );
+    }
+
+    #[::core::prelude::v1::test]
+    fn prompt_token_estimate_matches_model_tokenizer() {
+        assert_eq!(HUNDRED_WORD_MESSAGE.split_whitespace().count(), 100);
+        let adapter = RigProviderAdapter::new(ProviderConfig::new(
+            "openai",
+            "test-key",
+            "http://127.0.0.1:9/v1",
+        ));
+        let Ok(adapter) = adapter else {
+            panic!("adapter should build without network access");
+        };
+        let message = ProviderMessage::new(crate::provider::Role::User, HUNDRED_WORD_MESSAGE);
+
+        let model_id = adapter.default_model();
+        let estimate = adapter.estimate_prompt_tokens(model_id, std::slice::from_ref(&message));
+        let actual = exact_prompt_tokens(model_id, &message);
+
+        assert_within_ten_percent(estimate, actual);
+        assert_eq!(adapter.estimate_prompt_tokens(model_id, &[]), 0);
+    }
+
+    #[::core::prelude::v1::test]
+    fn prompt_token_estimate_uses_the_selected_model_encoding() {
+        let adapter = RigProviderAdapter::new(ProviderConfig::new(
+            "openai",
+            "test-key",
+            "http://127.0.0.1:9/v1",
+        ));
+        let Ok(adapter) = adapter else {
+            panic!("adapter should build without network access");
+        };
+        // o200k_base packs CJK text far tighter than cl100k_base, so the counts diverge.
+        let messages = [ProviderMessage::new(
+            crate::provider::Role::User,
+            "今日はとても良い天気なので、公園で散歩をしながら友達と話しました。",
+        )];
+        let o200k = crate::provider::estimate_message_tokens_with(
+            crate::provider::cached_tokenizer(tiktoken_rs::tokenizer::Tokenizer::O200kBase),
+            &messages,
+        );
+        let cl100k = crate::provider::estimate_message_tokens_with(
+            crate::provider::cached_tokenizer(tiktoken_rs::tokenizer::Tokenizer::Cl100kBase),
+            &messages,
+        );
+
+        assert_ne!(o200k, cl100k);
+        assert_eq!(adapter.estimate_message_tokens("gpt-4o", &messages), o200k);
+        assert_eq!(adapter.estimate_message_tokens("gpt-4", &messages), cl100k);
+        assert_eq!(
+            adapter.estimate_message_tokens("local-llama", &messages),
+            cl100k
+        );
+    }
+
+    #[::core::prelude::v1::test]
+    fn message_token_estimates_add_up_to_the_prompt_estimate() {
+        let adapter = RigProviderAdapter::new(ProviderConfig::new(
+            "openai",
+            "test-key",
+            "http://127.0.0.1:9/v1",
+        ));
+        let Ok(adapter) = adapter else {
+            panic!("adapter should build without network access");
+        };
+        let history = [
+            ProviderMessage::new(crate::provider::Role::User, "What is a tokenizer?"),
+            ProviderMessage::new(
+                crate::provider::Role::Assistant,
+                "It splits text into the units a model reads.",
+            ),
+        ];
+        let draft = ProviderMessage::new(crate::provider::Role::User, HUNDRED_WORD_MESSAGE);
+        let mut prompt = history.to_vec();
+        prompt.push(draft.clone());
+
+        // Callers cache the history count and only re-tokenize the draft as it changes.
+        assert_eq!(
+            adapter.estimate_message_tokens("gpt-4o", &history)
+                + adapter.estimate_prompt_tokens("gpt-4o", std::slice::from_ref(&draft)),
+            adapter.estimate_prompt_tokens("gpt-4o", &prompt)
+        );
+    }
+
+    #[::core::prelude::v1::test]
+    fn character_heuristic_errs_towards_overestimating() {
+        let message = ProviderMessage::new(crate::provider::Role::User, HUNDRED_WORD_MESSAGE);
+
+        let estimate =
+            crate::provider::estimate_message_tokens_with(None, std::slice::from_ref(&message));
+        let actual = exact_prompt_tokens("gpt-4", &message);
 
-        Ok(ProviderStreamHandle { stream, worker })
+        // Without a tokenizer the estimate should warn high rather than understate the cost.
+        assert!(estimate >= actual, "estimate {estimate} is below {actual}");
     }
 }
@@ -0,0 +1,4 @@
+ALTER TABLE sessions ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX idx_sessions_pinned_updated_not_deleted
+    ON sessions (deleted_at, pinned DESC, updated_at DESC, id DESC);
@@ -0,0 +1,6 @@
+CREATE TABLE session_drafts (
+    session_id TEXT PRIMARY KEY NOT NULL,
+    content TEXT NOT NULL,
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY (session_id) REFERENCES sessions (id) ON DELETE CASCADE
+);
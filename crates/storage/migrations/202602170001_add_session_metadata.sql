@@ -0,0 +1 @@
+ALTER TABLE sessions ADD COLUMN metadata_json TEXT DEFAULT '{}';
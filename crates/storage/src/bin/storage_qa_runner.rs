@@ -3,14 +3,19 @@ use std::collections::HashSet;
 use std::env;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Barrier;
+use std::sync::atomic::{self, AtomicUsize};
+use std::time::{Duration, Instant};
 
+use serde::{Deserialize, Serialize};
 use snafu::{OptionExt, ResultExt, Snafu};
 
-use zova_storage::sqlite::LEGACY_CONVERSATIONS_TSV_RELATIVE_PATH;
+use zova_storage::sqlite::{LEGACY_CONVERSATIONS_TSV_RELATIVE_PATH, LegacyImportFormat};
 use zova_storage::{
-    AgentEventId, AgentEventStore, BranchId, DEFAULT_SESSION_TITLE, HistoryForkRequest, MediaRefId,
-    MediaStore, MessageId, MessagePatch, MessageRole, MessageStore, NewAgentEvent, NewMediaRef,
-    NewMessage, NewSession, SessionId, SessionPatch, SessionStore, SqliteStorage, StorageError,
+    AgentEventId, AgentEventStore, BranchId, BranchStore, DEFAULT_SESSION_TITLE, DraftStore,
+    HistoryForkRequest, MediaRefId, MediaStore, MessageId, MessagePatch, MessageRole, MessageStore,
+    NewAgentEvent, NewMediaRef, NewMessage, NewSession, SessionId, SessionPatch, SessionRecord,
+    SessionStore, SqliteStorage, SqliteStorageOptions, StorageError,
 };
 
 #[derive(Debug, Clone)]
@@ -26,8 +31,13 @@ enum Scenario {
     PrepNoop,
     SchemaInit,
     FkViolation,
+    UniqueErrorCode,
+    StageLabelsUnique,
     SessionCrud,
+    SessionPinnedOrder,
+    SessionSearch,
     HistoryBranchFork,
+    BranchList,
     CrossSessionGuard,
     MediaRefRoundtrip,
     MediaBlobGuard,
@@ -35,6 +45,29 @@ enum Scenario {
     MigrateTsvFixture,
     MigrateIdempotent,
     MigrateMalformedRow,
+    MigrateCsvFixture,
+    MigrateSessionIdMap,
+    PoolSizeOptions,
+    MessageSearch,
+    MessageSoftDelete,
+    ConcurrentReadWrite,
+    VacuumReclaimsPages,
+    MessageListByRole,
+    AgentEventTypedPayload,
+    SessionMessageCounts,
+    SessionDraftRoundtrip,
+    WalCheckpoint,
+    MediaSha256Verify,
+    MediaFromPath,
+    MessageDeleteAll,
+    MessageDeleteAllInactiveBranch,
+    ErrorDisplayMessages,
+    ReadonlyOpen,
+    SessionMetadata,
+    MessageWordCount,
+    SessionDisplayTitle,
+    UpdatedSince,
+    ErrorTransient,
     All,
 }
 
@@ -46,8 +79,13 @@ impl Scenario {
             "prep_noop" => Some(Self::PrepNoop),
             "schema_init" => Some(Self::SchemaInit),
             "fk_violation" => Some(Self::FkViolation),
+            "unique_error_code" => Some(Self::UniqueErrorCode),
+            "stage_labels_unique" => Some(Self::StageLabelsUnique),
             "session_crud" => Some(Self::SessionCrud),
+            "session_pinned_order" => Some(Self::SessionPinnedOrder),
+            "session_search" => Some(Self::SessionSearch),
             "history_branch_fork" => Some(Self::HistoryBranchFork),
+            "branch_list" => Some(Self::BranchList),
             "cross_session_guard" => Some(Self::CrossSessionGuard),
             "media_ref_roundtrip" => Some(Self::MediaRefRoundtrip),
             "media_blob_guard" => Some(Self::MediaBlobGuard),
@@ -55,6 +93,29 @@ impl Scenario {
             "migrate_tsv_fixture" => Some(Self::MigrateTsvFixture),
             "migrate_idempotent" => Some(Self::MigrateIdempotent),
             "migrate_malformed_row" => Some(Self::MigrateMalformedRow),
+            "migrate_csv_fixture" => Some(Self::MigrateCsvFixture),
+            "migrate_session_id_map" => Some(Self::MigrateSessionIdMap),
+            "pool_size_options" => Some(Self::PoolSizeOptions),
+            "message_search" => Some(Self::MessageSearch),
+            "message_soft_delete" => Some(Self::MessageSoftDelete),
+            "concurrent_read_write" => Some(Self::ConcurrentReadWrite),
+            "vacuum_reclaims_pages" => Some(Self::VacuumReclaimsPages),
+            "message_list_by_role" => Some(Self::MessageListByRole),
+            "agent_event_typed_payload" => Some(Self::AgentEventTypedPayload),
+            "session_message_counts" => Some(Self::SessionMessageCounts),
+            "session_draft_roundtrip" => Some(Self::SessionDraftRoundtrip),
+            "wal_checkpoint" => Some(Self::WalCheckpoint),
+            "media_sha256_verify" => Some(Self::MediaSha256Verify),
+            "media_from_path" => Some(Self::MediaFromPath),
+            "message_delete_all" => Some(Self::MessageDeleteAll),
+            "message_delete_all_inactive_branch" => Some(Self::MessageDeleteAllInactiveBranch),
+            "error_display_messages" => Some(Self::ErrorDisplayMessages),
+            "readonly_open" => Some(Self::ReadonlyOpen),
+            "session_metadata" => Some(Self::SessionMetadata),
+            "message_word_count" => Some(Self::MessageWordCount),
+            "session_display_title" => Some(Self::SessionDisplayTitle),
+            "updated_since" => Some(Self::UpdatedSince),
+            "error_transient" => Some(Self::ErrorTransient),
             "all" => Some(Self::All),
             _ => None,
         }
@@ -67,8 +128,13 @@ impl Scenario {
             Self::PrepNoop => "prep_noop",
             Self::SchemaInit => "schema_init",
             Self::FkViolation => "fk_violation",
+            Self::UniqueErrorCode => "unique_error_code",
+            Self::StageLabelsUnique => "stage_labels_unique",
             Self::SessionCrud => "session_crud",
+            Self::SessionPinnedOrder => "session_pinned_order",
+            Self::SessionSearch => "session_search",
             Self::HistoryBranchFork => "history_branch_fork",
+            Self::BranchList => "branch_list",
             Self::CrossSessionGuard => "cross_session_guard",
             Self::MediaRefRoundtrip => "media_ref_roundtrip",
             Self::MediaBlobGuard => "media_blob_guard",
@@ -76,6 +142,29 @@ impl Scenario {
             Self::MigrateTsvFixture => "migrate_tsv_fixture",
             Self::MigrateIdempotent => "migrate_idempotent",
             Self::MigrateMalformedRow => "migrate_malformed_row",
+            Self::MigrateCsvFixture => "migrate_csv_fixture",
+            Self::MigrateSessionIdMap => "migrate_session_id_map",
+            Self::PoolSizeOptions => "pool_size_options",
+            Self::MessageSearch => "message_search",
+            Self::MessageSoftDelete => "message_soft_delete",
+            Self::ConcurrentReadWrite => "concurrent_read_write",
+            Self::VacuumReclaimsPages => "vacuum_reclaims_pages",
+            Self::MessageListByRole => "message_list_by_role",
+            Self::AgentEventTypedPayload => "agent_event_typed_payload",
+            Self::SessionMessageCounts => "session_message_counts",
+            Self::SessionDraftRoundtrip => "session_draft_roundtrip",
+            Self::WalCheckpoint => "wal_checkpoint",
+            Self::MediaSha256Verify => "media_sha256_verify",
+            Self::MediaFromPath => "media_from_path",
+            Self::MessageDeleteAll => "message_delete_all",
+            Self::MessageDeleteAllInactiveBranch => "message_delete_all_inactive_branch",
+            Self::ErrorDisplayMessages => "error_display_messages",
+            Self::ReadonlyOpen => "readonly_open",
+            Self::SessionMetadata => "session_metadata",
+            Self::MessageWordCount => "message_word_count",
+            Self::SessionDisplayTitle => "session_display_title",
+            Self::UpdatedSince => "updated_since",
+            Self::ErrorTransient => "error_transient",
             Self::All => "all",
         }
     }
@@ -147,10 +236,21 @@ async fn run() -> RunnerResult<()> {
         Scenario::PrepNoop => run_prep_noop(),
         Scenario::SchemaInit => run_schema_init(require_db_path(&args, "schema_init")?).await,
         Scenario::FkViolation => run_fk_violation(require_db_path(&args, "fk_violation")?).await,
+        Scenario::UniqueErrorCode => {
+            run_unique_error_code(require_db_path(&args, "unique_error_code")?).await
+        }
+        Scenario::StageLabelsUnique => run_stage_labels_unique(),
         Scenario::SessionCrud => run_session_crud(require_db_path(&args, "session_crud")?).await,
+        Scenario::SessionPinnedOrder => {
+            run_session_pinned_order(require_db_path(&args, "session_pinned_order")?).await
+        }
+        Scenario::SessionSearch => {
+            run_session_search(require_db_path(&args, "session_search")?).await
+        }
         Scenario::HistoryBranchFork => {
             run_history_branch_fork(require_db_path(&args, "history_branch_fork")?).await
         }
+        Scenario::BranchList => run_branch_list(require_db_path(&args, "branch_list")?).await,
         Scenario::CrossSessionGuard => {
             run_cross_session_guard(require_db_path(&args, "cross_session_guard")?).await
         }
@@ -172,6 +272,68 @@ async fn run() -> RunnerResult<()> {
         Scenario::MigrateMalformedRow => {
             run_migrate_malformed_row(require_db_path(&args, "migrate_malformed_row")?).await
         }
+        Scenario::ConcurrentReadWrite => {
+            run_concurrent_read_write(require_db_path(&args, "concurrent_read_write")?).await
+        }
+        Scenario::VacuumReclaimsPages => {
+            run_vacuum_reclaims_pages(require_db_path(&args, "vacuum_reclaims_pages")?).await
+        }
+        Scenario::MessageListByRole => {
+            run_message_list_by_role(require_db_path(&args, "message_list_by_role")?).await
+        }
+        Scenario::AgentEventTypedPayload => {
+            run_agent_event_typed_payload(require_db_path(&args, "agent_event_typed_payload")?)
+                .await
+        }
+        Scenario::SessionMessageCounts => {
+            run_session_message_counts(require_db_path(&args, "session_message_counts")?).await
+        }
+        Scenario::SessionDraftRoundtrip => {
+            run_session_draft_roundtrip(require_db_path(&args, "session_draft_roundtrip")?).await
+        }
+        Scenario::WalCheckpoint => {
+            run_wal_checkpoint(require_db_path(&args, "wal_checkpoint")?).await
+        }
+        Scenario::MediaSha256Verify => {
+            run_media_sha256_verify(require_db_path(&args, "media_sha256_verify")?).await
+        }
+        Scenario::MediaFromPath => run_media_from_path(require_db_path(&args, "media_from_path")?),
+        Scenario::MessageDeleteAll => {
+            run_message_delete_all(require_db_path(&args, "message_delete_all")?).await
+        }
+        Scenario::MessageDeleteAllInactiveBranch => {
+            run_message_delete_all_inactive_branch(require_db_path(
+                &args,
+                "message_delete_all_inactive_branch",
+            )?)
+            .await
+        }
+        Scenario::ErrorDisplayMessages => run_error_display_messages(),
+        Scenario::ReadonlyOpen => run_readonly_open(require_db_path(&args, "readonly_open")?).await,
+        Scenario::SessionMetadata => {
+            run_session_metadata(require_db_path(&args, "session_metadata")?).await
+        }
+        Scenario::MigrateCsvFixture => {
+            run_migrate_csv_fixture(require_db_path(&args, "migrate_csv_fixture")?).await
+        }
+        Scenario::MessageWordCount => run_message_word_count(),
+        Scenario::SessionDisplayTitle => run_session_display_title(),
+        Scenario::UpdatedSince => run_updated_since(require_db_path(&args, "updated_since")?).await,
+        Scenario::ErrorTransient => {
+            run_error_transient(require_db_path(&args, "error_transient")?).await
+        }
+        Scenario::MigrateSessionIdMap => {
+            run_migrate_session_id_map(require_db_path(&args, "migrate_session_id_map")?).await
+        }
+        Scenario::PoolSizeOptions => {
+            run_pool_size_options(require_db_path(&args, "pool_size_options")?).await
+        }
+        Scenario::MessageSearch => {
+            run_message_search(require_db_path(&args, "message_search")?).await
+        }
+        Scenario::MessageSoftDelete => {
+            run_message_soft_delete(require_db_path(&args, "message_soft_delete")?).await
+        }
         Scenario::All => run_all(args.db_path.as_deref()).await,
     }
 }
@@ -264,22 +426,58 @@ async fn run_all(db_path: Option<&str>) -> RunnerResult<()> {
     run_id_roundtrip()?;
     run_id_invalid()?;
     run_prep_noop()?;
+    run_stage_labels_unique()?;
 
     if let Some(path) = db_path {
         run_schema_init(path).await?;
         run_fk_violation(path).await?;
         run_session_crud(path).await?;
+        run_session_pinned_order(path).await?;
         run_history_branch_fork(path).await?;
+        run_branch_list(path).await?;
         run_cross_session_guard(path).await?;
         run_media_ref_roundtrip(path).await?;
         run_media_blob_guard(path).await?;
         run_agent_event_roundtrip(path).await?;
+        run_unique_error_code(path).await?;
+        run_session_search(path).await?;
+        run_concurrent_read_write(path).await?;
+        run_vacuum_reclaims_pages(path).await?;
+        run_message_list_by_role(path).await?;
+        run_agent_event_typed_payload(path).await?;
+        run_session_message_counts(path).await?;
+        run_session_draft_roundtrip(path).await?;
+        run_wal_checkpoint(path).await?;
+        run_media_sha256_verify(path).await?;
+        run_media_from_path(path)?;
+        run_message_delete_all(path).await?;
+        run_message_delete_all_inactive_branch(path).await?;
+        run_readonly_open(path).await?;
+        run_session_metadata(path).await?;
+        run_updated_since(path).await?;
+        run_error_transient(path).await?;
+        run_migrate_session_id_map(path).await?;
+        run_pool_size_options(path).await?;
+        run_message_search(path).await?;
+        run_message_soft_delete(path).await?;
     }
+    run_error_display_messages()?;
+    run_message_word_count()?;
+    run_session_display_title()?;
 
     println!("all_passed=true");
     Ok(())
 }
 
+const CONCURRENT_READER_THREADS: usize = 4;
+const CONCURRENT_READS_PER_THREAD: usize = 10;
+const CONCURRENT_WRITER_THREADS: usize = 2;
+const CONCURRENT_WRITES_PER_THREAD: usize = 5;
+const CONCURRENT_SCENARIO_BUDGET: Duration = Duration::from_secs(10);
+const POOLED_READER_CONNECTIONS: u32 = 4;
+const POOL_SCENARIO_BUSY_TIMEOUT: Duration = Duration::from_millis(250);
+const SESSION_METADATA_REJECTED_DEPTH: usize = 1100;
+
 async fn run_schema_init(db_path: &str) -> RunnerResult<()> {
     let storage = SqliteStorage::open(db_path)
         .await
@@ -411,14 +609,1742 @@ async fn run_fk_violation(db_path: &str) -> RunnerResult<()> {
                 .fail();
             }
         }
-    };
+    };
+
+    println!("fk_violation_blocked={fk_violation_blocked}");
+    if !fk_violation_blocked {
+        return ScenarioFailedSnafu {
+            stage: "scenario-fk-violation-assert",
+            scenario: "fk_violation",
+            reason: "orphan message insert unexpectedly succeeded".to_string(),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_unique_error_code(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-unique-error-code-open",
+        })?;
+    let session = storage
+        .create_session(NewSession {
+            title: "unique-error-code".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-unique-error-code-create",
+        })?;
+
+    let insert_result = sqlx::query(
+        "INSERT INTO sessions (id, title, active_branch_id, created_at, updated_at, deleted_at) VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(session.id.to_string())
+    .bind("duplicate-session")
+    .bind(session.active_branch_id.to_string())
+    .bind(0_i64)
+    .bind(0_i64)
+    .bind(Option::<i64>::None)
+    .execute(storage.pool())
+    .await;
+
+    let error_code = match insert_result {
+        Ok(_) => {
+            return ScenarioFailedSnafu {
+                stage: "scenario-unique-error-code-insert",
+                scenario: "unique_error_code",
+                reason: "duplicate session id insert unexpectedly succeeded".to_string(),
+            }
+            .fail();
+        }
+        Err(error) => StorageError::from_sqlx("scenario-unique-error-code-insert", error)
+            .database_error_code(),
+    };
+
+    println!(
+        "unique_error_code={}",
+        error_code.as_deref().unwrap_or("none")
+    );
+    if error_code.is_none() {
+        return ScenarioFailedSnafu {
+            stage: "scenario-unique-error-code-assert",
+            scenario: "unique_error_code",
+            reason: "UNIQUE violation did not expose a database error code".to_string(),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_concurrent_read_write(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-concurrent-read-write-open",
+        })?;
+
+    // Each writer appends to its own session so sequence allocation never collides.
+    let mut writer_sessions = Vec::with_capacity(CONCURRENT_WRITER_THREADS);
+    for index in 0..CONCURRENT_WRITER_THREADS {
+        let session = storage
+            .create_session(NewSession {
+                title: format!("concurrent-writer-{index}"),
+            })
+            .context(StorageValidationSnafu {
+                stage: "scenario-concurrent-read-write-create",
+            })?;
+        writer_sessions.push(session.id);
+    }
+
+    let barrier = Barrier::new(CONCURRENT_READER_THREADS + CONCURRENT_WRITER_THREADS);
+    let started_at = Instant::now();
+    let outcomes = std::thread::scope(|scope| {
+        let mut handles = Vec::new();
+        for _ in 0..CONCURRENT_READER_THREADS {
+            handles.push(scope.spawn(|| {
+                barrier.wait();
+                (0..CONCURRENT_READS_PER_THREAD)
+                    .map(|_| storage.list_sessions(false).map(|_| ()))
+                    .collect::<Vec<_>>()
+            }));
+        }
+        for session_id in writer_sessions.iter().copied() {
+            let storage = &storage;
+            let barrier = &barrier;
+            handles.push(scope.spawn(move || {
+                barrier.wait();
+                (0..CONCURRENT_WRITES_PER_THREAD)
+                    .map(|index| {
+                        storage
+                            .append_message(
+                                session_id,
+                                NewMessage {
+                                    role: MessageRole::User,
+                                    content: format!("concurrent-write-{index}"),
+                                },
+                            )
+                            .map(|_| ())
+                    })
+                    .collect::<Vec<_>>()
+            }));
+        }
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join())
+            .collect::<Vec<_>>()
+    });
+    let elapsed = started_at.elapsed();
+
+    let mut call_results = Vec::new();
+    for outcome in outcomes {
+        match outcome {
+            Ok(results) => call_results.extend(results),
+            Err(_) => {
+                return ScenarioFailedSnafu {
+                    stage: "scenario-concurrent-read-write-join",
+                    scenario: "concurrent_read_write",
+                    reason: "a concurrent storage thread panicked".to_string(),
+                }
+                .fail();
+            }
+        }
+    }
+
+    let locked_errors = call_results
+        .iter()
+        .filter(|result| {
+            matches!(result, Err(error @ StorageError::SqliteQuery { .. }) if error.to_string().contains("locked"))
+        })
+        .count();
+    let first_error = call_results.into_iter().find_map(Result::err);
+
+    let mut appended_messages = 0;
+    for session_id in writer_sessions {
+        appended_messages += storage
+            .list_messages(session_id)
+            .context(StorageValidationSnafu {
+                stage: "scenario-concurrent-read-write-list-messages",
+            })?
+            .len();
+    }
+
+    println!("concurrent_locked_errors={locked_errors}");
+    println!("concurrent_appended_messages={appended_messages}");
+    println!("concurrent_elapsed_ms={}", elapsed.as_millis());
+
+    if let Some(error) = first_error {
+        return ScenarioFailedSnafu {
+            stage: "scenario-concurrent-read-write-call",
+            scenario: "concurrent_read_write",
+            reason: format!("concurrent storage call failed: {error}"),
+        }
+        .fail();
+    }
+    if appended_messages != CONCURRENT_WRITER_THREADS * CONCURRENT_WRITES_PER_THREAD {
+        return ScenarioFailedSnafu {
+            stage: "scenario-concurrent-read-write-assert-messages",
+            scenario: "concurrent_read_write",
+            reason: format!(
+                "expected every concurrent append to persist, found {appended_messages}"
+            ),
+        }
+        .fail();
+    }
+    if elapsed > CONCURRENT_SCENARIO_BUDGET {
+        return ScenarioFailedSnafu {
+            stage: "scenario-concurrent-read-write-assert-elapsed",
+            scenario: "concurrent_read_write",
+            reason: format!("concurrent calls took {}ms", elapsed.as_millis()),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_vacuum_reclaims_pages(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-vacuum-open",
+        })?;
+
+    // Large titles make sure the deleted rows span enough pages for VACUUM to shrink the file.
+    let padding = "x".repeat(2048);
+    for index in 0..100 {
+        storage
+            .create_session(NewSession {
+                title: format!("vacuum-{index:03}-{padding}"),
+            })
+            .context(StorageValidationSnafu {
+                stage: "scenario-vacuum-create",
+            })?;
+    }
+
+    // The store only soft-deletes, so hard-delete through the pool to leave free pages behind.
+    sqlx::query(
+        "DELETE FROM branches WHERE session_id IN (SELECT id FROM sessions WHERE title LIKE 'vacuum-%')",
+    )
+    .execute(storage.pool())
+    .await
+    .context(SqliteQuerySnafu {
+        stage: "scenario-vacuum-delete-branches",
+    })?;
+    sqlx::query("DELETE FROM sessions WHERE title LIKE 'vacuum-%'")
+        .execute(storage.pool())
+        .await
+        .context(SqliteQuerySnafu {
+            stage: "scenario-vacuum-delete-sessions",
+        })?;
+
+    let size_before = storage
+        .database_size_bytes()
+        .context(StorageValidationSnafu {
+            stage: "scenario-vacuum-size-before",
+        })?;
+    let freed_pages = storage.vacuum().context(StorageValidationSnafu {
+        stage: "scenario-vacuum-run",
+    })?;
+    let size_after = storage
+        .database_size_bytes()
+        .context(StorageValidationSnafu {
+            stage: "scenario-vacuum-size-after",
+        })?;
+
+    println!("vacuum_freed_pages={freed_pages}");
+    println!("vacuum_size_before={size_before}");
+    println!("vacuum_size_after={size_after}");
+    if freed_pages == 0 || size_after >= size_before {
+        return ScenarioFailedSnafu {
+            stage: "scenario-vacuum-assert",
+            scenario: "vacuum_reclaims_pages",
+            reason: format!(
+                "expected vacuum to shrink the database, freed {freed_pages} pages ({size_before} -> {size_after} bytes)"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_message_search(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-search-open",
+        })?;
+
+    let mut session_ids = Vec::new();
+    let fixtures = [
+        (
+            "message-search-first",
+            ["first mentions qa-needle once", "unrelated reply"],
+        ),
+        (
+            "message-search-second",
+            ["QA-NEEDLE in caps", "100%_literal"],
+        ),
+        ("message-search-deleted", ["deleted qa-needle", "ignored"]),
+    ];
+    for (title, contents) in fixtures {
+        let session = storage
+            .create_session(NewSession {
+                title: title.to_string(),
+            })
+            .context(StorageValidationSnafu {
+                stage: "scenario-message-search-create",
+            })?;
+        for content in contents {
+            storage
+                .append_message(
+                    session.id,
+                    NewMessage {
+                        role: MessageRole::User,
+                        content: content.to_string(),
+                    },
+                )
+                .context(StorageValidationSnafu {
+                    stage: "scenario-message-search-append",
+                })?;
+        }
+        session_ids.push(session.id);
+    }
+    let Some(deleted_session_id) = session_ids.last().copied() else {
+        return ScenarioFailedSnafu {
+            stage: "scenario-message-search-fixture",
+            scenario: "message_search",
+            reason: "no fixture sessions were created".to_string(),
+        }
+        .fail();
+    };
+    storage
+        .soft_delete_session(deleted_session_id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-search-delete",
+        })?;
+
+    let matches = storage
+        .search_messages("qa-needle", 10)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-search-query",
+        })?;
+    let mut matched_contents = matches
+        .iter()
+        .map(|hit| hit.message.content.as_str())
+        .collect::<Vec<_>>();
+    let sessions_joined = matches.iter().all(|hit| {
+        hit.session.id == hit.message.session_id
+            && hit.session.active_branch_id == hit.message.branch_id
+            && hit.session.title.starts_with("message-search")
+    });
+    matched_contents.sort_unstable();
+    let limited = storage
+        .search_messages("qa-needle", 1)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-search-limit",
+        })?;
+    let wildcard_matches = storage
+        .search_messages("0%_l", 10)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-search-wildcard",
+        })?;
+    let wildcard_literal = storage
+        .search_messages("%_", 10)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-search-wildcard-literal",
+        })?;
+
+    let matches_ok = matched_contents == ["QA-NEEDLE in caps", "first mentions qa-needle once"];
+    let limit_ok = limited.len() == 1;
+    let wildcard_ok = wildcard_matches.len() == 1 && wildcard_literal.len() == 1;
+    println!("message_search_matches={matched_contents:?}");
+    println!("message_search_limit_ok={limit_ok}");
+    println!("message_search_wildcard_ok={wildcard_ok}");
+    println!("message_search_sessions_joined={sessions_joined}");
+    if !matches_ok || !limit_ok || !wildcard_ok || !sessions_joined {
+        return ScenarioFailedSnafu {
+            stage: "scenario-message-search-assert",
+            scenario: "message_search",
+            reason: format!(
+                "unexpected message search results: matches={matched_contents:?}, limited={}, wildcard={}, wildcard_literal={}, sessions_joined={sessions_joined}",
+                limited.len(),
+                wildcard_matches.len(),
+                wildcard_literal.len()
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_message_soft_delete(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-soft-delete-open",
+        })?;
+    let session = storage
+        .create_session(NewSession {
+            title: "message-soft-delete".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-soft-delete-create",
+        })?;
+    let other_session = storage
+        .create_session(NewSession {
+            title: "message-soft-delete-other".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-soft-delete-create-other",
+        })?;
+
+    let mut message_ids = Vec::new();
+    for content in ["keep-first", "delete-me", "keep-last"] {
+        let message = storage
+            .append_message(
+                session.id,
+                NewMessage {
+                    role: MessageRole::User,
+                    content: content.to_string(),
+                },
+            )
+            .context(StorageValidationSnafu {
+                stage: "scenario-message-soft-delete-append",
+            })?;
+        message_ids.push(message.id);
+    }
+    let [first_id, deleted_id, last_id] = message_ids[..] else {
+        return ScenarioFailedSnafu {
+            stage: "scenario-message-soft-delete-fixture",
+            scenario: "message_soft_delete",
+            reason: format!("expected three fixture messages, got {message_ids:?}"),
+        }
+        .fail();
+    };
+
+    storage
+        .soft_delete_message(session.id, deleted_id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-soft-delete-apply",
+        })?;
+    let remaining = storage
+        .list_messages(session.id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-soft-delete-list",
+        })?
+        .into_iter()
+        .map(|message| message.content)
+        .collect::<Vec<_>>();
+    let repeat_not_found = matches!(
+        storage.soft_delete_message(session.id, deleted_id),
+        Err(StorageError::NotFound { .. })
+    );
+    let cross_session_not_found = matches!(
+        storage.soft_delete_message(other_session.id, first_id),
+        Err(StorageError::NotFound { .. })
+    );
+
+    storage
+        .fork_from_history(
+            session.id,
+            HistoryForkRequest {
+                source_message_id: last_id,
+                replacement_content: "keep-last-edited".to_string(),
+            },
+        )
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-soft-delete-fork",
+        })?;
+    let inactive_branch_conflict = matches!(
+        storage.soft_delete_message(session.id, first_id),
+        Err(StorageError::Conflict { .. })
+    );
+
+    let remaining_ok = remaining == ["keep-first", "keep-last"];
+    println!("message_soft_delete_remaining={remaining:?}");
+    println!("message_soft_delete_repeat_not_found={repeat_not_found}");
+    println!("message_soft_delete_cross_session_not_found={cross_session_not_found}");
+    println!("message_soft_delete_inactive_branch_conflict={inactive_branch_conflict}");
+    if !remaining_ok || !repeat_not_found || !cross_session_not_found || !inactive_branch_conflict {
+        return ScenarioFailedSnafu {
+            stage: "scenario-message-soft-delete-assert",
+            scenario: "message_soft_delete",
+            reason: format!(
+                "soft delete mismatch: remaining={remaining:?}, repeat_not_found={repeat_not_found}, cross_session_not_found={cross_session_not_found}, inactive_branch_conflict={inactive_branch_conflict}"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_message_list_by_role(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-list-by-role-open",
+        })?;
+    let session = storage
+        .create_session(NewSession {
+            title: "message-list-by-role".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-list-by-role-create",
+        })?;
+
+    let conversation = [
+        (MessageRole::System, "system-prompt"),
+        (MessageRole::User, "user-1"),
+        (MessageRole::Assistant, "assistant-1"),
+        (MessageRole::User, "user-2"),
+        (MessageRole::Assistant, "assistant-2"),
+        (MessageRole::User, "user-3"),
+    ];
+    for (role, content) in conversation {
+        storage
+            .append_message(
+                session.id,
+                NewMessage {
+                    role,
+                    content: content.to_string(),
+                },
+            )
+            .context(StorageValidationSnafu {
+                stage: "scenario-message-list-by-role-append",
+            })?;
+    }
+
+    let user_contents = storage
+        .list_messages_by_role(session.id, MessageRole::User)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-list-by-role-list-user",
+        })?
+        .into_iter()
+        .map(|message| message.content)
+        .collect::<Vec<_>>();
+    let assistant_contents = storage
+        .list_messages_by_role(session.id, MessageRole::Assistant)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-list-by-role-list-assistant",
+        })?
+        .into_iter()
+        .map(|message| message.content)
+        .collect::<Vec<_>>();
+
+    let user_ok = user_contents == ["user-1", "user-2", "user-3"];
+    let assistant_ok = assistant_contents == ["assistant-1", "assistant-2"];
+    println!("message_list_by_role_user_ok={user_ok}");
+    println!("message_list_by_role_assistant_ok={assistant_ok}");
+    if !user_ok || !assistant_ok {
+        return ScenarioFailedSnafu {
+            stage: "scenario-message-list-by-role-assert",
+            scenario: "message_list_by_role",
+            reason: format!(
+                "unexpected role filter results: user={user_contents:?}, assistant={assistant_contents:?}"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ToolCallPayload {
+    tool: String,
+    attempt: u32,
+    arguments: Vec<String>,
+}
+
+async fn run_agent_event_typed_payload(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-agent-event-typed-payload-open",
+        })?;
+    let session = storage
+        .create_session(NewSession {
+            title: "agent-event-typed-payload".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-agent-event-typed-payload-create-session",
+        })?;
+
+    let payload = ToolCallPayload {
+        tool: "search".to_string(),
+        attempt: 2,
+        arguments: vec!["zova".to_string(), "storage".to_string()],
+    };
+    let new_event = NewAgentEvent::with_payload("message.tool", &payload).map_err(|error| {
+        RunnerError::ScenarioFailed {
+            stage: "scenario-agent-event-typed-payload-encode",
+            scenario: "agent_event_typed_payload",
+            reason: format!("failed to encode payload: {error}"),
+        }
+    })?;
+    let record =
+        storage
+            .append_agent_event(session.id, new_event)
+            .context(StorageValidationSnafu {
+                stage: "scenario-agent-event-typed-payload-append",
+            })?;
+
+    let decoded = record.payload::<ToolCallPayload>().ok();
+    let value_tool = record.payload_value().ok().and_then(|value| {
+        value
+            .get("tool")
+            .and_then(|tool| tool.as_str())
+            .map(str::to_string)
+    });
+    let typed_ok = decoded.as_ref() == Some(&payload);
+    let value_ok = value_tool.as_deref() == Some("search");
+
+    println!("agent_event_typed_payload_ok={typed_ok}");
+    println!("agent_event_payload_value_ok={value_ok}");
+    if !typed_ok || !value_ok {
+        return ScenarioFailedSnafu {
+            stage: "scenario-agent-event-typed-payload-assert",
+            scenario: "agent_event_typed_payload",
+            reason: format!("payload did not round-trip: typed={decoded:?}, tool={value_tool:?}"),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_session_message_counts(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-message-counts-open",
+        })?;
+    let busy_session = storage
+        .create_session(NewSession {
+            title: "message-counts-busy".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-message-counts-create-busy",
+        })?;
+    let empty_session = storage
+        .create_session(NewSession {
+            title: "message-counts-empty".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-message-counts-create-empty",
+        })?;
+
+    for index in 0..5 {
+        storage
+            .append_message(
+                busy_session.id,
+                NewMessage {
+                    role: MessageRole::User,
+                    content: format!("count-{index}"),
+                },
+            )
+            .context(StorageValidationSnafu {
+                stage: "scenario-session-message-counts-append",
+            })?;
+    }
+
+    let sessions = storage
+        .list_sessions_with_counts(false)
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-message-counts-list",
+        })?;
+    let count_for = |session_id: SessionId| {
+        sessions
+            .iter()
+            .find(|session| session.id == session_id)
+            .and_then(|session| session.message_count)
+    };
+    let busy_count = count_for(busy_session.id);
+    let empty_count = count_for(empty_session.id);
+    let plain_list_has_no_counts = storage
+        .list_sessions(false)
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-message-counts-list-plain",
+        })?
+        .iter()
+        .all(|session| session.message_count.is_none());
+
+    println!("session_message_count_busy={busy_count:?}");
+    println!("session_message_count_empty={empty_count:?}");
+    println!("session_plain_list_has_no_counts={plain_list_has_no_counts}");
+    if busy_count != Some(5) || empty_count != Some(0) || !plain_list_has_no_counts {
+        return ScenarioFailedSnafu {
+            stage: "scenario-session-message-counts-assert",
+            scenario: "session_message_counts",
+            reason: format!(
+                "unexpected message counts: busy={busy_count:?}, empty={empty_count:?}, plain_none={plain_list_has_no_counts}"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_session_draft_roundtrip(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-draft-roundtrip-open",
+        })?;
+    let session = storage
+        .create_session(NewSession {
+            title: "draft-roundtrip".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-draft-roundtrip-create",
+        })?;
+
+    storage
+        .save_draft(session.id, "first draft")
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-draft-roundtrip-save-first",
+        })?;
+    storage
+        .save_draft(session.id, "second draft")
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-draft-roundtrip-save-second",
+        })?;
+    let loaded = storage
+        .load_draft(session.id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-draft-roundtrip-load",
+        })?;
+
+    storage
+        .save_draft(session.id, "")
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-draft-roundtrip-clear",
+        })?;
+    let cleared = storage
+        .load_draft(session.id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-draft-roundtrip-load-cleared",
+        })?;
+
+    let missing_session_rejected = matches!(
+        storage.save_draft(SessionId::new_v7(), "orphan"),
+        Err(StorageError::NotFound { .. })
+    );
+
+    println!("session_draft_loaded={loaded:?}");
+    println!("session_draft_cleared={cleared:?}");
+    println!("session_draft_missing_session_rejected={missing_session_rejected}");
+    if loaded.as_deref() != Some("second draft") || cleared.is_some() || !missing_session_rejected {
+        return ScenarioFailedSnafu {
+            stage: "scenario-session-draft-roundtrip-assert",
+            scenario: "session_draft_roundtrip",
+            reason: format!(
+                "unexpected draft state: loaded={loaded:?}, cleared={cleared:?}, missing_rejected={missing_session_rejected}"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_wal_checkpoint(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-wal-checkpoint-open",
+        })?;
+    let session = storage
+        .create_session(NewSession {
+            title: "wal-checkpoint".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-wal-checkpoint-create",
+        })?;
+
+    for index in 0..100 {
+        storage
+            .append_message(
+                session.id,
+                NewMessage {
+                    role: MessageRole::User,
+                    content: format!("checkpoint-{index}"),
+                },
+            )
+            .context(StorageValidationSnafu {
+                stage: "scenario-wal-checkpoint-append",
+            })?;
+    }
+
+    let result = storage.checkpoint().context(StorageValidationSnafu {
+        stage: "scenario-wal-checkpoint-run",
+    })?;
+
+    println!("wal_checkpoint_wal_frames={}", result.wal_frames);
+    println!(
+        "wal_checkpoint_checkpointed_frames={}",
+        result.checkpointed_frames
+    );
+    if result.checkpointed_frames == 0 {
+        return ScenarioFailedSnafu {
+            stage: "scenario-wal-checkpoint-assert",
+            scenario: "wal_checkpoint",
+            reason: format!("expected checkpointed frames after 100 inserts, got {result:?}"),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_media_sha256_verify(db_path: &str) -> RunnerResult<()> {
+    const FIXTURE_CONTENTS: &str = "zova media fixture\n";
+    const FIXTURE_SHA256: &str = "24e36e64978bb5ffa7ff2f0783c333b36ddad79d7764a17f2c0511da4cb83c56";
+
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-media-sha256-open",
+        })?;
+    let session = storage
+        .create_session(NewSession {
+            title: "media-sha256".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-media-sha256-create-session",
+        })?;
+    let message = storage
+        .append_message(
+            session.id,
+            NewMessage {
+                role: MessageRole::User,
+                content: "sha256-target".to_string(),
+            },
+        )
+        .context(StorageValidationSnafu {
+            stage: "scenario-media-sha256-append-message",
+        })?;
+
+    let fixture_path = PathBuf::from(format!("{db_path}-sha256-fixture.txt"));
+    std::fs::write(&fixture_path, FIXTURE_CONTENTS).context(FileIoSnafu {
+        stage: "scenario-media-sha256-write-fixture",
+        path: fixture_path.display().to_string(),
+    })?;
+
+    let attached = storage
+        .attach_media(
+            session.id,
+            message.id,
+            NewMediaRef {
+                uri: format!("file://{}", fixture_path.display()),
+                mime_type: "text/plain".to_string(),
+                size_bytes: FIXTURE_CONTENTS.len() as u64,
+                duration_ms: None,
+                width_px: None,
+                height_px: None,
+                sha256_hex: Some(FIXTURE_SHA256.to_string()),
+            },
+        )
+        .context(StorageValidationSnafu {
+            stage: "scenario-media-sha256-attach",
+        })?;
+
+    let verify = |record: &zova_storage::MediaRefRecord, stage: &'static str| {
+        record.verify_sha256(&fixture_path).context(FileIoSnafu {
+            stage,
+            path: fixture_path.display().to_string(),
+        })
+    };
+    let matching = verify(&attached, "scenario-media-sha256-verify-matching")?;
+    let mut tampered = attached.clone();
+    tampered.sha256_hex = Some("0".repeat(64));
+    let mismatched = verify(&tampered, "scenario-media-sha256-verify-mismatched")?;
+    let mut unhashed = attached.clone();
+    unhashed.sha256_hex = None;
+    let unhashed_passes = verify(&unhashed, "scenario-media-sha256-verify-unhashed")?;
+
+    remove_file_if_exists(&fixture_path, "scenario-media-sha256-remove-fixture")?;
+    let missing_file_errors = attached.verify_sha256(&fixture_path).is_err();
+
+    println!("media_sha256_matching={matching}");
+    println!("media_sha256_mismatched={mismatched}");
+    println!("media_sha256_unhashed_passes={unhashed_passes}");
+    println!("media_sha256_missing_file_errors={missing_file_errors}");
+    if !matching || mismatched || !unhashed_passes || !missing_file_errors {
+        return ScenarioFailedSnafu {
+            stage: "scenario-media-sha256-assert",
+            scenario: "media_sha256_verify",
+            reason: format!(
+                "unexpected verification results: matching={matching}, mismatched={mismatched}, unhashed={unhashed_passes}, missing_errors={missing_file_errors}"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+fn run_media_from_path(db_path: &str) -> RunnerResult<()> {
+    let fixture_path = PathBuf::from(format!("{db_path}-from-path-fixture.png"));
+    image::RgbImage::new(3, 2)
+        .save(&fixture_path)
+        .map_err(|error| RunnerError::ScenarioFailed {
+            stage: "scenario-media-from-path-write-fixture",
+            scenario: "media_from_path",
+            reason: format!("failed to encode fixture png: {error}"),
+        })?;
+
+    let media_ref = NewMediaRef::from_path(&fixture_path).context(FileIoSnafu {
+        stage: "scenario-media-from-path-build",
+        path: fixture_path.display().to_string(),
+    })?;
+    let expected_size = std::fs::metadata(&fixture_path)
+        .context(FileIoSnafu {
+            stage: "scenario-media-from-path-metadata",
+            path: fixture_path.display().to_string(),
+        })?
+        .len();
+    let verified = zova_storage::MediaRefRecord {
+        id: MediaRefId::new_v7(),
+        session_id: SessionId::new_v7(),
+        message_id: MessageId::new_v7(),
+        uri: media_ref.uri.clone(),
+        mime_type: media_ref.mime_type.clone(),
+        size_bytes: media_ref.size_bytes,
+        duration_ms: media_ref.duration_ms,
+        width_px: media_ref.width_px,
+        height_px: media_ref.height_px,
+        sha256_hex: media_ref.sha256_hex.clone(),
+        deleted_at_unix_seconds: None,
+    }
+    .verify_sha256(&fixture_path)
+    .context(FileIoSnafu {
+        stage: "scenario-media-from-path-verify",
+        path: fixture_path.display().to_string(),
+    })?;
+    remove_file_if_exists(&fixture_path, "scenario-media-from-path-remove-fixture")?;
+
+    let expected_uri = format!("file://{}", fixture_path.display());
+    let fields_ok = media_ref.uri == expected_uri
+        && media_ref.mime_type == "image/png"
+        && media_ref.size_bytes == expected_size
+        && media_ref.width_px == Some(3)
+        && media_ref.height_px == Some(2)
+        && media_ref.duration_ms.is_none()
+        && media_ref
+            .sha256_hex
+            .as_ref()
+            .is_some_and(|hex| hex.len() == 64);
+
+    println!("media_from_path_ref={media_ref:?}");
+    println!("media_from_path_fields_ok={fields_ok}");
+    println!("media_from_path_hash_verified={verified}");
+    if !fields_ok || !verified {
+        return ScenarioFailedSnafu {
+            stage: "scenario-media-from-path-assert",
+            scenario: "media_from_path",
+            reason: format!("unexpected media ref fields: {media_ref:?}, verified={verified}"),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_message_delete_all(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-open",
+        })?;
+    let session = storage
+        .create_session(NewSession {
+            title: "message-delete-all".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-create",
+        })?;
+
+    let mut last_message_id = None;
+    for index in 0..3 {
+        let message = storage
+            .append_message(
+                session.id,
+                NewMessage {
+                    role: MessageRole::User,
+                    content: format!("clear-{index}"),
+                },
+            )
+            .context(StorageValidationSnafu {
+                stage: "scenario-message-delete-all-append",
+            })?;
+        last_message_id = Some(message.id);
+    }
+    let last_message_id = last_message_id.context(ScenarioFailedSnafu {
+        stage: "scenario-message-delete-all-missing-message",
+        scenario: "message_delete_all",
+        reason: "no fixture message was appended".to_string(),
+    })?;
+    storage
+        .attach_media(
+            session.id,
+            last_message_id,
+            NewMediaRef {
+                uri: "file:///tmp/clear-fixture.png".to_string(),
+                mime_type: "image/png".to_string(),
+                size_bytes: 1,
+                duration_ms: None,
+                width_px: None,
+                height_px: None,
+                sha256_hex: None,
+            },
+        )
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-attach",
+        })?;
+    storage
+        .append_agent_event(
+            session.id,
+            NewAgentEvent {
+                message_id: Some(last_message_id),
+                event_type: "clear.fixture".to_string(),
+                payload_json: "{}".to_string(),
+            },
+        )
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-agent-event",
+        })?;
+
+    let new_branch_id = storage
+        .delete_all_messages(session.id, session.active_branch_id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-run",
+        })?;
+    let remaining = storage
+        .list_messages(session.id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-list",
+        })?
+        .len();
+    let active_branch_id = storage
+        .get_session(session.id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-get-session",
+        })?
+        .map(|session| session.active_branch_id);
+    let appended_after_clear = storage
+        .append_message(
+            session.id,
+            NewMessage {
+                role: MessageRole::User,
+                content: "fresh start".to_string(),
+            },
+        )
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-append-after",
+        })?;
+    let unknown_branch_rejected = matches!(
+        storage.delete_all_messages(session.id, BranchId::new_v7()),
+        Err(StorageError::NotFound { .. })
+    );
+
+    println!("message_delete_all_remaining={remaining}");
+    println!(
+        "message_delete_all_active_branch_swapped={}",
+        active_branch_id == Some(new_branch_id) && new_branch_id != session.active_branch_id
+    );
+    println!(
+        "message_delete_all_append_after_seq={}",
+        appended_after_clear.seq
+    );
+    println!("message_delete_all_unknown_branch_rejected={unknown_branch_rejected}");
+    if remaining != 0
+        || active_branch_id != Some(new_branch_id)
+        || appended_after_clear.branch_id != new_branch_id
+        || !unknown_branch_rejected
+    {
+        return ScenarioFailedSnafu {
+            stage: "scenario-message-delete-all-assert",
+            scenario: "message_delete_all",
+            reason: format!(
+                "unexpected state after clear: remaining={remaining}, active={active_branch_id:?}, new={new_branch_id}, unknown_rejected={unknown_branch_rejected}"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_message_delete_all_inactive_branch(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-inactive-open",
+        })?;
+    let session = storage
+        .create_session(NewSession {
+            title: "message-delete-all-inactive".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-inactive-create",
+        })?;
+
+    // Clearing once leaves the original branch behind as an inactive parent.
+    let inactive_branch_id = session.active_branch_id;
+    let active_branch_id = storage
+        .delete_all_messages(session.id, inactive_branch_id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-inactive-first-clear",
+        })?;
+    storage
+        .append_message(
+            session.id,
+            NewMessage {
+                role: MessageRole::User,
+                content: "active history".to_string(),
+            },
+        )
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-inactive-append",
+        })?;
+
+    let conflict = matches!(
+        storage.delete_all_messages(session.id, inactive_branch_id),
+        Err(StorageError::Conflict {
+            entity: "branch",
+            ..
+        })
+    );
+    let remaining = storage
+        .list_messages(session.id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-inactive-list",
+        })?
+        .len();
+    let current_branch_id = storage
+        .get_session(session.id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-message-delete-all-inactive-get-session",
+        })?
+        .map(|session| session.active_branch_id);
+
+    println!("message_delete_all_inactive_conflict={conflict}");
+    println!("message_delete_all_inactive_remaining={remaining}");
+    println!(
+        "message_delete_all_inactive_active_kept={}",
+        current_branch_id == Some(active_branch_id)
+    );
+    if !conflict || remaining != 1 || current_branch_id != Some(active_branch_id) {
+        return ScenarioFailedSnafu {
+            stage: "scenario-message-delete-all-inactive-assert",
+            scenario: "message_delete_all_inactive_branch",
+            reason: format!(
+                "inactive clear changed state: conflict={conflict}, remaining={remaining}, active={current_branch_id:?}, expected={active_branch_id}"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_readonly_open(db_path: &str) -> RunnerResult<()> {
+    let writer = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-readonly-open-writer",
+        })?;
+    let fixture = writer
+        .create_session(NewSession {
+            title: "readonly-fixture".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-readonly-open-create-fixture",
+        })?;
+
+    let reader = SqliteStorage::open_readonly(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-readonly-open-reader",
+        })?;
+    let sessions = reader
+        .list_sessions(false)
+        .context(StorageValidationSnafu {
+            stage: "scenario-readonly-open-list",
+        })?;
+    let lists_fixture = sessions.iter().any(|session| session.id == fixture.id);
+    let create_result = reader.create_session(NewSession {
+        title: "readonly-rejected".to_string(),
+    });
+    let create_rejected = matches!(create_result, Err(StorageError::ReadOnly { .. }));
+    let draft_rejected = matches!(
+        reader.save_draft(fixture.id, "draft"),
+        Err(StorageError::ReadOnly { .. })
+    );
+
+    println!("readonly_flag={}", reader.is_readonly());
+    println!("readonly_lists_fixture={lists_fixture}");
+    println!("readonly_create_rejected={create_rejected}");
+    println!("readonly_draft_rejected={draft_rejected}");
+    if !reader.is_readonly() || !lists_fixture || !create_rejected || !draft_rejected {
+        return ScenarioFailedSnafu {
+            stage: "scenario-readonly-open-assert",
+            scenario: "readonly_open",
+            reason: format!(
+                "readonly storage misbehaved: lists_fixture={lists_fixture}, create={create_result:?}"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_session_metadata(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-metadata-open",
+        })?;
+    let session = storage
+        .create_session(NewSession {
+            title: "session-metadata".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-metadata-create",
+        })?;
+    let default_metadata = storage
+        .get_session(session.id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-metadata-get-default",
+        })?
+        .and_then(|record| record.metadata_json);
+
+    let metadata = serde_json::json!({ "project": "zova" });
+    storage
+        .update_session_metadata(session.id, metadata.clone())
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-metadata-update",
+        })?;
+    let loaded = storage
+        .get_session(session.id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-metadata-get",
+        })?
+        .context(ScenarioFailedSnafu {
+            stage: "scenario-session-metadata-missing",
+            scenario: "session_metadata",
+            reason: "session vanished after metadata update".to_string(),
+        })?;
+    let loaded_metadata = loaded
+        .metadata_json
+        .as_deref()
+        .and_then(|raw| serde_json::from_str::<serde_json::Value>(raw).ok());
+
+    let tagged = storage
+        .update_session_metadata(
+            session.id,
+            serde_json::json!({ "project": "zova", "tags": ["work", 7, "rust"] }),
+        )
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-metadata-update-tags",
+        })?;
+
+    // SQLite's JSON parser caps nesting at 1000 levels while serde_json will
+    // serialize any depth, so the store must reject this before writing.
+    let mut too_deep = serde_json::Value::Array(Vec::new());
+    for _ in 0..SESSION_METADATA_REJECTED_DEPTH {
+        too_deep = serde_json::Value::Array(vec![too_deep]);
+    }
+    let rejected = matches!(
+        storage.update_session_metadata(session.id, too_deep),
+        Err(StorageError::Conflict {
+            stage: "session-update-invalid-metadata",
+            entity: "session",
+            ..
+        })
+    );
+    let kept_metadata = storage
+        .get_session(session.id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-metadata-get-after-reject",
+        })?
+        .and_then(|record| record.metadata_json);
+
+    let default_ok = default_metadata.as_deref() == Some("{}");
+    let roundtrip_ok = loaded_metadata.as_ref() == Some(&metadata) && loaded.tags.is_empty();
+    let tags_ok = tagged.tags == ["work", "rust"];
+    let title_kept = loaded.title == "session-metadata";
+    let reject_ok = rejected && kept_metadata == tagged.metadata_json;
+    println!("session_metadata_default={default_metadata:?}");
+    println!("session_metadata_loaded={:?}", loaded.metadata_json);
+    println!("session_metadata_roundtrip_ok={roundtrip_ok}");
+    println!("session_metadata_tags={:?}", tagged.tags);
+    println!("session_metadata_invalid_rejected={rejected}");
+    if !default_ok || !roundtrip_ok || !tags_ok || !title_kept || !reject_ok {
+        return ScenarioFailedSnafu {
+            stage: "scenario-session-metadata-assert",
+            scenario: "session_metadata",
+            reason: format!(
+                "default_ok={default_ok}, roundtrip_ok={roundtrip_ok}, tags_ok={tags_ok}, title_kept={title_kept}, reject_ok={reject_ok}"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_updated_since(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-updated-since-open",
+        })?;
+    let mut session_ids = Vec::new();
+    for title in [
+        "updated-since-old",
+        "updated-since-mid",
+        "updated-since-new",
+    ] {
+        let session = storage
+            .create_session(NewSession {
+                title: title.to_string(),
+            })
+            .context(StorageValidationSnafu {
+                stage: "scenario-updated-since-create",
+            })?;
+        session_ids.push(session.id);
+    }
+    let mut message_ids = Vec::new();
+    for content in ["old", "mid", "new"] {
+        let message = storage
+            .append_message(
+                session_ids[0],
+                NewMessage {
+                    role: MessageRole::User,
+                    content: content.to_string(),
+                },
+            )
+            .context(StorageValidationSnafu {
+                stage: "scenario-updated-since-append",
+            })?;
+        message_ids.push(message.id);
+    }
+    storage
+        .soft_delete_session(session_ids[2])
+        .context(StorageValidationSnafu {
+            stage: "scenario-updated-since-delete",
+        })?;
+
+    // Writes stamp rows with the wall clock, so pin known timestamps through the pool.
+    for (index, timestamp) in [1_000_i64, 2_000, 3_000].into_iter().enumerate() {
+        sqlx::query("UPDATE sessions SET updated_at = ? WHERE id = ?")
+            .bind(timestamp)
+            .bind(session_ids[index].to_string())
+            .execute(storage.pool())
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "scenario-updated-since-pin-session",
+            })?;
+        sqlx::query("UPDATE messages SET updated_at = ? WHERE id = ?")
+            .bind(timestamp)
+            .bind(message_ids[index].to_string())
+            .execute(storage.pool())
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "scenario-updated-since-pin-message",
+            })?;
+    }
+
+    let scenario_titles = |sessions: Vec<zova_storage::SessionRecord>| {
+        sessions
+            .into_iter()
+            .filter(|session| session.title.starts_with("updated-since-"))
+            .map(|session| session.title)
+            .collect::<Vec<_>>()
+    };
+    let live = scenario_titles(storage.list_sessions_updated_since(2_000, false).context(
+        StorageValidationSnafu {
+            stage: "scenario-updated-since-list-live",
+        },
+    )?);
+    let with_deleted = scenario_titles(storage.list_sessions_updated_since(2_000, true).context(
+        StorageValidationSnafu {
+            stage: "scenario-updated-since-list-deleted",
+        },
+    )?);
+    let messages = storage
+        .list_messages_updated_since(session_ids[0], 2_000)
+        .context(StorageValidationSnafu {
+            stage: "scenario-updated-since-list-messages",
+        })?
+        .into_iter()
+        .map(|message| message.content)
+        .collect::<Vec<_>>();
+
+    let live_ok = live == ["updated-since-mid"];
+    let deleted_ok = with_deleted == ["updated-since-new", "updated-since-mid"];
+    let messages_ok = messages == ["mid", "new"];
+    println!("updated_since_live={live:?}");
+    println!("updated_since_with_deleted={with_deleted:?}");
+    println!("updated_since_messages={messages:?}");
+    if !live_ok || !deleted_ok || !messages_ok {
+        return ScenarioFailedSnafu {
+            stage: "scenario-updated-since-assert",
+            scenario: "updated_since",
+            reason: format!(
+                "live_ok={live_ok}, deleted_ok={deleted_ok}, messages_ok={messages_ok}"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+fn run_message_word_count() -> RunnerResult<()> {
+    let message = |content: &str| zova_storage::MessageRecord {
+        id: MessageId::new_v7(),
+        session_id: SessionId::new_v7(),
+        branch_id: BranchId::new_v7(),
+        seq: 1,
+        role: MessageRole::User,
+        content: content.to_string(),
+        created_at_unix_seconds: 0,
+        deleted_at_unix_seconds: None,
+    };
+
+    let empty = message("  \n ");
+    let single = message("hello");
+    let sentence = message("The quick brown fox jumps over the lazy dog.");
+    let counts = [
+        empty.word_count(),
+        single.word_count(),
+        sentence.word_count(),
+    ];
+    // 9 words at 200 wpm is 2.7s, so the ceiling division must round up to 3.
+    let reading_times = [
+        sentence.reading_time_seconds(200),
+        single.reading_time_seconds(60),
+        sentence.reading_time_seconds(0),
+    ];
+    let empty_flags = [empty.is_empty(), single.is_empty()];
+
+    println!("message_word_counts={counts:?}");
+    println!("message_reading_times={reading_times:?}");
+    if counts != [0, 1, 9] || reading_times != [3, 1, 0] || empty_flags != [true, false] {
+        return ScenarioFailedSnafu {
+            stage: "scenario-message-word-count-assert",
+            scenario: "message_word_count",
+            reason: format!(
+                "counts={counts:?}, reading_times={reading_times:?}, empty_flags={empty_flags:?}"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+fn run_session_display_title() -> RunnerResult<()> {
+    let session = |title: &str| SessionRecord {
+        id: SessionId::new_v7(),
+        title: title.to_string(),
+        active_branch_id: BranchId::new_v7(),
+        pinned: false,
+        metadata_json: None,
+        tags: Vec::new(),
+        created_at_unix_seconds: 0,
+        updated_at_unix_seconds: 0,
+        deleted_at_unix_seconds: None,
+        message_count: None,
+    };
+
+    let sessions = [session(""), session(" \t\n"), session("Trip planning")];
+    let display_titles = sessions
+        .iter()
+        .map(SessionRecord::display_title)
+        .collect::<Vec<_>>();
+    let titled_flags = sessions
+        .iter()
+        .map(SessionRecord::is_titled)
+        .collect::<Vec<_>>();
+
+    println!("session_display_titles={display_titles:?}");
+    println!("session_titled_flags={titled_flags:?}");
+    if display_titles
+        != [
+            DEFAULT_SESSION_TITLE,
+            DEFAULT_SESSION_TITLE,
+            "Trip planning",
+        ]
+        || titled_flags != [false, false, true]
+    {
+        return ScenarioFailedSnafu {
+            stage: "scenario-session-display-title-assert",
+            scenario: "session_display_title",
+            reason: format!("display_titles={display_titles:?}, titled_flags={titled_flags:?}"),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+fn all_error_variants(scenario: &'static str) -> RunnerResult<Vec<StorageError>> {
+    let uuid_error = match SessionId::from_str("not-a-uuid") {
+        Err(StorageError::InvalidId { source, .. }) => source,
+        other => {
+            return ScenarioFailedSnafu {
+                stage: "scenario-error-variants-uuid",
+                scenario,
+                reason: format!("expected invalid id error, got {other:?}"),
+            }
+            .fail();
+        }
+    };
+    let io_error = || std::io::Error::other("qa");
+    Ok(vec![
+        StorageError::NotFound {
+            stage: "qa",
+            entity: "session",
+            id: "qa".to_string(),
+        },
+        StorageError::NotFound {
+            stage: "qa",
+            entity: "other",
+            id: "qa".to_string(),
+        },
+        StorageError::Conflict {
+            stage: "qa",
+            entity: "media_ref",
+            details: "qa".to_string(),
+        },
+        StorageError::InvalidId {
+            stage: "qa",
+            id_type: "SessionId",
+            raw: "not-a-uuid".to_string(),
+            source: uuid_error,
+        },
+        StorageError::InvariantViolation {
+            stage: "qa",
+            details: "qa".to_string(),
+        },
+        StorageError::CreateSqliteDirectory {
+            stage: "qa",
+            path: "qa".to_string(),
+            source: io_error(),
+        },
+        StorageError::SqliteConnectOptions {
+            stage: "qa",
+            database_url: "qa".to_string(),
+            source: sqlx::Error::RowNotFound,
+        },
+        StorageError::SqliteConnect {
+            stage: "qa",
+            database_url: "qa".to_string(),
+            source: sqlx::Error::RowNotFound,
+        },
+        StorageError::SqlitePragma {
+            stage: "qa",
+            pragma: "qa",
+            source: sqlx::Error::RowNotFound,
+        },
+        StorageError::SqliteMigrate {
+            stage: "qa",
+            source: sqlx::migrate::MigrateError::VersionMissing(1),
+        },
+        StorageError::SqliteQuery {
+            stage: "qa",
+            source: sqlx::Error::RowNotFound,
+        },
+        StorageError::SqliteThreadSpawn {
+            stage: "qa",
+            source: io_error(),
+        },
+        StorageError::SqliteRuntimeInit {
+            stage: "qa",
+            source: io_error(),
+        },
+        StorageError::ReadOnly { stage: "qa" },
+        StorageError::Unavailable {
+            stage: "qa",
+            reason: "qa".to_string(),
+        },
+        StorageError::ReadLegacyConversationTsv {
+            stage: "qa",
+            path: "qa".to_string(),
+            source: io_error(),
+        },
+    ])
+}
+
+async fn run_error_transient(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-error-transient-open",
+        })?;
+
+    // Hold the write lock on a pooled connection so a second writer hits SQLITE_BUSY.
+    let mut holder = storage.pool().acquire().await.context(SqliteQuerySnafu {
+        stage: "scenario-error-transient-acquire",
+    })?;
+    sqlx::query("BEGIN IMMEDIATE")
+        .execute(&mut *holder)
+        .await
+        .context(SqliteQuerySnafu {
+            stage: "scenario-error-transient-lock",
+        })?;
+    let options = sqlx::sqlite::SqliteConnectOptions::from_str(&format!("sqlite://{db_path}"))
+        .context(SqliteQuerySnafu {
+            stage: "scenario-error-transient-options",
+        })?
+        .busy_timeout(Duration::ZERO);
+    let mut contender =
+        sqlx::ConnectOptions::connect(&options)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "scenario-error-transient-connect",
+            })?;
+    let busy_result = sqlx::query("BEGIN IMMEDIATE")
+        .execute(&mut contender)
+        .await
+        .map_err(|error| StorageError::from_sqlx("scenario-error-transient-contend", error));
+    sqlx::query("ROLLBACK")
+        .execute(&mut *holder)
+        .await
+        .context(SqliteQuerySnafu {
+            stage: "scenario-error-transient-unlock",
+        })?;
+
+    let busy_error = match busy_result {
+        Err(error) => error,
+        Ok(_) => {
+            return ScenarioFailedSnafu {
+                stage: "scenario-error-transient-busy",
+                scenario: "error_transient",
+                reason: "second writer acquired the lock".to_string(),
+            }
+            .fail();
+        }
+    };
+    let permanent_errors = all_error_variants("error_transient")?;
+    let misclassified: Vec<_> = permanent_errors
+        .iter()
+        .filter(|error| error.is_transient())
+        .map(|error| format!("{error:?}"))
+        .collect();
+
+    println!(
+        "error_transient_busy_code={:?}",
+        busy_error.database_error_code()
+    );
+    println!("error_transient_busy={}", busy_error.is_transient());
+    println!("error_transient_permanent_count={}", permanent_errors.len());
+    println!("error_transient_misclassified={}", misclassified.len());
+    if !busy_error.is_transient() || !misclassified.is_empty() {
+        return ScenarioFailedSnafu {
+            stage: "scenario-error-transient-assert",
+            scenario: "error_transient",
+            reason: format!(
+                "busy_transient={}, misclassified=[{}]",
+                busy_error.is_transient(),
+                misclassified.join(", ")
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+fn run_error_display_messages() -> RunnerResult<()> {
+    let errors = all_error_variants("error_display_messages")?;
+
+    // A user-facing message must never leak the variant name or snafu/debug formatting.
+    let bad_messages: Vec<_> = errors
+        .iter()
+        .filter(|error| {
+            let message = error.display_message();
+            let debug = format!("{error:?}");
+            let variant = debug.split([' ', '{', '(']).next().unwrap_or_default();
+            message.trim().is_empty()
+                || message.contains(variant)
+                || message.contains('{')
+                || message.contains("stage")
+        })
+        .map(|error| format!("{error:?}"))
+        .collect();
+
+    println!("error_display_message_count={}", errors.len());
+    println!("error_display_message_bad={}", bad_messages.len());
+    if !bad_messages.is_empty() {
+        return ScenarioFailedSnafu {
+            stage: "scenario-error-display-messages-assert",
+            scenario: "error_display_messages",
+            reason: format!("bad display messages for: {}", bad_messages.join(", ")),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+fn run_stage_labels_unique() -> RunnerResult<()> {
+    // Only literal `stage: "..."` fields count; helpers that forward a `stage` parameter reuse
+    // the caller's label on purpose.
+    let source = include_str!("../sqlite/mod.rs");
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    for fragment in source.split("stage: \"").skip(1) {
+        let Some((label, _)) = fragment.split_once('"') else {
+            continue;
+        };
+        if !seen.insert(label) {
+            duplicates.push(label);
+        }
+    }
 
-    println!("fk_violation_blocked={fk_violation_blocked}");
-    if !fk_violation_blocked {
+    println!("stage_label_count={}", seen.len());
+    println!("stage_label_duplicates={}", duplicates.len());
+    if !duplicates.is_empty() {
         return ScenarioFailedSnafu {
-            stage: "scenario-fk-violation-assert",
-            scenario: "fk_violation",
-            reason: "orphan message insert unexpectedly succeeded".to_string(),
+            stage: "scenario-stage-labels-unique-assert",
+            scenario: "stage_labels_unique",
+            reason: format!("duplicate stage labels: {}", duplicates.join(", ")),
         }
         .fail();
     }
@@ -465,6 +2391,8 @@ async fn run_session_crud(db_path: &str) -> RunnerResult<()> {
             created_b.id,
             SessionPatch {
                 title: Some("session-b-updated".to_string()),
+                pinned: None,
+                metadata: None,
             },
         )
         .context(StorageValidationSnafu {
@@ -482,7 +2410,7 @@ async fn run_session_crud(db_path: &str) -> RunnerResult<()> {
             stage: "scenario-session-crud-list-after-delete",
         })?;
 
-    storage
+    let restored_a = storage
         .restore_session(created_a.id)
         .context(StorageValidationSnafu {
             stage: "scenario-session-crud-restore-a",
@@ -497,6 +2425,9 @@ async fn run_session_crud(db_path: &str) -> RunnerResult<()> {
     let soft_deleted = 1_i64;
     let restored = 1_i64;
     let list_order_ok = is_session_list_ordered(&active_after_restore);
+    let restored_record_ok = restored_a.id == created_a.id
+        && restored_a.deleted_at_unix_seconds.is_none()
+        && active_after_restore.contains(&restored_a);
 
     println!("created={created}");
     println!("soft_deleted={soft_deleted}");
@@ -504,6 +2435,7 @@ async fn run_session_crud(db_path: &str) -> RunnerResult<()> {
     println!("active_after_delete_count={}", active_after_delete.len());
     println!("active_after_restore_count={}", active_after_restore.len());
     println!("list_order_ok={list_order_ok}");
+    println!("restored_record_ok={restored_record_ok}");
 
     if active_after_delete.len() != 1 {
         return ScenarioFailedSnafu {
@@ -540,6 +2472,186 @@ async fn run_session_crud(db_path: &str) -> RunnerResult<()> {
         .fail();
     }
 
+    if !restored_record_ok {
+        return ScenarioFailedSnafu {
+            stage: "scenario-session-crud-assert-restored-record",
+            scenario: "session_crud",
+            reason: "restore_session did not return the restored session state".to_string(),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_session_pinned_order(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-pinned-order-open",
+        })?;
+
+    let pinned_candidate = storage
+        .create_session(NewSession {
+            title: "pinned-reference".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-pinned-order-create-pinned",
+        })?;
+    let pinned = storage
+        .update_session(
+            pinned_candidate.id,
+            SessionPatch {
+                title: None,
+                pinned: Some(true),
+                metadata: None,
+            },
+        )
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-pinned-order-pin",
+        })?;
+
+    // Newer sessions would normally sort ahead, so they prove pinning overrides recency.
+    for title in ["unpinned-newer-a", "unpinned-newer-b"] {
+        storage
+            .create_session(NewSession {
+                title: title.to_string(),
+            })
+            .context(StorageValidationSnafu {
+                stage: "scenario-session-pinned-order-create-unpinned",
+            })?;
+    }
+
+    let sessions = storage
+        .list_sessions(false)
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-pinned-order-list",
+        })?;
+
+    let pinned_first = sessions
+        .first()
+        .is_some_and(|session| session.id == pinned.id && session.pinned);
+    let list_order_ok = is_session_list_ordered(&sessions);
+
+    let unpinned = storage
+        .update_session(
+            pinned.id,
+            SessionPatch {
+                title: None,
+                pinned: Some(false),
+                metadata: None,
+            },
+        )
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-pinned-order-unpin",
+        })?;
+    let unpin_ok = !unpinned.pinned && unpinned.title == "pinned-reference";
+
+    println!("pinned_first={pinned_first}");
+    println!("pinned_list_order_ok={list_order_ok}");
+    println!("unpin_ok={unpin_ok}");
+
+    if !pinned_first || !list_order_ok {
+        return ScenarioFailedSnafu {
+            stage: "scenario-session-pinned-order-assert-order",
+            scenario: "session_pinned_order",
+            reason: "pinned session was not listed ahead of newer unpinned sessions".to_string(),
+        }
+        .fail();
+    }
+
+    if !unpin_ok {
+        return ScenarioFailedSnafu {
+            stage: "scenario-session-pinned-order-assert-unpin",
+            scenario: "session_pinned_order",
+            reason: "unpin patch did not clear pinned flag or altered the title".to_string(),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_session_search(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-search-open",
+        })?;
+
+    let mut expected_ids = HashSet::new();
+    for index in 0..20 {
+        let title = if index % 2 == 0 {
+            format!("search-needle-{index:02}")
+        } else {
+            format!("search-haystack-{index:02}")
+        };
+        let created =
+            storage
+                .create_session(NewSession { title })
+                .context(StorageValidationSnafu {
+                    stage: "scenario-session-search-create",
+                })?;
+        if index % 2 == 0 {
+            expected_ids.insert(created.id);
+        }
+    }
+
+    let matches = storage
+        .search_sessions("NEEDLE", 50)
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-search-query",
+        })?;
+    let limited = storage
+        .search_sessions("needle", 3)
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-search-limited",
+        })?;
+    let wildcard = storage
+        .search_sessions("%", 50)
+        .context(StorageValidationSnafu {
+            stage: "scenario-session-search-wildcard",
+        })?;
+
+    let matched_ids = matches
+        .iter()
+        .map(|session| session.id)
+        .collect::<HashSet<_>>();
+    let matches_ok = matched_ids == expected_ids;
+    let order_ok = is_session_list_ordered(&matches);
+    let limit_ok = limited.len() == 3
+        && limited
+            .iter()
+            .zip(matches.iter())
+            .all(|(left, right)| left.id == right.id);
+    let wildcard_literal = wildcard.is_empty();
+
+    println!("search_match_count={}", matches.len());
+    println!("search_matches_ok={matches_ok}");
+    println!("search_order_ok={order_ok}");
+    println!("search_limit_ok={limit_ok}");
+    println!("search_wildcard_literal={wildcard_literal}");
+
+    if !matches_ok || !order_ok {
+        return ScenarioFailedSnafu {
+            stage: "scenario-session-search-assert-matches",
+            scenario: "session_search",
+            reason: "title search returned unexpected sessions or ordering".to_string(),
+        }
+        .fail();
+    }
+
+    if !limit_ok || !wildcard_literal {
+        return ScenarioFailedSnafu {
+            stage: "scenario-session-search-assert-limit",
+            scenario: "session_search",
+            reason: "title search ignored the limit or treated '%' as a wildcard".to_string(),
+        }
+        .fail();
+    }
+
     println!("runner_ok=true");
     Ok(())
 }
@@ -682,6 +2794,104 @@ async fn run_history_branch_fork(db_path: &str) -> RunnerResult<()> {
     Ok(())
 }
 
+async fn run_branch_list(db_path: &str) -> RunnerResult<()> {
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-branch-list-open",
+        })?;
+
+    let session = storage
+        .create_session(NewSession {
+            title: "branch-list-session".to_string(),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-branch-list-create-session",
+        })?;
+    let root_branch_id = session.active_branch_id;
+
+    storage
+        .append_message(
+            session.id,
+            NewMessage {
+                role: MessageRole::User,
+                content: "branch-root".to_string(),
+            },
+        )
+        .context(StorageValidationSnafu {
+            stage: "scenario-branch-list-append",
+        })?;
+
+    let mut forked_branch_ids = Vec::new();
+    for replacement in ["branch-edit-a", "branch-edit-b"] {
+        // Each fork must target a message on the currently active branch.
+        let source = storage
+            .list_messages(session.id)
+            .context(StorageValidationSnafu {
+                stage: "scenario-branch-list-list-messages",
+            })?
+            .into_iter()
+            .next()
+            .context(ScenarioFailedSnafu {
+                stage: "scenario-branch-list-source",
+                scenario: "branch_list",
+                reason: "active branch has no message to fork from".to_string(),
+            })?;
+        let outcome = storage
+            .fork_from_history(
+                session.id,
+                HistoryForkRequest {
+                    source_message_id: source.id,
+                    replacement_content: replacement.to_string(),
+                },
+            )
+            .context(StorageValidationSnafu {
+                stage: "scenario-branch-list-fork",
+            })?;
+        forked_branch_ids.push(outcome.new_branch_id);
+    }
+
+    let branches = storage
+        .list_branches(session.id)
+        .context(StorageValidationSnafu {
+            stage: "scenario-branch-list-query",
+        })?;
+
+    let listed_ids = branches
+        .iter()
+        .map(|branch| branch.id)
+        .collect::<HashSet<_>>();
+    let expected_ids = std::iter::once(root_branch_id)
+        .chain(forked_branch_ids.iter().copied())
+        .collect::<HashSet<_>>();
+    let ids_ok = branches.len() == 3 && listed_ids == expected_ids;
+    let root_ok = branches
+        .iter()
+        .any(|branch| branch.id == root_branch_id && branch.parent_branch_id.is_none());
+    let parents_ok = forked_branch_ids.iter().all(|forked_id| {
+        branches
+            .iter()
+            .any(|branch| branch.id == *forked_id && branch.parent_branch_id.is_some())
+    });
+
+    println!("branch_list_count={}", branches.len());
+    println!("branch_list_ids_ok={ids_ok}");
+    println!("branch_list_root_ok={root_ok}");
+    println!("branch_list_parents_ok={parents_ok}");
+
+    if !ids_ok || !root_ok || !parents_ok {
+        return ScenarioFailedSnafu {
+            stage: "scenario-branch-list-assert",
+            scenario: "branch_list",
+            reason: "list_branches did not return the root branch and both forks".to_string(),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
 async fn run_cross_session_guard(db_path: &str) -> RunnerResult<()> {
     let storage = SqliteStorage::open(db_path)
         .await
@@ -1169,6 +3379,274 @@ async fn run_migrate_idempotent(db_path: &str) -> RunnerResult<()> {
     Ok(())
 }
 
+async fn run_migrate_csv_fixture(db_path: &str) -> RunnerResult<()> {
+    reset_sqlite_files(db_path)?;
+    let fixture_path = PathBuf::from(format!("{db_path}.legacy.csv"));
+    std::fs::write(&fixture_path, LEGACY_CSV_FIXTURE).context(FileIoSnafu {
+        stage: "scenario-migrate-csv-fixture-write",
+        path: fixture_path.display().to_string(),
+    })?;
+
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-migrate-csv-fixture-open",
+        })?;
+    let report = storage.import_legacy_conversations_from_path(&fixture_path);
+    let _ = std::fs::remove_file(&fixture_path);
+    let report = report.context(StorageValidationSnafu {
+        stage: "scenario-migrate-csv-fixture-import",
+    })?;
+
+    let sessions = storage
+        .list_sessions(false)
+        .context(StorageValidationSnafu {
+            stage: "scenario-migrate-csv-fixture-list",
+        })?;
+    let titles_in_order: Vec<_> = sessions
+        .iter()
+        .map(|session| session.title.as_str())
+        .collect();
+
+    let detected_csv = LegacyImportFormat::detect(LEGACY_CSV_FIXTURE) == LegacyImportFormat::Csv;
+    let detected_tsv =
+        LegacyImportFormat::detect(TASK6_VALID_TSV_FIXTURE) == LegacyImportFormat::Tsv;
+    let titles_ok = titles_in_order
+        == [
+            "Plain title",
+            "She said \"hi\", then left",
+            "Budget, Q3 review",
+        ];
+    println!("migrate_csv_detected={detected_csv}");
+    println!("migrate_csv_imported_sessions={}", report.imported_sessions);
+    println!("migrate_csv_titles={titles_in_order:?}");
+    if !detected_csv || !detected_tsv || report.imported_sessions != 3 || !titles_ok {
+        return ScenarioFailedSnafu {
+            stage: "scenario-migrate-csv-fixture-assert",
+            scenario: "migrate_csv_fixture",
+            reason: format!(
+                "csv import mismatch: detected_csv={detected_csv}, detected_tsv={detected_tsv}, report={report:?}, titles={titles_in_order:?}"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_migrate_session_id_map(db_path: &str) -> RunnerResult<()> {
+    reset_sqlite_files(db_path)?;
+    let _fixture_guard = LegacyFixtureGuard::install(TASK6_VALID_TSV_FIXTURE)?;
+
+    let storage = SqliteStorage::open(db_path)
+        .await
+        .context(StorageValidationSnafu {
+            stage: "scenario-migrate-session-id-map-open",
+        })?;
+    let first_import = storage
+        .import_legacy_conversations_from_default_path()
+        .context(StorageValidationSnafu {
+            stage: "scenario-migrate-session-id-map-import-first",
+        })?;
+    let second_import = storage
+        .import_legacy_conversations_from_default_path()
+        .context(StorageValidationSnafu {
+            stage: "scenario-migrate-session-id-map-import-second",
+        })?;
+
+    let mut mapped_titles = Vec::new();
+    for (legacy_id, expected_title) in [(10, "Legacy Old"), (11, "Legacy Mid"), (12, "Legacy New")]
+    {
+        let Some(session_id) = first_import.session_id_map.get(&legacy_id).copied() else {
+            return ScenarioFailedSnafu {
+                stage: "scenario-migrate-session-id-map-assert-key",
+                scenario: "migrate_session_id_map",
+                reason: format!(
+                    "legacy id {legacy_id} missing from session_id_map={:?}",
+                    first_import.session_id_map
+                ),
+            }
+            .fail();
+        };
+        let session = storage
+            .get_session(session_id)
+            .context(StorageValidationSnafu {
+                stage: "scenario-migrate-session-id-map-get",
+            })?;
+        let title_matches = session
+            .as_ref()
+            .is_some_and(|session| session.title == expected_title);
+        if !title_matches {
+            return ScenarioFailedSnafu {
+                stage: "scenario-migrate-session-id-map-assert-session",
+                scenario: "migrate_session_id_map",
+                reason: format!(
+                    "legacy id {legacy_id} mapped to {session_id} but get_session returned {session:?}, expected title {expected_title:?}"
+                ),
+            }
+            .fail();
+        }
+        mapped_titles.push(expected_title);
+    }
+
+    let map_len = first_import.session_id_map.len();
+    let rerun_map_empty = second_import.session_id_map.is_empty();
+    println!("session_id_map_len={map_len}");
+    println!("session_id_map_titles={mapped_titles:?}");
+    println!("session_id_map_rerun_empty={rerun_map_empty}");
+
+    if map_len != 3 || !rerun_map_empty {
+        return ScenarioFailedSnafu {
+            stage: "scenario-migrate-session-id-map-assert-len",
+            scenario: "migrate_session_id_map",
+            reason: format!(
+                "expected 3 mapped sessions and an empty map on re-import, got first={:?}, second={:?}",
+                first_import.session_id_map, second_import.session_id_map
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+async fn run_pool_size_options(db_path: &str) -> RunnerResult<()> {
+    reset_sqlite_files(db_path)?;
+
+    let pooled = time_locked_store_readers(db_path, POOLED_READER_CONNECTIONS).await?;
+    let single = time_locked_store_readers(db_path, 1).await?;
+    let wal_file_present = Path::new(&format!("{db_path}-wal")).exists();
+
+    println!("pool_busy_readers={}", pooled.busy_readers);
+    println!("pool_concurrent_peak_in_flight={}", pooled.peak_in_flight);
+    println!("pool_concurrent_elapsed_ms={}", pooled.elapsed.as_millis());
+    println!("pool_single_busy_readers={}", single.busy_readers);
+    println!("pool_single_peak_in_flight={}", single.peak_in_flight);
+    println!("pool_single_elapsed_ms={}", single.elapsed.as_millis());
+    println!("pool_rollback_wal_file_present={wal_file_present}");
+
+    let readers = POOLED_READER_CONNECTIONS as usize;
+    // Every reader holds its permit for a whole busy timeout, so a full pool lets all of them in
+    // at once while a single permit only ever admits one.
+    if pooled.busy_readers != readers
+        || single.busy_readers != readers
+        || pooled.peak_in_flight != readers
+        || single.peak_in_flight != 1
+        || wal_file_present
+    {
+        return ScenarioFailedSnafu {
+            stage: "scenario-pool-size-options-assert",
+            scenario: "pool_size_options",
+            reason: format!(
+                "pool options mismatch: pooled={pooled:?}, single={single:?}, wal_file_present={wal_file_present}"
+            ),
+        }
+        .fail();
+    }
+
+    println!("runner_ok=true");
+    Ok(())
+}
+
+#[derive(Debug)]
+struct LockedReaderRun {
+    elapsed: Duration,
+    busy_readers: usize,
+    peak_in_flight: usize,
+}
+
+/// Runs `POOLED_READER_CONNECTIONS` concurrent `list_sessions` calls while another connection
+/// holds an exclusive lock, recording how many calls failed as busy and the most that held a
+/// store permit at once.
+async fn time_locked_store_readers(
+    db_path: &str,
+    max_connections: u32,
+) -> RunnerResult<LockedReaderRun> {
+    // Rollback journaling lets one exclusive transaction block readers, so every store call
+    // has to wait out its whole busy timeout.
+    let storage = SqliteStorage::open_with_options(
+        db_path,
+        SqliteStorageOptions {
+            max_connections,
+            busy_timeout_ms: POOL_SCENARIO_BUSY_TIMEOUT.as_millis() as u64,
+            enable_wal: false,
+        },
+    )
+    .await
+    .context(StorageValidationSnafu {
+        stage: "scenario-pool-size-options-open",
+    })?;
+    storage
+        .create_session(NewSession {
+            title: format!("pool-readers-{max_connections}"),
+        })
+        .context(StorageValidationSnafu {
+            stage: "scenario-pool-size-options-create",
+        })?;
+
+    let mut lock = storage.pool().acquire().await.context(SqliteQuerySnafu {
+        stage: "scenario-pool-size-options-acquire-lock",
+    })?;
+    sqlx::query("BEGIN EXCLUSIVE")
+        .execute(&mut *lock)
+        .await
+        .context(SqliteQuerySnafu {
+            stage: "scenario-pool-size-options-begin-exclusive",
+        })?;
+
+    let readers = POOLED_READER_CONNECTIONS as usize;
+    let permits = storage.available_store_permits();
+    let barrier = Barrier::new(readers);
+    let finished_readers = AtomicUsize::new(0);
+    let started_at = Instant::now();
+    let (outcomes, peak_in_flight) = std::thread::scope(|scope| {
+        let handles = (0..readers)
+            .map(|_| {
+                scope.spawn(|| {
+                    barrier.wait();
+                    let outcome = storage.list_sessions(false).map(|_| ());
+                    finished_readers.fetch_add(1, atomic::Ordering::SeqCst);
+                    outcome
+                })
+            })
+            .collect::<Vec<_>>();
+        let monitor = scope.spawn(|| {
+            let mut peak_in_flight = 0;
+            while finished_readers.load(atomic::Ordering::SeqCst) < readers {
+                let in_flight = permits.saturating_sub(storage.available_store_permits());
+                peak_in_flight = peak_in_flight.max(in_flight);
+                std::thread::yield_now();
+            }
+            peak_in_flight
+        });
+        let outcomes = handles
+            .into_iter()
+            .map(|handle| handle.join())
+            .collect::<Vec<_>>();
+        (outcomes, monitor.join().unwrap_or_default())
+    });
+    let elapsed = started_at.elapsed();
+
+    sqlx::query("ROLLBACK")
+        .execute(&mut *lock)
+        .await
+        .context(SqliteQuerySnafu {
+            stage: "scenario-pool-size-options-rollback",
+        })?;
+
+    let busy_readers = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome, Ok(Err(error)) if error.is_transient()))
+        .count();
+    Ok(LockedReaderRun {
+        elapsed,
+        busy_readers,
+        peak_in_flight,
+    })
+}
+
 async fn run_migrate_malformed_row(db_path: &str) -> RunnerResult<()> {
     reset_sqlite_files(db_path)?;
     let _fixture_guard = LegacyFixtureGuard::install(TASK6_MALFORMED_TSV_FIXTURE)?;
@@ -1249,6 +3727,8 @@ const TASK6_VALID_TSV_FIXTURE: &str =
 
 const TASK6_MALFORMED_TSV_FIXTURE: &str = "21\t1700001000\tValid One\nnot-a-number\t1700002000\tBroken Id\n22\tnot-a-timestamp\tBroken Timestamp\n23\t1700003000\n24\t1700004000\t   \n";
 
+const LEGACY_CSV_FIXTURE: &str = "31,1700005000,\"Budget, Q3 review\"\n32,1700006000,\"She said \"\"hi\"\", then left\"\n33,1700007000,Plain title\n";
+
 #[derive(Debug)]
 struct LegacyFixtureGuard {
     path: PathBuf,
@@ -1358,6 +3838,10 @@ fn is_session_list_ordered(sessions: &[zova_storage::SessionRecord]) -> bool {
         let left = &pair[0];
         let right = &pair[1];
 
+        if left.pinned != right.pinned {
+            return left.pinned;
+        }
+
         if left.updated_at_unix_seconds != right.updated_at_unix_seconds {
             return left.updated_at_unix_seconds > right.updated_at_unix_seconds;
         }
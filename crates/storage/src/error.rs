@@ -1,5 +1,8 @@
 use snafu::Snafu;
 
+const SQLITE_BUSY: i32 = 5;
+const SQLITE_LOCKED: i32 = 6;
+
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub(crate)))]
 pub enum StorageError {
@@ -71,6 +74,10 @@ pub enum StorageError {
         stage: &'static str,
         source: std::io::Error,
     },
+    #[snafu(display("storage was opened read-only at {stage}"))]
+    ReadOnly { stage: &'static str },
+    #[snafu(display("storage is unavailable: {reason}"))]
+    Unavailable { stage: &'static str, reason: String },
     #[snafu(display("failed to read legacy conversation TSV from {path}"))]
     ReadLegacyConversationTsv {
         stage: &'static str,
@@ -79,4 +86,75 @@ pub enum StorageError {
     },
 }
 
+impl StorageError {
+    /// Wraps a raw sqlx error as a query failure tagged with `stage`.
+    pub fn from_sqlx(stage: &'static str, source: sqlx::Error) -> Self {
+        Self::SqliteQuery { stage, source }
+    }
+
+    /// Returns a short, non-technical description suitable for showing to users.
+    pub fn display_message(&self) -> &'static str {
+        match self {
+            Self::NotFound { entity, .. } => match *entity {
+                "session" => "Conversation was deleted",
+                "message" => "Message was deleted",
+                "branch" => "Conversation branch was deleted",
+                "media_ref" => "Attachment was deleted",
+                "agent_event" => "Activity record was deleted",
+                _ => "Item not found",
+            },
+            Self::Conflict { entity, .. } => match *entity {
+                "media_ref" => "Attachment URI format not supported",
+                _ => "Change conflicts with existing data",
+            },
+            Self::InvalidId { .. } => "Invalid identifier",
+            Self::InvariantViolation { .. } => "Stored data is inconsistent",
+            Self::CreateSqliteDirectory { .. } => "Could not create the database folder",
+            Self::SqliteConnectOptions { .. } => "Database location is invalid",
+            Self::SqliteConnect { .. } => "Database not found",
+            Self::SqlitePragma { .. } => "Could not configure the database",
+            Self::SqliteMigrate { .. } => "Could not upgrade the database",
+            Self::SqliteQuery { .. } => "Database operation failed",
+            Self::SqliteThreadSpawn { .. } | Self::SqliteRuntimeInit { .. } => {
+                "Could not start the database worker"
+            }
+            Self::ReadOnly { .. } => "Database is open in read-only mode",
+            Self::Unavailable { .. } => "Storage is unavailable",
+            Self::ReadLegacyConversationTsv { .. } => "Could not read old conversation history",
+        }
+    }
+
+    /// Returns true when retrying may succeed because SQLite reported the database as busy or
+    /// locked by another connection; every other failure is permanent.
+    pub fn is_transient(&self) -> bool {
+        if !matches!(self, Self::SqliteQuery { .. }) {
+            return false;
+        }
+
+        self.database_error_code()
+            .and_then(|code| code.parse::<i32>().ok())
+            // Extended result codes keep the primary code in the low byte.
+            .is_some_and(|code| matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED))
+    }
+
+    /// Returns the SQLite extended result code (e.g. `"2067"` for a UNIQUE violation) when the
+    /// failure came from the database engine itself.
+    pub fn database_error_code(&self) -> Option<String> {
+        let source = match self {
+            Self::SqliteConnectOptions { source, .. }
+            | Self::SqliteConnect { source, .. }
+            | Self::SqlitePragma { source, .. }
+            | Self::SqliteQuery { source, .. } => source,
+            _ => return None,
+        };
+
+        match source {
+            sqlx::Error::Database(database_error) => {
+                database_error.code().map(|code| code.into_owned())
+            }
+            _ => None,
+        }
+    }
+}
+
 pub type StorageResult<T> = Result<T, StorageError>;
@@ -5,24 +5,43 @@ pub mod types;
 
 pub use error::{StorageError, StorageResult};
 pub use ids::{AgentEventId, BranchId, MediaRefId, MessageId, SessionId};
-pub use sqlite::SqliteStorage;
+pub use sqlite::{SqliteStorage, SqliteStorageOptions};
 pub use types::{
-    AgentEventRecord, DEFAULT_SESSION_TITLE, HistoryForkOutcome, HistoryForkRequest,
-    MediaRefRecord, MessageIdRemap, MessagePatch, MessageRecord, MessageRole, NewAgentEvent,
-    NewMediaRef, NewMessage, NewSession, SessionPatch, SessionRecord,
+    AgentEventRecord, BranchRecord, CheckpointResult, DEFAULT_SESSION_TITLE, HistoryForkOutcome,
+    HistoryForkRequest, MediaRefRecord, MessageIdRemap, MessagePatch, MessageRecord, MessageRole,
+    MessageSearchHit, NewAgentEvent, NewMediaRef, NewMessage, NewSession, SessionPatch,
+    SessionRecord,
 };
 
 pub trait SessionStore: Send + Sync {
     fn create_session(&self, input: NewSession) -> StorageResult<SessionRecord>;
     fn list_sessions(&self, include_deleted: bool) -> StorageResult<Vec<SessionRecord>>;
+    fn list_sessions_with_counts(&self, include_deleted: bool)
+    -> StorageResult<Vec<SessionRecord>>;
+    fn search_sessions(&self, query: &str, limit: usize) -> StorageResult<Vec<SessionRecord>>;
+    /// Returns sessions whose `updated_at` is at or after `cutoff_unix_seconds`, most recent first.
+    fn list_sessions_updated_since(
+        &self,
+        cutoff_unix_seconds: u64,
+        include_deleted: bool,
+    ) -> StorageResult<Vec<SessionRecord>>;
     fn get_session(&self, session_id: SessionId) -> StorageResult<Option<SessionRecord>>;
     fn update_session(
         &self,
         session_id: SessionId,
         patch: SessionPatch,
     ) -> StorageResult<SessionRecord>;
+    fn update_session_metadata(
+        &self,
+        session_id: SessionId,
+        metadata: serde_json::Value,
+    ) -> StorageResult<SessionRecord>;
     fn soft_delete_session(&self, session_id: SessionId) -> StorageResult<()>;
-    fn restore_session(&self, session_id: SessionId) -> StorageResult<()>;
+    fn restore_session(&self, session_id: SessionId) -> StorageResult<SessionRecord>;
+}
+
+pub trait BranchStore: Send + Sync {
+    fn list_branches(&self, session_id: SessionId) -> StorageResult<Vec<BranchRecord>>;
 }
 
 pub trait MessageStore: Send + Sync {
@@ -32,6 +51,21 @@ pub trait MessageStore: Send + Sync {
         input: NewMessage,
     ) -> StorageResult<MessageRecord>;
     fn list_messages(&self, session_id: SessionId) -> StorageResult<Vec<MessageRecord>>;
+    fn list_messages_by_role(
+        &self,
+        session_id: SessionId,
+        role: MessageRole,
+    ) -> StorageResult<Vec<MessageRecord>>;
+    /// Returns live messages on the active branch whose `updated_at` is at or after
+    /// `cutoff_unix_seconds`, in sequence order.
+    fn list_messages_updated_since(
+        &self,
+        session_id: SessionId,
+        cutoff_unix_seconds: u64,
+    ) -> StorageResult<Vec<MessageRecord>>;
+    /// Returns up to `limit` live messages on the active branch of any live session whose
+    /// content contains `query`, most recent first, each with its session.
+    fn search_messages(&self, query: &str, limit: usize) -> StorageResult<Vec<MessageSearchHit>>;
     fn get_message(
         &self,
         session_id: SessionId,
@@ -43,11 +77,27 @@ pub trait MessageStore: Send + Sync {
         message_id: MessageId,
         patch: MessagePatch,
     ) -> StorageResult<MessageRecord>;
+    /// Marks a live message on the session's active branch as deleted so it drops out of
+    /// `list_messages`; messages on other branches are rejected as conflicts.
+    fn soft_delete_message(
+        &self,
+        session_id: SessionId,
+        message_id: MessageId,
+    ) -> StorageResult<()>;
     fn fork_from_history(
         &self,
         session_id: SessionId,
         request: HistoryForkRequest,
     ) -> StorageResult<HistoryForkOutcome>;
+    /// Hard-deletes every message on `branch_id` and points the session at a fresh, empty
+    /// branch, returning its ID.
+    ///
+    /// Fails with `StorageError::Conflict` unless `branch_id` is the session's active branch.
+    fn delete_all_messages(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+    ) -> StorageResult<BranchId>;
 }
 
 pub trait MediaStore: Send + Sync {
@@ -84,6 +134,18 @@ pub trait AgentEventStore: Send + Sync {
     ) -> StorageResult<Vec<AgentEventRecord>>;
 }
 
-pub trait Storage: SessionStore + MessageStore + MediaStore + AgentEventStore {}
+pub trait DraftStore: Send + Sync {
+    /// Stores unsent input text for a session; empty content clears the draft.
+    fn save_draft(&self, session_id: SessionId, content: &str) -> StorageResult<()>;
+    fn load_draft(&self, session_id: SessionId) -> StorageResult<Option<String>>;
+}
 
-impl<T> Storage for T where T: SessionStore + MessageStore + MediaStore + AgentEventStore {}
+pub trait Storage:
+    SessionStore + BranchStore + MessageStore + MediaStore + AgentEventStore + DraftStore
+{
+}
+
+impl<T> Storage for T where
+    T: SessionStore + BranchStore + MessageStore + MediaStore + AgentEventStore + DraftStore
+{
+}
@@ -1,16 +1,19 @@
+use std::collections::HashMap;
 use std::future::Future;
 use std::num::ParseIntError;
 use std::path::Path;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Duration;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use snafu::{OptionExt, ResultExt};
 use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions};
 use sqlx::{Connection, FromRow, SqliteConnection, SqlitePool};
+use tokio::sync::Semaphore;
 
 use super::error::{
-    ConflictSnafu, InvariantViolationSnafu, NotFoundSnafu, SqliteQuerySnafu,
+    ConflictSnafu, InvariantViolationSnafu, NotFoundSnafu, ReadOnlySnafu, SqliteQuerySnafu,
     SqliteRuntimeInitSnafu, SqliteThreadSpawnSnafu,
 };
 use super::error::{
@@ -19,14 +22,44 @@ use super::error::{
 };
 use super::ids::{AgentEventId, BranchId, MediaRefId, MessageId, SessionId};
 use super::types::{
-    AgentEventRecord, DEFAULT_SESSION_TITLE, HistoryForkOutcome, HistoryForkRequest,
-    MediaRefRecord, MessageIdRemap, MessagePatch, MessageRecord, MessageRole, NewAgentEvent,
-    NewMediaRef, NewMessage, NewSession, SessionPatch, SessionRecord,
+    AgentEventRecord, BranchRecord, CheckpointResult, DEFAULT_SESSION_TITLE, HistoryForkOutcome,
+    HistoryForkRequest, MediaRefRecord, MessageIdRemap, MessagePatch, MessageRecord, MessageRole,
+    MessageSearchHit, NewAgentEvent, NewMediaRef, NewMessage, NewSession, SessionPatch,
+    SessionRecord,
 };
-use super::{AgentEventStore, MediaStore, MessageStore, SessionStore};
+use super::{AgentEventStore, BranchStore, DraftStore, MediaStore, MessageStore, SessionStore};
 
 pub const LEGACY_CONVERSATIONS_TSV_RELATIVE_PATH: &str = ".zova/conversations.tsv";
 
+/// Field layout of a legacy conversation export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyImportFormat {
+    Tsv,
+    Csv,
+}
+
+impl LegacyImportFormat {
+    /// Picks CSV only when every non-empty line has more commas than tabs, so TSV titles that
+    /// happen to contain commas keep importing as before.
+    pub fn detect(source: &str) -> Self {
+        let mut lines = source
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .peekable();
+        if lines.peek().is_none() {
+            return Self::Tsv;
+        }
+
+        let all_comma_separated =
+            lines.all(|line| line.matches(',').count() > line.matches('\t').count());
+        if all_comma_separated {
+            Self::Csv
+        } else {
+            Self::Tsv
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LegacyImportWarning {
     pub line_number: usize,
@@ -41,6 +74,8 @@ pub struct LegacyImportReport {
     pub skipped_rows: usize,
     pub warnings: Vec<LegacyImportWarning>,
     pub already_migrated: bool,
+    /// New session id assigned to each imported legacy conversation id.
+    pub session_id_map: HashMap<u64, SessionId>,
 }
 
 #[derive(Debug, Clone)]
@@ -50,17 +85,64 @@ struct LegacyConversationRow {
     title: String,
 }
 
+/// Connection pool settings for [`SqliteStorage::open_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SqliteStorageOptions {
+    /// Upper bound on open connections, shared by the pool and concurrent store calls; the
+    /// desktop app only ever needs one.
+    pub max_connections: u32,
+    /// How long each connection waits on a locked database before failing with `SQLITE_BUSY`.
+    pub busy_timeout_ms: u64,
+    /// Switches the database to write-ahead logging so readers do not block the writer.
+    pub enable_wal: bool,
+}
+
+impl Default for SqliteStorageOptions {
+    fn default() -> Self {
+        Self {
+            max_connections: 1,
+            busy_timeout_ms: 5_000,
+            enable_wal: true,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SqliteStorage {
     pool: SqlitePool,
+    store_connection: StoreConnectionConfig,
+    // Store calls open their own connection, so these permits are what caps them at
+    // `max_connections`.
+    store_permits: Arc<Semaphore>,
+    readonly: bool,
+}
+
+/// Settings every per-call store connection is opened with.
+#[derive(Debug, Clone)]
+struct StoreConnectionConfig {
     database_url: String,
+    busy_timeout_ms: u64,
 }
 
 impl SqliteStorage {
     pub async fn open(database_location: &str) -> StorageResult<Self> {
+        Self::open_with_options(database_location, SqliteStorageOptions::default()).await
+    }
+
+    /// Opens the database like [`SqliteStorage::open`] but with caller-provided pool settings,
+    /// e.g. a larger `max_connections` for server deployments.
+    pub async fn open_with_options(
+        database_location: &str,
+        options: SqliteStorageOptions,
+    ) -> StorageResult<Self> {
         ensure_database_directory(database_location)?;
 
         let database_url = normalize_database_url(database_location);
+        let (journal_mode, journal_mode_name) = if options.enable_wal {
+            (SqliteJournalMode::Wal, "WAL")
+        } else {
+            (SqliteJournalMode::Delete, "DELETE")
+        };
         let connect_options = SqliteConnectOptions::from_str(&database_url)
             .context(SqliteConnectOptionsSnafu {
                 stage: "sqlite-open-parse-url",
@@ -68,11 +150,11 @@ impl SqliteStorage {
             })?
             .create_if_missing(true)
             .foreign_keys(true)
-            .journal_mode(SqliteJournalMode::Wal)
-            .busy_timeout(Duration::from_millis(5_000));
+            .journal_mode(journal_mode)
+            .busy_timeout(Duration::from_millis(options.busy_timeout_ms));
 
         let pool = SqlitePoolOptions::new()
-            .max_connections(1)
+            .max_connections(options.max_connections)
             .connect_with(connect_options)
             .await
             .context(SqliteConnectSnafu {
@@ -81,7 +163,8 @@ impl SqliteStorage {
             })?;
 
         // Explicit PRAGMA writes make bootstrap behavior deterministic for QA checks.
-        let _: String = sqlx::query_scalar("PRAGMA journal_mode = WAL;")
+        let journal_mode_pragma = format!("PRAGMA journal_mode = {journal_mode_name};");
+        let _: String = sqlx::query_scalar(&journal_mode_pragma)
             .fetch_one(&pool)
             .await
             .context(SqlitePragmaSnafu {
@@ -95,13 +178,16 @@ impl SqliteStorage {
                 stage: "sqlite-open-pragma-foreign-keys",
                 pragma: "foreign_keys",
             })?;
-        sqlx::query("PRAGMA busy_timeout = 5000;")
-            .execute(&pool)
-            .await
-            .context(SqlitePragmaSnafu {
-                stage: "sqlite-open-pragma-busy-timeout",
-                pragma: "busy_timeout",
-            })?;
+        sqlx::query(&format!(
+            "PRAGMA busy_timeout = {};",
+            options.busy_timeout_ms
+        ))
+        .execute(&pool)
+        .await
+        .context(SqlitePragmaSnafu {
+            stage: "sqlite-open-pragma-busy-timeout",
+            pragma: "busy_timeout",
+        })?;
 
         sqlx::migrate!("./migrations")
             .run(&pool)
@@ -110,13 +196,66 @@ impl SqliteStorage {
                 stage: "sqlite-open-migrate",
             })?;
 
-        Ok(Self { pool, database_url })
+        Ok(Self {
+            pool,
+            store_connection: StoreConnectionConfig {
+                database_url,
+                busy_timeout_ms: options.busy_timeout_ms,
+            },
+            store_permits: store_permits(options.max_connections),
+            readonly: false,
+        })
+    }
+
+    /// Opens an existing database without taking write locks or running migrations.
+    ///
+    /// Every mutating call on the returned instance fails with `StorageError::ReadOnly`.
+    pub async fn open_readonly(database_location: &str) -> StorageResult<Self> {
+        let database_url = readonly_database_url(&normalize_database_url(database_location));
+        let connect_options = SqliteConnectOptions::from_str(&database_url)
+            .context(SqliteConnectOptionsSnafu {
+                stage: "sqlite-open-readonly-parse-url",
+                database_url: database_url.clone(),
+            })?
+            .read_only(true)
+            .busy_timeout(Duration::from_millis(
+                SqliteStorageOptions::default().busy_timeout_ms,
+            ));
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(connect_options)
+            .await
+            .context(SqliteConnectSnafu {
+                stage: "sqlite-open-readonly-connect",
+                database_url: database_url.clone(),
+            })?;
+
+        let options = SqliteStorageOptions::default();
+        Ok(Self {
+            pool,
+            store_connection: StoreConnectionConfig {
+                database_url,
+                busy_timeout_ms: options.busy_timeout_ms,
+            },
+            store_permits: store_permits(options.max_connections),
+            readonly: true,
+        })
+    }
+
+    pub fn is_readonly(&self) -> bool {
+        self.readonly
     }
 
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
 
+    /// How many more store calls can start before new ones queue for a connection permit.
+    pub fn available_store_permits(&self) -> usize {
+        self.store_permits.available_permits()
+    }
+
     pub fn import_legacy_conversations_from_default_path(
         &self,
     ) -> StorageResult<LegacyImportReport> {
@@ -129,6 +268,7 @@ impl SqliteStorage {
         &self,
         legacy_tsv_path: &Path,
     ) -> StorageResult<LegacyImportReport> {
+        self.ensure_writable("legacy-import-readonly")?;
         let source_path = legacy_tsv_path.display().to_string();
         let source_text = match std::fs::read_to_string(legacy_tsv_path) {
             Ok(contents) => Some(contents),
@@ -150,14 +290,16 @@ impl SqliteStorage {
                 skipped_rows: 0,
                 warnings: Vec::new(),
                 already_migrated: false,
+                session_id_map: HashMap::new(),
             });
         };
 
-        let (legacy_rows, warnings) = parse_legacy_conversation_rows(&source_text);
+        let format = LegacyImportFormat::detect(&source_text);
+        let (legacy_rows, warnings) = parse_legacy_conversation_rows(&source_text, format);
         let imported_candidates = legacy_rows.len();
-        let database_url = self.database_url.clone();
-        let (imported_sessions, already_migrated) = self.run_db_call("legacy-session-import", async move {
-            let mut connection = connect_store_connection(&database_url, "legacy-import-connect").await?;
+        let store_connection = self.store_connection.clone();
+        let (imported_sessions, already_migrated, session_id_map) = self.run_db_call("legacy-session-import", async move {
+            let mut connection = connect_store_connection(&store_connection, "legacy-import-connect").await?;
             let existing_sessions = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM sessions")
                 .fetch_one(&mut connection)
                 .await
@@ -167,17 +309,18 @@ impl SqliteStorage {
 
             // Migration is idempotent: once sqlite has session rows, import is treated as applied.
             if existing_sessions > 0 {
-                return Ok((0_usize, true));
+                return Ok((0_usize, true, HashMap::new()));
             }
 
             if imported_candidates == 0 {
-                return Ok((0_usize, false));
+                return Ok((0_usize, false, HashMap::new()));
             }
 
             let mut tx = connection.begin().await.context(SqliteQuerySnafu {
                 stage: "legacy-import-begin",
             })?;
 
+            let mut session_id_map = HashMap::with_capacity(imported_candidates);
             // Insert sessions and initial branches atomically so active_branch invariants remain consistent.
             for row in legacy_rows {
                 let session_id = SessionId::new_v7();
@@ -209,13 +352,15 @@ impl SqliteStorage {
                 .context(SqliteQuerySnafu {
                     stage: "legacy-import-insert-branch",
                 })?;
+
+                session_id_map.insert(row.legacy_id, session_id);
             }
 
             tx.commit().await.context(SqliteQuerySnafu {
                 stage: "legacy-import-commit",
             })?;
 
-            Ok((imported_candidates, false))
+            Ok((imported_candidates, false, session_id_map))
         })?;
 
         Ok(LegacyImportReport {
@@ -225,9 +370,90 @@ impl SqliteStorage {
             skipped_rows: warnings.len(),
             warnings,
             already_migrated,
+            session_id_map,
+        })
+    }
+
+    /// Rebuilds the database file and returns how many pages were released back to the OS.
+    pub fn vacuum(&self) -> StorageResult<u64> {
+        self.ensure_writable("storage-vacuum-readonly")?;
+        let store_connection = self.store_connection.clone();
+        self.run_db_call("storage-vacuum", async move {
+            let mut connection =
+                connect_store_connection(&store_connection, "storage-vacuum-connect").await?;
+            let pages_before =
+                load_page_count(&mut connection, "storage-vacuum-page-count-before").await?;
+
+            // Checkpoint first so pages still living in the WAL are folded back before rebuilding.
+            sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
+                .execute(&mut connection)
+                .await
+                .context(SqlitePragmaSnafu {
+                    stage: "storage-vacuum-wal-checkpoint",
+                    pragma: "wal_checkpoint",
+                })?;
+            sqlx::query("VACUUM;")
+                .execute(&mut connection)
+                .await
+                .context(SqliteQuerySnafu {
+                    stage: "storage-vacuum-run",
+                })?;
+
+            let pages_after =
+                load_page_count(&mut connection, "storage-vacuum-page-count-after").await?;
+            Ok(pages_before.saturating_sub(pages_after))
+        })
+    }
+
+    /// Runs a passive WAL checkpoint, copying committed frames back without blocking writers.
+    pub fn checkpoint(&self) -> StorageResult<CheckpointResult> {
+        self.ensure_writable("storage-checkpoint-readonly")?;
+        let store_connection = self.store_connection.clone();
+        self.run_db_call("storage-checkpoint", async move {
+            let mut connection =
+                connect_store_connection(&store_connection, "storage-checkpoint-connect").await?;
+            let (_busy, wal_frames, checkpointed_frames) =
+                sqlx::query_as::<_, (i64, i64, i64)>("PRAGMA wal_checkpoint(PASSIVE);")
+                    .fetch_one(&mut connection)
+                    .await
+                    .context(SqlitePragmaSnafu {
+                        stage: "storage-checkpoint-run",
+                        pragma: "wal_checkpoint",
+                    })?;
+
+            Ok(CheckpointResult {
+                wal_frames: i64_to_u32(wal_frames, "storage-checkpoint-wal-frames")?,
+                checkpointed_frames: i64_to_u32(
+                    checkpointed_frames,
+                    "storage-checkpoint-checkpointed-frames",
+                )?,
+            })
         })
     }
 
+    pub fn database_size_bytes(&self) -> StorageResult<u64> {
+        let store_connection = self.store_connection.clone();
+        self.run_db_call("storage-size", async move {
+            let mut connection =
+                connect_store_connection(&store_connection, "storage-size-connect").await?;
+            let page_count = load_page_count(&mut connection, "storage-size-page-count").await?;
+            let page_size = sqlx::query_scalar::<_, i64>("PRAGMA page_size;")
+                .fetch_one(&mut connection)
+                .await
+                .context(SqliteQuerySnafu {
+                    stage: "storage-size-page-size",
+                })?;
+            Ok(page_count.saturating_mul(i64_to_u64(page_size, "storage-size-page-size-convert")?))
+        })
+    }
+
+    fn ensure_writable(&self, stage: &'static str) -> StorageResult<()> {
+        if self.readonly {
+            return ReadOnlySnafu { stage }.fail();
+        }
+        Ok(())
+    }
+
     fn run_db_call<T, F>(&self, stage: &'static str, op: F) -> StorageResult<T>
     where
         T: Send + 'static,
@@ -235,6 +461,7 @@ impl SqliteStorage {
     {
         // Store traits are sync, so each call executes on a dedicated worker thread
         // with its own current-thread runtime to avoid nested-runtime blocking panics.
+        let permits = Arc::clone(&self.store_permits);
         let worker = std::thread::Builder::new()
             .name(format!("sqlite-store-{stage}"))
             .spawn(move || {
@@ -244,7 +471,16 @@ impl SqliteStorage {
                     .context(SqliteRuntimeInitSnafu {
                         stage: "sqlite-store-runtime-build",
                     })?;
-                runtime.block_on(op)
+                runtime.block_on(async move {
+                    let Ok(_permit) = permits.acquire().await else {
+                        return InvariantViolationSnafu {
+                            stage: "sqlite-store-acquire-permit",
+                            details: "sqlite store permits were closed".to_string(),
+                        }
+                        .fail();
+                    };
+                    op.await
+                })
             })
             .context(SqliteThreadSpawnSnafu {
                 stage: "sqlite-store-spawn-worker",
@@ -263,9 +499,10 @@ impl SqliteStorage {
 
 impl SessionStore for SqliteStorage {
     fn create_session(&self, input: NewSession) -> StorageResult<SessionRecord> {
-        let database_url = self.database_url.clone();
+        self.ensure_writable("session-create-readonly")?;
+        let store_connection = self.store_connection.clone();
         self.run_db_call("session-create", async move {
-            let mut connection = connect_store_connection(&database_url, "session-create-connect").await?;
+            let mut connection = connect_store_connection(&store_connection, "session-create-connect").await?;
             let title = input.title;
             let mut tx = connection
                 .begin()
@@ -312,25 +549,30 @@ impl SessionStore for SqliteStorage {
                 id: session_id,
                 title,
                 active_branch_id: branch_id,
+                pinned: false,
+                metadata_json: Some("{}".to_string()),
+                tags: Vec::new(),
+                created_at_unix_seconds: i64_to_u64(now, "session-create-created-at")?,
                 updated_at_unix_seconds: i64_to_u64(now, "session-create-updated-at")?,
                 deleted_at_unix_seconds: None,
+                message_count: None,
             })
         })
     }
 
     fn list_sessions(&self, include_deleted: bool) -> StorageResult<Vec<SessionRecord>> {
-        let database_url = self.database_url.clone();
+        let store_connection = self.store_connection.clone();
         self.run_db_call("session-list", async move {
-            let mut connection = connect_store_connection(&database_url, "session-list-connect").await?;
+            let mut connection = connect_store_connection(&store_connection, "session-list-connect").await?;
             let rows = if include_deleted {
                 sqlx::query_as::<_, SessionRow>(
-                    "SELECT id, title, active_branch_id, updated_at, deleted_at FROM sessions ORDER BY updated_at DESC, id DESC",
+                    "SELECT id, title, active_branch_id, pinned, metadata_json, created_at, updated_at, deleted_at FROM sessions ORDER BY pinned DESC, updated_at DESC, id DESC",
                 )
                 .fetch_all(&mut connection)
                 .await
             } else {
                 sqlx::query_as::<_, SessionRow>(
-                    "SELECT id, title, active_branch_id, updated_at, deleted_at FROM sessions WHERE deleted_at IS NULL ORDER BY updated_at DESC, id DESC",
+                    "SELECT id, title, active_branch_id, pinned, metadata_json, created_at, updated_at, deleted_at FROM sessions WHERE deleted_at IS NULL ORDER BY pinned DESC, updated_at DESC, id DESC",
                 )
                 .fetch_all(&mut connection)
                 .await
@@ -343,12 +585,83 @@ impl SessionStore for SqliteStorage {
         })
     }
 
+    fn list_sessions_with_counts(
+        &self,
+        include_deleted: bool,
+    ) -> StorageResult<Vec<SessionRecord>> {
+        let store_connection = self.store_connection.clone();
+        self.run_db_call("session-list-with-counts", async move {
+            let mut connection =
+                connect_store_connection(&store_connection, "session-list-with-counts-connect").await?;
+            // Counts follow list_messages: only live messages on the active branch are visible.
+            let rows = sqlx::query_as::<_, SessionRow>(
+                "SELECT s.id, s.title, s.active_branch_id, s.pinned, s.metadata_json, s.created_at, s.updated_at, s.deleted_at, COUNT(m.id) AS message_count FROM sessions s LEFT JOIN messages m ON m.session_id = s.id AND m.branch_id = s.active_branch_id AND m.deleted_at IS NULL WHERE (? OR s.deleted_at IS NULL) GROUP BY s.id ORDER BY s.pinned DESC, s.updated_at DESC, s.id DESC",
+            )
+            .bind(include_deleted)
+            .fetch_all(&mut connection)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "session-list-with-counts-query",
+            })?;
+
+            rows.into_iter().map(session_row_to_record).collect()
+        })
+    }
+
+    fn search_sessions(&self, query: &str, limit: usize) -> StorageResult<Vec<SessionRecord>> {
+        let store_connection = self.store_connection.clone();
+        let pattern = format!("%{}%", escape_like_pattern(query));
+        // A limit beyond i64 range is effectively unbounded for sqlite.
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        self.run_db_call("session-search", async move {
+            let mut connection = connect_store_connection(&store_connection, "session-search-connect").await?;
+            let rows = sqlx::query_as::<_, SessionRow>(
+                "SELECT id, title, active_branch_id, pinned, metadata_json, created_at, updated_at, deleted_at FROM sessions WHERE title LIKE ? ESCAPE '\\' AND deleted_at IS NULL ORDER BY updated_at DESC, id DESC LIMIT ?",
+            )
+            .bind(pattern)
+            .bind(limit)
+            .fetch_all(&mut connection)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "session-search-query",
+            })?;
+
+            rows.into_iter().map(session_row_to_record).collect()
+        })
+    }
+
+    fn list_sessions_updated_since(
+        &self,
+        cutoff_unix_seconds: u64,
+        include_deleted: bool,
+    ) -> StorageResult<Vec<SessionRecord>> {
+        let store_connection = self.store_connection.clone();
+        self.run_db_call("session-list-updated-since", async move {
+            let cutoff = u64_to_i64(cutoff_unix_seconds, "session-list-updated-since-cutoff")?;
+            let mut connection =
+                connect_store_connection(&store_connection, "session-list-updated-since-connect")
+                    .await?;
+            let rows = sqlx::query_as::<_, SessionRow>(
+                "SELECT id, title, active_branch_id, pinned, metadata_json, created_at, updated_at, deleted_at FROM sessions WHERE updated_at >= ? AND (? OR deleted_at IS NULL) ORDER BY updated_at DESC, id DESC",
+            )
+            .bind(cutoff)
+            .bind(include_deleted)
+            .fetch_all(&mut connection)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "session-list-updated-since-query",
+            })?;
+
+            rows.into_iter().map(session_row_to_record).collect()
+        })
+    }
+
     fn get_session(&self, session_id: SessionId) -> StorageResult<Option<SessionRecord>> {
-        let database_url = self.database_url.clone();
+        let store_connection = self.store_connection.clone();
         self.run_db_call("session-get", async move {
-            let mut connection = connect_store_connection(&database_url, "session-get-connect").await?;
+            let mut connection = connect_store_connection(&store_connection, "session-get-connect").await?;
             let row = sqlx::query_as::<_, SessionRow>(
-                "SELECT id, title, active_branch_id, updated_at, deleted_at FROM sessions WHERE id = ?",
+                "SELECT id, title, active_branch_id, pinned, metadata_json, created_at, updated_at, deleted_at FROM sessions WHERE id = ?",
             )
             .bind(session_id.to_string())
             .fetch_optional(&mut connection)
@@ -366,14 +679,36 @@ impl SessionStore for SqliteStorage {
         session_id: SessionId,
         patch: SessionPatch,
     ) -> StorageResult<SessionRecord> {
-        let database_url = self.database_url.clone();
+        self.ensure_writable("session-update-readonly")?;
+        let store_connection = self.store_connection.clone();
         self.run_db_call("session-update", async move {
-            let mut connection = connect_store_connection(&database_url, "session-update-connect").await?;
+            let mut connection = connect_store_connection(&store_connection, "session-update-connect").await?;
             let now = unix_timestamp_seconds();
+            let metadata_json = patch.metadata.map(|metadata| metadata.to_string());
+            if let Some(metadata_json) = &metadata_json {
+                let is_valid = sqlx::query_scalar::<_, bool>("SELECT json_valid(?)")
+                    .bind(metadata_json)
+                    .fetch_one(&mut connection)
+                    .await
+                    .context(SqliteQuerySnafu {
+                        stage: "session-update-validate-metadata",
+                    })?;
+                if !is_valid {
+                    return ConflictSnafu {
+                        stage: "session-update-invalid-metadata",
+                        entity: "session",
+                        details: "session metadata must be valid JSON".to_string(),
+                    }
+                    .fail();
+                }
+            }
+
             let update_result = sqlx::query(
-                "UPDATE sessions SET title = COALESCE(?, title), updated_at = ? WHERE id = ?",
+                "UPDATE sessions SET title = COALESCE(?, title), pinned = COALESCE(?, pinned), metadata_json = COALESCE(?, metadata_json), updated_at = ? WHERE id = ?",
             )
             .bind(patch.title)
+            .bind(patch.pinned)
+            .bind(metadata_json)
             .bind(now)
             .bind(session_id.to_string())
             .execute(&mut connection)
@@ -392,7 +727,7 @@ impl SessionStore for SqliteStorage {
             }
 
             let row = sqlx::query_as::<_, SessionRow>(
-                "SELECT id, title, active_branch_id, updated_at, deleted_at FROM sessions WHERE id = ?",
+                "SELECT id, title, active_branch_id, pinned, metadata_json, created_at, updated_at, deleted_at FROM sessions WHERE id = ?",
             )
             .bind(session_id.to_string())
             .fetch_optional(&mut connection)
@@ -410,11 +745,26 @@ impl SessionStore for SqliteStorage {
         })
     }
 
+    fn update_session_metadata(
+        &self,
+        session_id: SessionId,
+        metadata: serde_json::Value,
+    ) -> StorageResult<SessionRecord> {
+        self.update_session(
+            session_id,
+            SessionPatch {
+                metadata: Some(metadata),
+                ..SessionPatch::default()
+            },
+        )
+    }
+
     fn soft_delete_session(&self, session_id: SessionId) -> StorageResult<()> {
-        let database_url = self.database_url.clone();
+        self.ensure_writable("session-soft-delete-readonly")?;
+        let store_connection = self.store_connection.clone();
         self.run_db_call("session-soft-delete", async move {
             let mut connection =
-                connect_store_connection(&database_url, "session-soft-delete-connect").await?;
+                connect_store_connection(&store_connection, "session-soft-delete-connect").await?;
             let now = unix_timestamp_seconds();
             let result = sqlx::query(
                 "UPDATE sessions SET deleted_at = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL",
@@ -444,10 +794,11 @@ impl SessionStore for SqliteStorage {
         })
     }
 
-    fn restore_session(&self, session_id: SessionId) -> StorageResult<()> {
-        let database_url = self.database_url.clone();
+    fn restore_session(&self, session_id: SessionId) -> StorageResult<SessionRecord> {
+        self.ensure_writable("session-restore-readonly")?;
+        let store_connection = self.store_connection.clone();
         self.run_db_call("session-restore", async move {
-            let mut connection = connect_store_connection(&database_url, "session-restore-connect").await?;
+            let mut connection = connect_store_connection(&store_connection, "session-restore-connect").await?;
             let now = unix_timestamp_seconds();
             let result = sqlx::query(
                 "UPDATE sessions SET deleted_at = NULL, updated_at = ? WHERE id = ? AND deleted_at IS NOT NULL",
@@ -472,7 +823,44 @@ impl SessionStore for SqliteStorage {
                 }
             }
 
-            Ok(())
+            let row = sqlx::query_as::<_, SessionRow>(
+                "SELECT id, title, active_branch_id, pinned, metadata_json, created_at, updated_at, deleted_at FROM sessions WHERE id = ?",
+            )
+            .bind(session_id.to_string())
+            .fetch_optional(&mut connection)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "session-restore-load",
+            })?
+            .context(NotFoundSnafu {
+                stage: "session-restore-load-missing",
+                entity: "session",
+                id: session_id.to_string(),
+            })?;
+
+            session_row_to_record(row)
+        })
+    }
+}
+
+impl BranchStore for SqliteStorage {
+    fn list_branches(&self, session_id: SessionId) -> StorageResult<Vec<BranchRecord>> {
+        let store_connection = self.store_connection.clone();
+        self.run_db_call("branch-list", async move {
+            let mut connection = connect_store_connection(&store_connection, "branch-list-connect").await?;
+            ensure_session_in_scope(&mut connection, session_id, "branch-list-ensure-session").await?;
+
+            let rows = sqlx::query_as::<_, BranchRow>(
+                "SELECT id, parent_branch_id, created_at, deleted_at FROM branches WHERE session_id = ? ORDER BY created_at ASC, id ASC",
+            )
+            .bind(session_id.to_string())
+            .fetch_all(&mut connection)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "branch-list-query",
+            })?;
+
+            rows.into_iter().map(branch_row_to_record).collect()
         })
     }
 }
@@ -483,9 +871,10 @@ impl MessageStore for SqliteStorage {
         session_id: SessionId,
         input: NewMessage,
     ) -> StorageResult<MessageRecord> {
-        let database_url = self.database_url.clone();
+        self.ensure_writable("message-append-readonly")?;
+        let store_connection = self.store_connection.clone();
         self.run_db_call("message-append", async move {
-            let mut connection = connect_store_connection(&database_url, "message-append-connect").await?;
+            let mut connection = connect_store_connection(&store_connection, "message-append-connect").await?;
             let active_branch_id =
                 load_active_branch_id(&mut connection, session_id, "message-append-load-active").await?;
             let next_seq = sqlx::query_scalar::<_, i64>(
@@ -527,19 +916,20 @@ impl MessageStore for SqliteStorage {
                 seq: i64_to_u64(next_seq, "message-append-seq")?,
                 role: input.role,
                 content: input.content,
+                created_at_unix_seconds: i64_to_u64(now, "message-append-created-at")?,
                 deleted_at_unix_seconds: None,
             })
         })
     }
 
     fn list_messages(&self, session_id: SessionId) -> StorageResult<Vec<MessageRecord>> {
-        let database_url = self.database_url.clone();
+        let store_connection = self.store_connection.clone();
         self.run_db_call("message-list", async move {
-            let mut connection = connect_store_connection(&database_url, "message-list-connect").await?;
+            let mut connection = connect_store_connection(&store_connection, "message-list-connect").await?;
             let active_branch_id =
                 load_active_branch_id(&mut connection, session_id, "message-list-load-active").await?;
             let rows = sqlx::query_as::<_, MessageRow>(
-                "SELECT id, session_id, branch_id, seq, role, content, deleted_at FROM messages WHERE session_id = ? AND branch_id = ? AND deleted_at IS NULL ORDER BY seq ASC, id ASC",
+                "SELECT id, session_id, branch_id, seq, role, content, created_at, deleted_at FROM messages WHERE session_id = ? AND branch_id = ? AND deleted_at IS NULL ORDER BY seq ASC, id ASC",
             )
             .bind(session_id.to_string())
             .bind(active_branch_id.to_string())
@@ -553,16 +943,103 @@ impl MessageStore for SqliteStorage {
         })
     }
 
+    fn list_messages_by_role(
+        &self,
+        session_id: SessionId,
+        role: MessageRole,
+    ) -> StorageResult<Vec<MessageRecord>> {
+        let store_connection = self.store_connection.clone();
+        self.run_db_call("message-list-by-role", async move {
+            let mut connection =
+                connect_store_connection(&store_connection, "message-list-by-role-connect").await?;
+            let active_branch_id = load_active_branch_id(
+                &mut connection,
+                session_id,
+                "message-list-by-role-load-active",
+            )
+            .await?;
+            let rows = sqlx::query_as::<_, MessageRow>(
+                "SELECT id, session_id, branch_id, seq, role, content, created_at, deleted_at FROM messages WHERE session_id = ? AND branch_id = ? AND role = ? AND deleted_at IS NULL ORDER BY seq ASC, id ASC",
+            )
+            .bind(session_id.to_string())
+            .bind(active_branch_id.to_string())
+            .bind(role_to_sql(role))
+            .fetch_all(&mut connection)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "message-list-by-role-query",
+            })?;
+
+            rows.into_iter().map(message_row_to_record).collect()
+        })
+    }
+
+    fn list_messages_updated_since(
+        &self,
+        session_id: SessionId,
+        cutoff_unix_seconds: u64,
+    ) -> StorageResult<Vec<MessageRecord>> {
+        let store_connection = self.store_connection.clone();
+        self.run_db_call("message-list-updated-since", async move {
+            let cutoff = u64_to_i64(cutoff_unix_seconds, "message-list-updated-since-cutoff")?;
+            let mut connection =
+                connect_store_connection(&store_connection, "message-list-updated-since-connect")
+                    .await?;
+            let active_branch_id = load_active_branch_id(
+                &mut connection,
+                session_id,
+                "message-list-updated-since-load-active",
+            )
+            .await?;
+            let rows = sqlx::query_as::<_, MessageRow>(
+                "SELECT id, session_id, branch_id, seq, role, content, created_at, deleted_at FROM messages WHERE session_id = ? AND branch_id = ? AND updated_at >= ? AND deleted_at IS NULL ORDER BY seq ASC, id ASC",
+            )
+            .bind(session_id.to_string())
+            .bind(active_branch_id.to_string())
+            .bind(cutoff)
+            .fetch_all(&mut connection)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "message-list-updated-since-query",
+            })?;
+
+            rows.into_iter().map(message_row_to_record).collect()
+        })
+    }
+
+    fn search_messages(&self, query: &str, limit: usize) -> StorageResult<Vec<MessageSearchHit>> {
+        let store_connection = self.store_connection.clone();
+        let pattern = format!("%{}%", escape_like_pattern(query));
+        // A limit beyond i64 range is effectively unbounded for sqlite.
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        self.run_db_call("message-search", async move {
+            let mut connection = connect_store_connection(&store_connection, "message-search-connect").await?;
+            // Session columns ride along so callers need no per-hit session lookup.
+            let rows = sqlx::query_as::<_, MessageSearchRow>(
+                "SELECT m.id, m.session_id, m.branch_id, m.seq, m.role, m.content, m.created_at, m.deleted_at, s.title AS session_title, s.active_branch_id AS session_active_branch_id, s.pinned AS session_pinned, s.metadata_json AS session_metadata_json, s.created_at AS session_created_at, s.updated_at AS session_updated_at, s.deleted_at AS session_deleted_at FROM messages m JOIN sessions s ON s.id = m.session_id AND s.active_branch_id = m.branch_id WHERE m.content LIKE ? ESCAPE '\\' AND m.deleted_at IS NULL AND s.deleted_at IS NULL ORDER BY m.created_at DESC, m.seq DESC, m.id DESC LIMIT ?",
+            )
+            .bind(pattern)
+            .bind(limit)
+            .fetch_all(&mut connection)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "message-search-query",
+            })?;
+
+            rows.into_iter().map(message_search_row_to_hit).collect()
+        })
+    }
+
     fn get_message(
         &self,
         session_id: SessionId,
         message_id: MessageId,
     ) -> StorageResult<Option<MessageRecord>> {
-        let database_url = self.database_url.clone();
+        let store_connection = self.store_connection.clone();
         self.run_db_call("message-get", async move {
-            let mut connection = connect_store_connection(&database_url, "message-get-connect").await?;
+            let mut connection = connect_store_connection(&store_connection, "message-get-connect").await?;
             let row = sqlx::query_as::<_, MessageRow>(
-                "SELECT id, session_id, branch_id, seq, role, content, deleted_at FROM messages WHERE session_id = ? AND id = ? AND deleted_at IS NULL",
+                "SELECT id, session_id, branch_id, seq, role, content, created_at, deleted_at FROM messages WHERE session_id = ? AND id = ? AND deleted_at IS NULL",
             )
             .bind(session_id.to_string())
             .bind(message_id.to_string())
@@ -582,9 +1059,10 @@ impl MessageStore for SqliteStorage {
         message_id: MessageId,
         patch: MessagePatch,
     ) -> StorageResult<MessageRecord> {
-        let database_url = self.database_url.clone();
+        self.ensure_writable("message-update-readonly")?;
+        let store_connection = self.store_connection.clone();
         self.run_db_call("message-update", async move {
-            let mut connection = connect_store_connection(&database_url, "message-update-connect").await?;
+            let mut connection = connect_store_connection(&store_connection, "message-update-connect").await?;
             let now = unix_timestamp_seconds();
             let update_result = sqlx::query(
                 "UPDATE messages SET content = COALESCE(?, content), updated_at = ? WHERE session_id = ? AND id = ? AND deleted_at IS NULL",
@@ -609,7 +1087,7 @@ impl MessageStore for SqliteStorage {
             }
 
             let row = sqlx::query_as::<_, MessageRow>(
-                "SELECT id, session_id, branch_id, seq, role, content, deleted_at FROM messages WHERE session_id = ? AND id = ? AND deleted_at IS NULL",
+                "SELECT id, session_id, branch_id, seq, role, content, created_at, deleted_at FROM messages WHERE session_id = ? AND id = ? AND deleted_at IS NULL",
             )
             .bind(session_id.to_string())
             .bind(message_id.to_string())
@@ -628,14 +1106,68 @@ impl MessageStore for SqliteStorage {
         })
     }
 
+    fn soft_delete_message(
+        &self,
+        session_id: SessionId,
+        message_id: MessageId,
+    ) -> StorageResult<()> {
+        self.ensure_writable("message-soft-delete-readonly")?;
+        let store_connection = self.store_connection.clone();
+        self.run_db_call("message-soft-delete", async move {
+            let mut connection =
+                connect_store_connection(&store_connection, "message-soft-delete-connect").await?;
+            let active_branch_id = load_active_branch_id(
+                &mut connection,
+                session_id,
+                "message-soft-delete-load-active",
+            )
+            .await?;
+
+            let now = unix_timestamp_seconds();
+            let result = sqlx::query(
+                "UPDATE messages SET deleted_at = ?, updated_at = ? WHERE session_id = ? AND branch_id = ? AND id = ? AND deleted_at IS NULL",
+            )
+            .bind(now)
+            .bind(now)
+            .bind(session_id.to_string())
+            .bind(active_branch_id.to_string())
+            .bind(message_id.to_string())
+            .execute(&mut connection)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "message-soft-delete-apply",
+            })?;
+
+            if result.rows_affected() == 0 {
+                ensure_message_in_session(
+                    &mut connection,
+                    session_id,
+                    message_id,
+                    "message-soft-delete-missing",
+                )
+                .await?;
+                // The message is live but was not updated, so it sits on an inactive branch.
+                return ConflictSnafu {
+                    stage: "message-soft-delete-inactive-branch",
+                    entity: "message",
+                    details: format!("message '{message_id}' is not on the active branch"),
+                }
+                .fail();
+            }
+
+            Ok(())
+        })
+    }
+
     fn fork_from_history(
         &self,
         session_id: SessionId,
         request: HistoryForkRequest,
     ) -> StorageResult<HistoryForkOutcome> {
-        let database_url = self.database_url.clone();
+        self.ensure_writable("history-fork-readonly")?;
+        let store_connection = self.store_connection.clone();
         self.run_db_call("message-fork-from-history", async move {
-            let mut connection = connect_store_connection(&database_url, "message-fork-connect").await?;
+            let mut connection = connect_store_connection(&store_connection, "message-fork-connect").await?;
             let mut tx = connection.begin().await.context(SqliteQuerySnafu {
                 stage: "message-fork-begin",
             })?;
@@ -766,6 +1298,131 @@ impl MessageStore for SqliteStorage {
             })
         })
     }
+
+    fn delete_all_messages(
+        &self,
+        session_id: SessionId,
+        branch_id: BranchId,
+    ) -> StorageResult<BranchId> {
+        self.ensure_writable("message-delete-all-readonly")?;
+        let store_connection = self.store_connection.clone();
+        self.run_db_call("message-delete-all", async move {
+            let mut connection =
+                connect_store_connection(&store_connection, "message-delete-all-connect").await?;
+            let mut tx = connection.begin().await.context(SqliteQuerySnafu {
+                stage: "message-delete-all-begin",
+            })?;
+
+            let branch_exists = sqlx::query_scalar::<_, i64>(
+                "SELECT 1 FROM branches WHERE session_id = ? AND id = ? LIMIT 1",
+            )
+            .bind(session_id.to_string())
+            .bind(branch_id.to_string())
+            .fetch_optional(&mut *tx)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "message-delete-all-load-branch",
+            })?;
+            if branch_exists.is_none() {
+                return NotFoundSnafu {
+                    stage: "message-delete-all-branch-missing",
+                    entity: "branch",
+                    id: branch_id.to_string(),
+                }
+                .fail();
+            }
+
+            // Clearing an inactive branch would still repoint the session below and orphan the
+            // real active history.
+            let active_branch_id = load_active_branch_id_in_tx(
+                &mut tx,
+                session_id,
+                "message-delete-all-load-active-branch",
+            )
+            .await?;
+            if active_branch_id != branch_id {
+                return ConflictSnafu {
+                    stage: "message-delete-all-inactive-branch",
+                    entity: "branch",
+                    details: format!("branch '{branch_id}' is not the active branch"),
+                }
+                .fail();
+            }
+
+            // Media refs and agent events hold RESTRICT foreign keys on messages, so they go first.
+            sqlx::query(
+                "DELETE FROM media_refs WHERE session_id = ? AND message_id IN (SELECT id FROM messages WHERE session_id = ? AND branch_id = ?)",
+            )
+            .bind(session_id.to_string())
+            .bind(session_id.to_string())
+            .bind(branch_id.to_string())
+            .execute(&mut *tx)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "message-delete-all-media",
+            })?;
+            sqlx::query(
+                "DELETE FROM agent_events WHERE session_id = ? AND message_id IN (SELECT id FROM messages WHERE session_id = ? AND branch_id = ?)",
+            )
+            .bind(session_id.to_string())
+            .bind(session_id.to_string())
+            .bind(branch_id.to_string())
+            .execute(&mut *tx)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "message-delete-all-agent-events",
+            })?;
+            sqlx::query("DELETE FROM messages WHERE session_id = ? AND branch_id = ?")
+                .bind(session_id.to_string())
+                .bind(branch_id.to_string())
+                .execute(&mut *tx)
+                .await
+                .context(SqliteQuerySnafu {
+                    stage: "message-delete-all-messages",
+                })?;
+
+            let now = unix_timestamp_seconds();
+            let new_branch_id = BranchId::new_v7();
+            sqlx::query(
+                "INSERT INTO branches (id, session_id, parent_branch_id, created_at, deleted_at) VALUES (?, ?, ?, ?, NULL)",
+            )
+            .bind(new_branch_id.to_string())
+            .bind(session_id.to_string())
+            .bind(branch_id.to_string())
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "message-delete-all-insert-branch",
+            })?;
+
+            sqlx::query("UPDATE sessions SET active_branch_id = ?, updated_at = ? WHERE id = ?")
+                .bind(new_branch_id.to_string())
+                .bind(now)
+                .bind(session_id.to_string())
+                .execute(&mut *tx)
+                .await
+                .context(SqliteQuerySnafu {
+                    stage: "message-delete-all-update-session-active-branch",
+                })?;
+
+            sqlx::query("UPDATE branches SET deleted_at = ? WHERE session_id = ? AND id = ?")
+                .bind(now)
+                .bind(session_id.to_string())
+                .bind(branch_id.to_string())
+                .execute(&mut *tx)
+                .await
+                .context(SqliteQuerySnafu {
+                    stage: "message-delete-all-soft-delete-old-branch",
+                })?;
+
+            tx.commit().await.context(SqliteQuerySnafu {
+                stage: "message-delete-all-commit",
+            })?;
+
+            Ok(new_branch_id)
+        })
+    }
 }
 
 impl MediaStore for SqliteStorage {
@@ -775,9 +1432,10 @@ impl MediaStore for SqliteStorage {
         message_id: MessageId,
         input: NewMediaRef,
     ) -> StorageResult<MediaRefRecord> {
-        let database_url = self.database_url.clone();
+        self.ensure_writable("media-attach-readonly")?;
+        let store_connection = self.store_connection.clone();
         self.run_db_call("media-attach", async move {
-            let mut connection = connect_store_connection(&database_url, "media-attach-connect").await?;
+            let mut connection = connect_store_connection(&store_connection, "media-attach-connect").await?;
             ensure_message_in_session(
                 &mut connection,
                 session_id,
@@ -839,9 +1497,9 @@ impl MediaStore for SqliteStorage {
         message_id: MessageId,
         include_deleted: bool,
     ) -> StorageResult<Vec<MediaRefRecord>> {
-        let database_url = self.database_url.clone();
+        let store_connection = self.store_connection.clone();
         self.run_db_call("media-list", async move {
-            let mut connection = connect_store_connection(&database_url, "media-list-connect").await?;
+            let mut connection = connect_store_connection(&store_connection, "media-list-connect").await?;
             ensure_message_in_session(
                 &mut connection,
                 session_id,
@@ -881,10 +1539,11 @@ impl MediaStore for SqliteStorage {
         message_id: MessageId,
         media_ref_id: MediaRefId,
     ) -> StorageResult<()> {
-        let database_url = self.database_url.clone();
+        self.ensure_writable("media-soft-delete-readonly")?;
+        let store_connection = self.store_connection.clone();
         self.run_db_call("media-soft-delete", async move {
             let mut connection =
-                connect_store_connection(&database_url, "media-soft-delete-connect").await?;
+                connect_store_connection(&store_connection, "media-soft-delete-connect").await?;
             ensure_message_in_session(
                 &mut connection,
                 session_id,
@@ -930,10 +1589,11 @@ impl AgentEventStore for SqliteStorage {
         session_id: SessionId,
         input: NewAgentEvent,
     ) -> StorageResult<AgentEventRecord> {
-        let database_url = self.database_url.clone();
+        self.ensure_writable("agent-event-append-readonly")?;
+        let store_connection = self.store_connection.clone();
         self.run_db_call("agent-event-append", async move {
             let mut connection =
-                connect_store_connection(&database_url, "agent-event-append-connect").await?;
+                connect_store_connection(&store_connection, "agent-event-append-connect").await?;
 
             if let Some(message_id) = input.message_id {
                 ensure_message_in_session(
@@ -998,9 +1658,9 @@ impl AgentEventStore for SqliteStorage {
         session_id: SessionId,
         message_id: Option<MessageId>,
     ) -> StorageResult<Vec<AgentEventRecord>> {
-        let database_url = self.database_url.clone();
+        let store_connection = self.store_connection.clone();
         self.run_db_call("agent-event-list", async move {
-            let mut connection = connect_store_connection(&database_url, "agent-event-list-connect").await?;
+            let mut connection = connect_store_connection(&store_connection, "agent-event-list-connect").await?;
 
             if let Some(scoped_message_id) = message_id {
                 ensure_message_in_session(
@@ -1041,13 +1701,80 @@ impl AgentEventStore for SqliteStorage {
     }
 }
 
+impl DraftStore for SqliteStorage {
+    fn save_draft(&self, session_id: SessionId, content: &str) -> StorageResult<()> {
+        self.ensure_writable("draft-save-readonly")?;
+        let store_connection = self.store_connection.clone();
+        let content = content.to_string();
+        self.run_db_call("draft-save", async move {
+            let mut connection = connect_store_connection(&store_connection, "draft-save-connect").await?;
+            ensure_session_in_scope(&mut connection, session_id, "draft-save-session-missing").await?;
+
+            if content.is_empty() {
+                sqlx::query("DELETE FROM session_drafts WHERE session_id = ?")
+                    .bind(session_id.to_string())
+                    .execute(&mut connection)
+                    .await
+                    .context(SqliteQuerySnafu {
+                        stage: "draft-save-clear",
+                    })?;
+                return Ok(());
+            }
+
+            sqlx::query(
+                "INSERT INTO session_drafts (session_id, content, updated_at) VALUES (?, ?, ?) ON CONFLICT (session_id) DO UPDATE SET content = excluded.content, updated_at = excluded.updated_at",
+            )
+            .bind(session_id.to_string())
+            .bind(content)
+            .bind(unix_timestamp_seconds())
+            .execute(&mut connection)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "draft-save-upsert",
+            })?;
+
+            Ok(())
+        })
+    }
+
+    fn load_draft(&self, session_id: SessionId) -> StorageResult<Option<String>> {
+        let store_connection = self.store_connection.clone();
+        self.run_db_call("draft-load", async move {
+            let mut connection =
+                connect_store_connection(&store_connection, "draft-load-connect").await?;
+            sqlx::query_scalar::<_, String>(
+                "SELECT content FROM session_drafts WHERE session_id = ?",
+            )
+            .bind(session_id.to_string())
+            .fetch_optional(&mut connection)
+            .await
+            .context(SqliteQuerySnafu {
+                stage: "draft-load-query",
+            })
+        })
+    }
+}
+
 #[derive(Debug, FromRow)]
 struct SessionRow {
     id: String,
     title: String,
     active_branch_id: Option<String>,
+    pinned: bool,
+    metadata_json: Option<String>,
+    created_at: i64,
     updated_at: i64,
     deleted_at: Option<i64>,
+    #[sqlx(default)]
+    message_count: Option<i64>,
+}
+
+#[derive(Debug, FromRow)]
+struct BranchRow {
+    id: String,
+    parent_branch_id: Option<String>,
+    created_at: i64,
+    deleted_at: Option<i64>,
 }
 
 #[derive(Debug, FromRow)]
@@ -1058,7 +1785,27 @@ struct MessageRow {
     seq: i64,
     role: String,
     content: String,
+    created_at: i64,
+    deleted_at: Option<i64>,
+}
+
+#[derive(Debug, FromRow)]
+struct MessageSearchRow {
+    id: String,
+    session_id: String,
+    branch_id: String,
+    seq: i64,
+    role: String,
+    content: String,
+    created_at: i64,
     deleted_at: Option<i64>,
+    session_title: String,
+    session_active_branch_id: Option<String>,
+    session_pinned: bool,
+    session_metadata_json: Option<String>,
+    session_created_at: i64,
+    session_updated_at: i64,
+    session_deleted_at: Option<i64>,
 }
 
 #[derive(Debug, FromRow)]
@@ -1099,6 +1846,26 @@ struct AgentEventRow {
     created_at: i64,
 }
 
+fn tags_from_metadata_json(metadata_json: Option<&str>) -> Vec<String> {
+    // Metadata is free-form, so anything that is not a string array simply yields no tags.
+    let Some(metadata) =
+        metadata_json.and_then(|raw| serde_json::from_str::<serde_json::Value>(raw).ok())
+    else {
+        return Vec::new();
+    };
+
+    metadata
+        .get("tags")
+        .and_then(serde_json::Value::as_array)
+        .map(|tags| {
+            tags.iter()
+                .filter_map(serde_json::Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 fn session_row_to_record(row: SessionRow) -> StorageResult<SessionRecord> {
     Ok(SessionRecord {
         id: SessionId::parse(&row.id)?,
@@ -1109,11 +1876,35 @@ fn session_row_to_record(row: SessionRow) -> StorageResult<SessionRecord> {
                 details: "session row is missing active_branch_id".to_string(),
             },
         )?)?,
+        pinned: row.pinned,
+        tags: tags_from_metadata_json(row.metadata_json.as_deref()),
+        metadata_json: row.metadata_json,
+        created_at_unix_seconds: i64_to_u64(row.created_at, "session-row-created-at")?,
         updated_at_unix_seconds: i64_to_u64(row.updated_at, "session-row-updated-at")?,
         deleted_at_unix_seconds: row
             .deleted_at
             .map(|value| i64_to_u64(value, "session-row-deleted-at"))
             .transpose()?,
+        message_count: row
+            .message_count
+            .map(|value| i64_to_usize(value, "session-row-message-count"))
+            .transpose()?,
+    })
+}
+
+fn branch_row_to_record(row: BranchRow) -> StorageResult<BranchRecord> {
+    Ok(BranchRecord {
+        id: BranchId::parse(&row.id)?,
+        parent_branch_id: row
+            .parent_branch_id
+            .as_deref()
+            .map(BranchId::parse)
+            .transpose()?,
+        created_at_unix_seconds: i64_to_u64(row.created_at, "branch-row-created-at")?,
+        deleted_at_unix_seconds: row
+            .deleted_at
+            .map(|value| i64_to_u64(value, "branch-row-deleted-at"))
+            .transpose()?,
     })
 }
 
@@ -1125,6 +1916,7 @@ fn message_row_to_record(row: MessageRow) -> StorageResult<MessageRecord> {
         seq: i64_to_u64(row.seq, "message-row-seq")?,
         role: role_from_sql(&row.role)?,
         content: row.content,
+        created_at_unix_seconds: i64_to_u64(row.created_at, "message-row-created-at")?,
         deleted_at_unix_seconds: row
             .deleted_at
             .map(|value| i64_to_u64(value, "message-row-deleted-at"))
@@ -1132,6 +1924,31 @@ fn message_row_to_record(row: MessageRow) -> StorageResult<MessageRecord> {
     })
 }
 
+fn message_search_row_to_hit(row: MessageSearchRow) -> StorageResult<MessageSearchHit> {
+    let session = session_row_to_record(SessionRow {
+        id: row.session_id.clone(),
+        title: row.session_title,
+        active_branch_id: row.session_active_branch_id,
+        pinned: row.session_pinned,
+        metadata_json: row.session_metadata_json,
+        created_at: row.session_created_at,
+        updated_at: row.session_updated_at,
+        deleted_at: row.session_deleted_at,
+        message_count: None,
+    })?;
+    let message = message_row_to_record(MessageRow {
+        id: row.id,
+        session_id: row.session_id,
+        branch_id: row.branch_id,
+        seq: row.seq,
+        role: row.role,
+        content: row.content,
+        created_at: row.created_at,
+        deleted_at: row.deleted_at,
+    })?;
+    Ok(MessageSearchHit { session, message })
+}
+
 fn media_ref_row_to_record(row: MediaRefRow) -> StorageResult<MediaRefRecord> {
     Ok(MediaRefRecord {
         id: MediaRefId::parse(&row.id)?,
@@ -1175,17 +1992,23 @@ fn agent_event_row_to_record(row: AgentEventRow) -> StorageResult<AgentEventReco
     })
 }
 
+fn store_permits(max_connections: u32) -> Arc<Semaphore> {
+    // A zero limit would park every store call forever instead of failing.
+    Arc::new(Semaphore::new(max_connections.max(1) as usize))
+}
+
+// Journal mode is persisted in the database file by `open_with_options`, so only the
+// connection-scoped pragmas need repeating here.
 async fn connect_store_connection(
-    database_url: &str,
+    config: &StoreConnectionConfig,
     stage: &'static str,
 ) -> StorageResult<SqliteConnection> {
-    let mut connection =
-        SqliteConnection::connect(database_url)
-            .await
-            .context(SqliteConnectSnafu {
-                stage,
-                database_url: database_url.to_string(),
-            })?;
+    let mut connection = SqliteConnection::connect(&config.database_url)
+        .await
+        .context(SqliteConnectSnafu {
+            stage,
+            database_url: config.database_url.clone(),
+        })?;
 
     sqlx::query("PRAGMA foreign_keys = ON;")
         .execute(&mut connection)
@@ -1194,13 +2017,16 @@ async fn connect_store_connection(
             stage: "sqlite-store-pragma-foreign-keys",
             pragma: "foreign_keys",
         })?;
-    sqlx::query("PRAGMA busy_timeout = 5000;")
-        .execute(&mut connection)
-        .await
-        .context(SqlitePragmaSnafu {
-            stage: "sqlite-store-pragma-busy-timeout",
-            pragma: "busy_timeout",
-        })?;
+    sqlx::query(&format!(
+        "PRAGMA busy_timeout = {};",
+        config.busy_timeout_ms
+    ))
+    .execute(&mut connection)
+    .await
+    .context(SqlitePragmaSnafu {
+        stage: "sqlite-store-pragma-busy-timeout",
+        pragma: "busy_timeout",
+    })?;
 
     Ok(connection)
 }
@@ -1368,12 +2194,32 @@ fn role_from_sql(raw: &str) -> StorageResult<MessageRole> {
     }
 }
 
+async fn load_page_count(
+    connection: &mut SqliteConnection,
+    stage: &'static str,
+) -> StorageResult<u64> {
+    let page_count = sqlx::query_scalar::<_, i64>("PRAGMA page_count;")
+        .fetch_one(connection)
+        .await
+        .context(SqliteQuerySnafu { stage })?;
+    i64_to_u64(page_count, stage)
+}
+
 fn unix_timestamp_seconds() -> i64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_or(0_i64, |duration| duration.as_secs() as i64)
 }
 
+fn i64_to_usize(value: i64, stage: &'static str) -> StorageResult<usize> {
+    value
+        .try_into()
+        .map_err(|_| super::error::StorageError::InvariantViolation {
+            stage,
+            details: format!("sqlite integer '{value}' cannot map to usize"),
+        })
+}
+
 fn i64_to_u64(value: i64, stage: &'static str) -> StorageResult<u64> {
     value
         .try_into()
@@ -1401,6 +2247,17 @@ fn u64_to_i64(value: u64, stage: &'static str) -> StorageResult<i64> {
         })
 }
 
+fn escape_like_pattern(raw: &str) -> String {
+    let mut escaped = String::with_capacity(raw.len());
+    for character in raw.chars() {
+        if matches!(character, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(character);
+    }
+    escaped
+}
+
 fn validate_media_uri(uri: &str, stage: &'static str) -> StorageResult<()> {
     let uri_lower = uri.to_ascii_lowercase();
     let is_blob_like = uri_lower.starts_with("data:") || uri_lower.contains(";base64,");
@@ -1418,6 +2275,7 @@ fn validate_media_uri(uri: &str, stage: &'static str) -> StorageResult<()> {
 
 fn parse_legacy_conversation_rows(
     store: &str,
+    format: LegacyImportFormat,
 ) -> (Vec<LegacyConversationRow>, Vec<LegacyImportWarning>) {
     let mut rows = Vec::new();
     let mut warnings = Vec::new();
@@ -1427,7 +2285,11 @@ fn parse_legacy_conversation_rows(
             continue;
         }
 
-        match parse_legacy_conversation_row(line) {
+        let parsed = match format {
+            LegacyImportFormat::Tsv => parse_legacy_conversation_row(line),
+            LegacyImportFormat::Csv => parse_legacy_csv_conversation_row(line),
+        };
+        match parsed {
             Ok(parsed) => rows.push(parsed),
             Err(reason) => warnings.push(LegacyImportWarning {
                 line_number: index + 1,
@@ -1453,10 +2315,57 @@ fn parse_legacy_conversation_row(line: &str) -> Result<LegacyConversationRow, &'
     let raw_updated_at = fields.next().ok_or("missing-updated-at")?;
     let raw_title = fields.next().ok_or("missing-title")?;
 
+    legacy_conversation_row(raw_id, raw_updated_at, decode_legacy_title(raw_title))
+}
+
+fn parse_legacy_csv_conversation_row(line: &str) -> Result<LegacyConversationRow, &'static str> {
+    let mut fields = split_csv_fields(line)?.into_iter();
+    let raw_id = fields.next().ok_or("missing-id")?;
+    let raw_updated_at = fields.next().ok_or("missing-updated-at")?;
+    let title_fields: Vec<String> = fields.collect();
+    if title_fields.is_empty() {
+        return Err("missing-title");
+    }
+
+    // An unquoted title that contains commas spills into extra fields; stitch it back together.
+    legacy_conversation_row(&raw_id, &raw_updated_at, title_fields.join(","))
+}
+
+/// Splits one CSV record, honouring double-quoted fields with `""` escapes.
+fn split_csv_fields(line: &str) -> Result<Vec<String>, &'static str> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut characters = line.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '"' if in_quotes && characters.peek() == Some(&'"') => {
+                field.push('"');
+                characters.next();
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            other => field.push(other),
+        }
+    }
+
+    if in_quotes {
+        return Err("unterminated-quote");
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
+fn legacy_conversation_row(
+    raw_id: &str,
+    raw_updated_at: &str,
+    decoded_title: String,
+) -> Result<LegacyConversationRow, &'static str> {
     let legacy_id = parse_legacy_u64(raw_id).map_err(|_| "invalid-id")?;
     let updated_at_unix_seconds =
         parse_legacy_u64(raw_updated_at).map_err(|_| "invalid-updated-at")?;
-    let decoded_title = decode_legacy_title(raw_title);
 
     // Legacy create behavior defaults empty/whitespace-only titles to "New Conversation".
     let title = if decoded_title.trim().is_empty() {
@@ -1520,6 +2429,13 @@ fn ensure_database_directory(database_location: &str) -> StorageResult<()> {
     Ok(())
 }
 
+fn readonly_database_url(database_url: &str) -> String {
+    // Store calls open their own connections from the URL, so the read-only mode has to live
+    // in the URL rather than only in the pool options.
+    let separator = if database_url.contains('?') { '&' } else { '?' };
+    format!("{database_url}{separator}mode=ro")
+}
+
 fn normalize_database_url(database_location: &str) -> String {
     if database_location.starts_with("sqlite:") {
         return database_location.to_string();
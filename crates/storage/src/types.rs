@@ -1,3 +1,8 @@
+use std::io::Cursor;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
 use super::ids::{AgentEventId, BranchId, MediaRefId, MessageId, SessionId};
 
 /// Default session title used when legacy rows have empty titles.
@@ -16,8 +21,33 @@ pub struct SessionRecord {
     pub id: SessionId,
     pub title: String,
     pub active_branch_id: BranchId,
+    pub pinned: bool,
+    /// Free-form JSON attributes; defaults to an empty object for new sessions.
+    pub metadata_json: Option<String>,
+    /// The `tags` string array from `metadata_json`, in stored order.
+    pub tags: Vec<String>,
+    pub created_at_unix_seconds: u64,
     pub updated_at_unix_seconds: u64,
     pub deleted_at_unix_seconds: Option<u64>,
+    // Only populated by count-aware queries, since the join is not free.
+    pub message_count: Option<usize>,
+}
+
+impl SessionRecord {
+    /// Returns the title to show for this session, falling back to
+    /// [`DEFAULT_SESSION_TITLE`] when the stored title is blank.
+    pub fn display_title(&self) -> &str {
+        if self.is_titled() {
+            &self.title
+        } else {
+            DEFAULT_SESSION_TITLE
+        }
+    }
+
+    /// Returns true when the stored title contains something other than whitespace.
+    pub fn is_titled(&self) -> bool {
+        !self.title.trim().is_empty()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -28,6 +58,24 @@ pub struct NewSession {
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct SessionPatch {
     pub title: Option<String>,
+    pub pinned: Option<bool>,
+    /// Replaces the whole metadata object rather than merging keys.
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchRecord {
+    pub id: BranchId,
+    pub parent_branch_id: Option<BranchId>,
+    pub created_at_unix_seconds: u64,
+    pub deleted_at_unix_seconds: Option<u64>,
+}
+
+/// A message matched by `MessageStore::search_messages`, with the session it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageSearchHit {
+    pub session: SessionRecord,
+    pub message: MessageRecord,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -38,9 +86,29 @@ pub struct MessageRecord {
     pub seq: u64,
     pub role: MessageRole,
     pub content: String,
+    pub created_at_unix_seconds: u64,
     pub deleted_at_unix_seconds: Option<u64>,
 }
 
+impl MessageRecord {
+    /// Counts whitespace-separated words in `content`.
+    pub fn word_count(&self) -> usize {
+        self.content.split_whitespace().count()
+    }
+
+    /// Estimated reading time at `wpm` words per minute, rounded up; zero when `wpm` is zero.
+    pub fn reading_time_seconds(&self, wpm: u32) -> u64 {
+        if wpm == 0 {
+            return 0;
+        }
+        (self.word_count() as u64 * 60).div_ceil(u64::from(wpm))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.content.trim().is_empty()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NewMessage {
     pub role: MessageRole,
@@ -86,6 +154,18 @@ pub struct MediaRefRecord {
     pub deleted_at_unix_seconds: Option<u64>,
 }
 
+impl MediaRefRecord {
+    /// Checks the file at `path` against the recorded digest; refs without one always pass.
+    pub fn verify_sha256(&self, path: &Path) -> std::io::Result<bool> {
+        let Some(expected) = self.sha256_hex.as_deref() else {
+            return Ok(true);
+        };
+
+        let contents = std::fs::read(path)?;
+        Ok(sha256_hex(&contents).eq_ignore_ascii_case(expected))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NewMediaRef {
     pub uri: String,
@@ -97,6 +177,37 @@ pub struct NewMediaRef {
     pub sha256_hex: Option<String>,
 }
 
+impl NewMediaRef {
+    /// Describes a local file as a `file://` media ref, hashing its contents and reading image
+    /// dimensions from the header when the extension names an image format.
+    pub fn from_path(path: &Path) -> std::io::Result<Self> {
+        let contents = std::fs::read(path)?;
+        let mime_type = mime_type_for_path(path);
+        let (width_px, height_px) = if mime_type.starts_with("image/") {
+            // Unreadable headers leave dimensions unset rather than rejecting the attachment.
+            match image::ImageReader::new(Cursor::new(&contents))
+                .with_guessed_format()?
+                .into_dimensions()
+            {
+                Ok((width, height)) => (Some(width), Some(height)),
+                Err(_) => (None, None),
+            }
+        } else {
+            (None, None)
+        };
+
+        Ok(Self {
+            uri: format!("file://{}", path.display()),
+            mime_type: mime_type.to_string(),
+            size_bytes: contents.len() as u64,
+            duration_ms: None,
+            width_px,
+            height_px,
+            sha256_hex: Some(sha256_hex(&contents)),
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AgentEventRecord {
     pub id: AgentEventId,
@@ -107,9 +218,74 @@ pub struct AgentEventRecord {
     pub created_at_unix_seconds: u64,
 }
 
+impl AgentEventRecord {
+    /// Decodes the stored JSON payload into a caller-defined type.
+    pub fn payload<T: serde::de::DeserializeOwned>(&self) -> serde_json::Result<T> {
+        serde_json::from_str(&self.payload_json)
+    }
+
+    pub fn payload_value(&self) -> serde_json::Result<serde_json::Value> {
+        self.payload()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NewAgentEvent {
     pub message_id: Option<MessageId>,
     pub event_type: String,
     pub payload_json: String,
 }
+
+impl NewAgentEvent {
+    /// Builds a session-level event by serializing `payload` as its JSON body.
+    pub fn with_payload<T: serde::Serialize>(
+        event_type: &str,
+        payload: &T,
+    ) -> serde_json::Result<Self> {
+        Ok(Self {
+            message_id: None,
+            event_type: event_type.to_string(),
+            payload_json: serde_json::to_string(payload)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointResult {
+    pub wal_frames: u32,
+    pub checkpointed_frames: u32,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    let mut hex = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        hex.push_str(&format!("{byte:02x}"));
+    }
+    hex
+}
+
+fn mime_type_for_path(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "m4a" => "audio/mp4",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "pdf" => "application/pdf",
+        "json" => "application/json",
+        "txt" | "md" => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
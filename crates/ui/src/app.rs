@@ -1,4 +1,5 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use gpui::prelude::FluentBuilder;
 use gpui::*;
@@ -6,10 +7,12 @@ use gpui_component::notification::NotificationList;
 use gpui_component::{
     ActiveTheme, Icon, IconName, Sizable,
     button::{Button, ButtonVariants},
-    h_flex, v_flex,
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    v_flex,
 };
 
-use crate::chat::{ChatSidebar, ChatView};
+use crate::chat::{ChatSidebar, ChatView, SearchResult};
 
 /// Returns the default themes directory path.
 /// This is a pure function to allow deterministic testing of path resolution.
@@ -18,11 +21,13 @@ pub fn default_themes_path() -> PathBuf {
 }
 
 /// Default sidebar width when expanded.
-pub const SIDEBAR_DEFAULT_WIDTH: f32 = 260.0;
+pub const SIDEBAR_DEFAULT_WIDTH: f32 = 240.0;
 /// Minimum allowed sidebar width.
-pub const SIDEBAR_MIN_WIDTH: f32 = 200.0;
+pub const SIDEBAR_MIN_WIDTH: f32 = 160.0;
 /// Maximum allowed sidebar width.
-pub const SIDEBAR_MAX_WIDTH: f32 = 400.0;
+pub const SIDEBAR_MAX_WIDTH: f32 = 480.0;
+/// Hit-area width of the sidebar resize handle.
+const SIDEBAR_RESIZE_HANDLE_WIDTH: f32 = 4.0;
 pub const SIDEBAR_COLLAPSED_WIDTH: f32 = 56.0;
 #[cfg(target_os = "macos")]
 const WINDOW_TOOLBAR_LEFT_SAFE_PADDING: f32 = 78.0;
@@ -32,6 +37,9 @@ const WINDOW_TOOLBAR_LEFT_SAFE_PADDING: f32 = 16.0;
 const WINDOW_TOOLBAR_RIGHT_SAFE_PADDING: f32 = 120.0;
 #[cfg(not(target_os = "windows"))]
 const WINDOW_TOOLBAR_RIGHT_SAFE_PADDING: f32 = 16.0;
+const SEARCH_PALETTE_WIDTH: f32 = 520.0;
+const SEARCH_PALETTE_TOP_OFFSET: f32 = 72.0;
+const SEARCH_PALETTE_DEBOUNCE_MS: u64 = 150;
 /// Compile-time validation of sidebar layout constraints.
 /// These assertions ensure the constants maintain valid relationships.
 const _: () = {
@@ -61,7 +69,16 @@ pub fn compute_sidebar_width(drag_x: f32) -> f32 {
     drag_x.clamp(SIDEBAR_MIN_WIDTH, SIDEBAR_MAX_WIDTH)
 }
 
-gpui::actions!(shell, [NewChat, ToggleSidebar, Quit,]);
+/// Moves the search palette selection by `delta` rows, clamped to the result list.
+pub fn step_search_selection(selected: usize, delta: isize, result_count: usize) -> usize {
+    if result_count == 0 {
+        return 0;
+    }
+
+    selected.saturating_add_signed(delta).min(result_count - 1)
+}
+
+gpui::actions!(shell, [NewChat, ToggleSidebar, Quit, GlobalSearch,]);
 
 /// Marker type for sidebar resize drag operations.
 /// Used to identify drag events specific to the resize handle.
@@ -95,6 +112,11 @@ pub struct ChatAppShell {
     /// Current width of the sidebar when expanded.
     sidebar_width: f32,
     title_bar_should_move: bool,
+    /// Query input of the global search palette; `None` while the palette is closed.
+    search_palette_input: Option<Entity<InputState>>,
+    search_results: Vec<SearchResult>,
+    selected_search_result: usize,
+    search_task: Option<Task<()>>,
 }
 
 impl ChatAppShell {
@@ -110,16 +132,109 @@ impl ChatAppShell {
         cx: &mut Context<Self>,
     ) -> Self {
         let chat_view = cx.new(|cx| ChatView::new(window, cx));
+        let sidebar_width = chat_view
+            .read(cx)
+            .settings_state()
+            .read(cx)
+            .settings()
+            .sidebar_width
+            .map(|width| compute_sidebar_width(width as f32))
+            .unwrap_or(SIDEBAR_DEFAULT_WIDTH);
 
         Self {
             notification_list,
             chat_view,
             sidebar_collapsed: false,
-            sidebar_width: SIDEBAR_DEFAULT_WIDTH,
+            sidebar_width,
             title_bar_should_move: false,
+            search_palette_input: None,
+            search_results: Vec::new(),
+            selected_search_result: 0,
+            search_task: None,
+        }
+    }
+
+    /// Searches conversation titles and message content across all conversations off the UI
+    /// thread.
+    pub fn global_search(
+        &mut self,
+        query: &str,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<SearchResult>> {
+        let sidebar = self.chat_view.read(cx).sidebar().clone();
+        sidebar.update(cx, |sidebar, cx| sidebar.global_search(query, cx))
+    }
+
+    /// Runs the palette search once typing pauses; a newer query drops the pending one.
+    fn schedule_global_search(&mut self, query: String, cx: &mut Context<Self>) {
+        self.search_task = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor()
+                .timer(Duration::from_millis(SEARCH_PALETTE_DEBOUNCE_MS))
+                .await;
+            let Ok(search) = this.update(cx, |this, cx| this.global_search(&query, cx)) else {
+                return;
+            };
+            let results = search.await;
+            let _ = this.update(cx, |this, cx| {
+                this.search_task = None;
+                this.search_results = results;
+                this.selected_search_result = 0;
+                cx.notify();
+            });
+        }));
+    }
+
+    fn open_search_palette(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(search_input) = self.search_palette_input.as_ref() {
+            search_input.update(cx, |state, cx| state.focus(window, cx));
+            return;
+        }
+
+        let search_input = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("Search conversations and messages...")
+        });
+        cx.subscribe_in(
+            &search_input,
+            window,
+            |this, state, event: &InputEvent, _window, cx| match event {
+                InputEvent::Change => {
+                    let query = state.read(cx).value().to_string();
+                    this.schedule_global_search(query, cx);
+                }
+                InputEvent::PressEnter { .. } => {
+                    this.open_search_result(this.selected_search_result, cx);
+                }
+                InputEvent::Focus | InputEvent::Blur => {}
+            },
+        )
+        .detach();
+        search_input.update(cx, |state, cx| state.focus(window, cx));
+        self.search_palette_input = Some(search_input);
+        self.search_results.clear();
+        self.selected_search_result = 0;
+        cx.notify();
+    }
+
+    fn dismiss_search_palette(&mut self, cx: &mut Context<Self>) {
+        self.search_task = None;
+        if self.search_palette_input.take().is_some() {
+            self.search_results.clear();
+            self.selected_search_result = 0;
+            cx.notify();
         }
     }
 
+    fn open_search_result(&mut self, index: usize, cx: &mut Context<Self>) {
+        let Some(result) = self.search_results.get(index).cloned() else {
+            return;
+        };
+
+        self.dismiss_search_palette(cx);
+        self.chat_view.update(cx, |chat_view, cx| {
+            chat_view.open_search_result(&result, cx)
+        });
+    }
+
     /// Toggles the sidebar between collapsed and expanded states.
     fn toggle_sidebar(&mut self, cx: &mut Context<Self>) {
         self.sidebar_collapsed = !self.sidebar_collapsed;
@@ -135,6 +250,18 @@ impl ChatAppShell {
         cx.notify();
     }
 
+    /// Persists the current sidebar width once a resize drag is released.
+    fn persist_sidebar_width(&mut self, cx: &mut Context<Self>) {
+        let sidebar_width = compute_sidebar_width(self.sidebar_width).round() as u32;
+        let settings_state = self.chat_view.read(cx).settings_state().clone();
+
+        if let Err(error) =
+            settings_state.update(cx, |state, _cx| state.update_sidebar_width(sidebar_width))
+        {
+            tracing::error!("failed to persist sidebar width: {error}");
+        }
+    }
+
     /// Handles the new chat action.
     fn new_chat(&mut self, cx: &mut Context<Self>) {
         self.chat_view
@@ -158,6 +285,9 @@ impl Render for ChatAppShell {
             .size_full()
             .relative()
             .bg(theme.background)
+            .on_action(cx.listener(|this, _: &GlobalSearch, window, cx| {
+                this.open_search_palette(window, cx);
+            }))
             .child(
                 v_flex()
                     .size_full()
@@ -184,6 +314,10 @@ impl Render for ChatAppShell {
                     )
                     .child(self.render_bottom_bar(cx)),
             )
+            // Drops can land anywhere in the window, so the release is observed at the root.
+            .on_drop(cx.listener(|this, _: &SidebarResizeDrag, _window, cx| {
+                this.persist_sidebar_width(cx);
+            }))
             .child(
                 div()
                     .absolute()
@@ -192,6 +326,7 @@ impl Render for ChatAppShell {
                     .right_0()
                     .child(self.render_top_bar(window, toolbar_height, cx)),
             )
+            .children(self.render_search_palette(cx))
             .child(self.notification_list.clone())
     }
 }
@@ -450,16 +585,136 @@ impl ChatAppShell {
             .child(sidebar_content)
     }
 
+    fn render_search_palette(&self, cx: &mut Context<Self>) -> Option<AnyElement> {
+        let search_input = self.search_palette_input.clone()?;
+        let has_query = !search_input.read(cx).value().trim().is_empty();
+        let theme = cx.theme();
+
+        let results = self
+            .search_results
+            .iter()
+            .enumerate()
+            .map(|(index, result)| {
+                let (detail, icon) = match result {
+                    SearchResult::Conversation(_) => ("Title match".to_string(), IconName::File),
+                    SearchResult::Message {
+                        message_snippet, ..
+                    } => (message_snippet.clone(), IconName::Search),
+                };
+                let selected = index == self.selected_search_result;
+
+                h_flex()
+                    .id(("search-palette-result", index))
+                    .w_full()
+                    .gap_2()
+                    .px_3()
+                    .py_2()
+                    .items_center()
+                    .rounded_md()
+                    .cursor_pointer()
+                    .when(selected, |row| row.bg(theme.muted))
+                    .hover(|row| row.bg(theme.muted))
+                    .on_click(cx.listener(move |this, _, _window, cx| {
+                        this.open_search_result(index, cx);
+                    }))
+                    .child(
+                        Icon::new(icon)
+                            .size(px(14.))
+                            .text_color(theme.muted_foreground),
+                    )
+                    .child(
+                        v_flex()
+                            .flex_1()
+                            .min_w_0()
+                            .child(
+                                div()
+                                    .truncate()
+                                    .text_sm()
+                                    .text_color(theme.foreground)
+                                    .child(result.conversation().title.clone()),
+                            )
+                            .child(
+                                div()
+                                    .truncate()
+                                    .text_xs()
+                                    .text_color(theme.muted_foreground)
+                                    .child(detail),
+                            ),
+                    )
+            })
+            .collect::<Vec<_>>();
+        let empty_state = (has_query && results.is_empty()).then(|| {
+            div()
+                .px_3()
+                .py_2()
+                .text_sm()
+                .text_color(theme.muted_foreground)
+                .child("No matching conversations")
+        });
+
+        Some(
+            h_flex()
+                .id("search-palette-overlay")
+                .absolute()
+                .top(px(SEARCH_PALETTE_TOP_OFFSET))
+                .left_0()
+                .right_0()
+                .justify_center()
+                .child(
+                    v_flex()
+                        .id("search-palette")
+                        .w(px(SEARCH_PALETTE_WIDTH))
+                        .max_h(px(420.))
+                        .gap_1()
+                        .p_2()
+                        .bg(theme.popover)
+                        .border_1()
+                        .border_color(theme.border)
+                        .rounded_md()
+                        .shadow_md()
+                        .on_mouse_down_out(cx.listener(|this, _, _window, cx| {
+                            this.dismiss_search_palette(cx);
+                        }))
+                        .on_key_down(cx.listener(|this, event: &KeyDownEvent, _window, cx| {
+                            match event.keystroke.key.as_str() {
+                                "escape" => this.dismiss_search_palette(cx),
+                                "down" | "up" => {
+                                    let delta = if event.keystroke.key == "down" { 1 } else { -1 };
+                                    this.selected_search_result = step_search_selection(
+                                        this.selected_search_result,
+                                        delta,
+                                        this.search_results.len(),
+                                    );
+                                    cx.notify();
+                                }
+                                _ => return,
+                            }
+                            cx.stop_propagation();
+                        }))
+                        .child(Input::new(&search_input).small())
+                        .child(
+                            v_flex()
+                                .id("search-palette-results")
+                                .min_h_0()
+                                .overflow_y_scroll()
+                                .children(results)
+                                .children(empty_state),
+                        ),
+                )
+                .into_any_element(),
+        )
+    }
+
     /// Renders the resize handle for adjusting sidebar width.
     ///
-    /// The handle is a thin vertical line that shows a resize cursor on hover
+    /// The handle is a thin vertical strip that shows a resize cursor on hover
     /// and allows dragging to adjust the sidebar width.
     fn render_resize_handle(&self, cx: &Context<Self>) -> impl IntoElement {
         let theme = cx.theme();
 
         div()
             .id("sidebar-resize-handle")
-            .w(px(1.0))
+            .w(px(SIDEBAR_RESIZE_HANDLE_WIDTH))
             .h_full()
             .flex_shrink_0()
             .cursor(CursorStyle::ResizeLeftRight)
@@ -477,3 +732,29 @@ impl ChatAppShell {
             ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[::core::prelude::v1::test]
+    fn sidebar_width_is_clamped_to_bounds() {
+        assert_eq!(compute_sidebar_width(SIDEBAR_DEFAULT_WIDTH), 240.0);
+        assert_eq!(compute_sidebar_width(320.0), 320.0);
+        assert_eq!(compute_sidebar_width(900.0), SIDEBAR_MAX_WIDTH);
+    }
+
+    #[::core::prelude::v1::test]
+    fn search_selection_stays_within_results() {
+        assert_eq!(step_search_selection(0, 1, 3), 1);
+        assert_eq!(step_search_selection(2, 1, 3), 2);
+        assert_eq!(step_search_selection(0, -1, 3), 0);
+        assert_eq!(step_search_selection(4, -1, 0), 0);
+    }
+
+    #[::core::prelude::v1::test]
+    fn releasing_below_minimum_snaps_to_minimum() {
+        assert_eq!(compute_sidebar_width(100.0), 160.0);
+        assert_eq!(compute_sidebar_width(-20.0), 160.0);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::chat::message::{ConversationId, StreamTarget, StreamTransition};
+use crate::chat::message::{ConversationId, MessageId, StreamTarget, StreamTransition};
 
 /// Emitted when sidebar selection changes the active conversation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -6,6 +6,31 @@ pub struct ConversationSelected {
     pub conversation_id: ConversationId,
 }
 
+/// Emitted after a conversation title change has been persisted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversationRenamed {
+    pub conversation_id: ConversationId,
+    pub new_title: String,
+}
+
+/// Emitted after the user confirms wiping a conversation's message history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConversationClearRequested {
+    pub conversation_id: ConversationId,
+}
+
+/// Emitted when the user asks to see a conversation's statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConversationStatisticsRequested {
+    pub conversation_id: ConversationId,
+}
+
+/// Emitted when the user picks "Delete message" from a message's context menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MessageDeleteRequested {
+    pub message_id: MessageId,
+}
+
 /// Emitted when the user submits a prompt to generate a response.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Submit {
@@ -13,6 +38,12 @@ pub struct Submit {
     pub content: String,
 }
 
+/// Emitted whenever the message input text changes, carrying the full current text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DraftChanged {
+    pub content: String,
+}
+
 /// Emitted when user requests cancellation of an active stream.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Stop {
@@ -1,3 +1,5 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 /// Stable identifier for one conversation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ConversationId(pub u64);
@@ -75,6 +77,7 @@ pub struct Message {
     pub role: Role,
     pub content: String,
     pub status: MessageStatus,
+    pub created_at_unix_seconds: u64,
 }
 
 impl Message {
@@ -90,9 +93,16 @@ impl Message {
             role,
             content: content.into(),
             status,
+            created_at_unix_seconds: unix_now_seconds(),
         }
     }
 
+    /// Overrides the creation time, e.g. when hydrating persisted history.
+    pub fn with_created_at(mut self, created_at_unix_seconds: u64) -> Self {
+        self.created_at_unix_seconds = created_at_unix_seconds;
+        self
+    }
+
     /// Creates a pending user message before stream starts.
     pub fn user_pending(id: MessageId, content: impl Into<String>) -> Self {
         Self::new(id, Role::User, content, MessageStatus::Pending)
@@ -129,6 +139,14 @@ impl Conversation {
         }
     }
 
+    /// Sums whitespace-separated words across every message.
+    pub fn total_word_count(&self) -> usize {
+        self.messages
+            .iter()
+            .map(|message| message.content.split_whitespace().count())
+            .sum()
+    }
+
     /// Applies a deterministic stream transition.
     pub fn apply_stream_transition(
         &mut self,
@@ -266,3 +284,51 @@ impl StreamState {
         }
     }
 }
+
+/// Read-only summary of a persisted conversation, shown from the sidebar context menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConversationStats {
+    pub message_count: usize,
+    pub user_message_count: usize,
+    pub assistant_message_count: usize,
+    pub total_word_count: usize,
+    pub created_at: u64,
+    pub last_updated_at: u64,
+    pub branch_count: usize,
+}
+
+/// Returns the current wall-clock time in unix seconds.
+pub(crate) fn unix_now_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0))
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[::core::prelude::v1::test]
+    fn total_word_count_sums_whitespace_separated_words() {
+        let mut conversation = Conversation::new(ConversationId::new(1), "Words");
+        assert_eq!(conversation.total_word_count(), 0);
+
+        for (id, role, content) in [
+            (1, Role::User, "Hello, world!"),
+            (2, Role::Assistant, "  Hi\tthere.\n\nHow   are you? "),
+            (3, Role::User, ""),
+            (4, Role::User, "state-of-the-art — really"),
+        ] {
+            conversation.messages.push(Message::new(
+                MessageId::new(id),
+                role,
+                content,
+                MessageStatus::Done,
+            ));
+        }
+
+        // Punctuation stays attached to its word, and runs of whitespace count once.
+        assert_eq!(conversation.total_word_count(), 10);
+    }
+}
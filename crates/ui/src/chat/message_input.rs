@@ -2,24 +2,39 @@ use gpui::*;
 use gpui_component::{
     ActiveTheme, IconName, Sizable,
     button::{Button, ButtonVariants},
+    h_flex,
     input::{Input, InputEvent, InputState},
     v_flex,
 };
 
-use crate::chat::events::{Stop, Submit};
+use crate::chat::events::{DraftChanged, Stop, Submit};
 use crate::chat::message::{ConversationId, StreamSessionId, StreamTarget};
 
 const DEFAULT_STREAM_TARGET: StreamTarget =
     StreamTarget::new(ConversationId::new(0), StreamSessionId::new(0));
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenUsageLevel {
+    Normal,
+    Warning,
+    Critical,
+}
+
 pub struct MessageInput {
     input_state: Entity<InputState>,
     stream_target: StreamTarget,
     is_streaming: bool,
     pending_newline: bool,
+    // Applied on the next render because replacing input text needs window access.
+    pending_content: Option<String>,
+    // Zero hides the counter, e.g. while the draft is empty.
+    token_estimate: usize,
+    char_count: usize,
+    max_context: Option<u32>,
 }
 
 impl EventEmitter<Submit> for MessageInput {}
+impl EventEmitter<DraftChanged> for MessageInput {}
 impl EventEmitter<Stop> for MessageInput {}
 
 impl MessageInput {
@@ -34,7 +49,13 @@ impl MessageInput {
         cx.subscribe_in(
             &input_state,
             window,
-            |this, _, event: &InputEvent, window, cx| {
+            |this, state, event: &InputEvent, window, cx| {
+                if let InputEvent::Change = event {
+                    let content = state.read(cx).value().to_string();
+                    this.set_char_count(&content, cx);
+                    cx.emit(DraftChanged { content });
+                }
+
                 if let InputEvent::PressEnter { secondary } = event {
                     if *secondary {
                         this.pending_newline = false;
@@ -59,6 +80,10 @@ impl MessageInput {
             stream_target: DEFAULT_STREAM_TARGET,
             is_streaming: false,
             pending_newline: false,
+            pending_content: None,
+            token_estimate: 0,
+            char_count: 0,
+            max_context: None,
         }
     }
 
@@ -84,6 +109,48 @@ impl MessageInput {
             state.set_value("", window, cx);
         });
         self.pending_newline = false;
+        // `set_value` emits no change event, so the counter is reset here.
+        self.set_char_count("", cx);
+    }
+
+    /// Returns the current input text, including content queued by `set_content`.
+    pub fn content(&self, cx: &App) -> String {
+        match self.pending_content.as_ref() {
+            Some(content) => content.clone(),
+            None => self.input_state.read(cx).value().to_string(),
+        }
+    }
+
+    /// Replaces the input text, e.g. to restore a saved draft.
+    pub fn set_content(&mut self, text: String, cx: &mut Context<Self>) {
+        self.set_char_count(&text, cx);
+        self.pending_content = Some(text);
+        self.pending_newline = false;
+        cx.notify();
+    }
+
+    /// Sets the estimated prompt size shown under the input; zero hides the counter.
+    pub fn set_token_estimate(&mut self, estimate: usize, cx: &mut Context<Self>) {
+        if self.token_estimate != estimate {
+            self.token_estimate = estimate;
+            cx.notify();
+        }
+    }
+
+    fn set_char_count(&mut self, content: &str, cx: &mut Context<Self>) {
+        let char_count = content.chars().count();
+        if self.char_count != char_count {
+            self.char_count = char_count;
+            cx.notify();
+        }
+    }
+
+    /// Sets the selected model's context window, used to color the token counter.
+    pub fn set_max_context(&mut self, max_context: Option<u32>, cx: &mut Context<Self>) {
+        if self.max_context != max_context {
+            self.max_context = max_context;
+            cx.notify();
+        }
     }
 
     fn handle_shift_enter(&mut self, window: &mut Window, cx: &mut Context<Self>) {
@@ -138,8 +205,31 @@ impl MessageInput {
 }
 
 impl Render for MessageInput {
-    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if let Some(content) = self.pending_content.take() {
+            self.input_state.update(cx, |state, cx| {
+                state.set_value(content, window, cx);
+            });
+        }
+
         let theme = cx.theme();
+        let token_counter = (self.token_estimate > 0).then(|| {
+            let color = match token_usage_level(self.token_estimate, self.max_context) {
+                TokenUsageLevel::Normal => theme.muted_foreground,
+                TokenUsageLevel::Warning => theme.warning,
+                TokenUsageLevel::Critical => theme.danger,
+            };
+            div()
+                .text_xs()
+                .text_color(color)
+                .child(token_estimate_label(self.token_estimate))
+        });
+        let char_counter = (self.char_count > 0).then(|| {
+            div()
+                .text_xs()
+                .text_color(theme.muted_foreground)
+                .child(char_count_label(self.char_count))
+        });
         let is_streaming = self.is_streaming;
         let action = if is_streaming {
             Button::new("stop")
@@ -187,6 +277,81 @@ impl Render for MessageInput {
                             .disabled(is_streaming),
                     ),
             )
-            .child(div().w_full().flex().justify_end().child(action))
+            .child(
+                h_flex()
+                    .w_full()
+                    .items_center()
+                    .justify_end()
+                    .gap_2()
+                    .children(char_counter)
+                    .children(token_counter)
+                    .child(action),
+            )
+    }
+}
+
+fn char_count_label(char_count: usize) -> String {
+    if char_count == 1 {
+        "1 char".to_string()
+    } else {
+        format!("{char_count} chars")
+    }
+}
+
+fn token_estimate_label(estimate: usize) -> String {
+    if estimate == 1 {
+        "~1 token".to_string()
+    } else {
+        format!("~{estimate} tokens")
+    }
+}
+
+fn token_usage_level(estimate: usize, max_context: Option<u32>) -> TokenUsageLevel {
+    let Some(max_context) = max_context.filter(|max_context| *max_context > 0) else {
+        return TokenUsageLevel::Normal;
+    };
+
+    let estimate = estimate as u64;
+    let max_context = u64::from(max_context);
+    if estimate * 10 > max_context * 9 {
+        TokenUsageLevel::Critical
+    } else if estimate * 2 > max_context {
+        TokenUsageLevel::Warning
+    } else {
+        TokenUsageLevel::Normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[::core::prelude::v1::test]
+    fn token_counter_escalates_with_context_usage() {
+        let max_context = Some(1_000);
+
+        assert_eq!(token_usage_level(500, max_context), TokenUsageLevel::Normal);
+        assert_eq!(
+            token_usage_level(501, max_context),
+            TokenUsageLevel::Warning
+        );
+        assert_eq!(
+            token_usage_level(900, max_context),
+            TokenUsageLevel::Warning
+        );
+        assert_eq!(
+            token_usage_level(901, max_context),
+            TokenUsageLevel::Critical
+        );
+        assert_eq!(token_usage_level(5_000, None), TokenUsageLevel::Normal);
+        assert_eq!(token_usage_level(1, Some(0)), TokenUsageLevel::Normal);
+        assert_eq!(token_estimate_label(1), "~1 token");
+        assert_eq!(token_estimate_label(42), "~42 tokens");
+    }
+
+    #[::core::prelude::v1::test]
+    fn char_counter_labels_pluralize() {
+        assert_eq!(char_count_label(1), "1 char");
+        assert_eq!(char_count_label(280), "280 chars");
     }
 }
@@ -3,6 +3,7 @@ use std::collections::{HashMap, HashSet};
 use std::hash::Hasher;
 use std::ops::Range;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use gpui::prelude::FluentBuilder as _;
 use gpui::*;
@@ -10,12 +11,15 @@ use gpui_component::{
     ActiveTheme, IconName, Sizable,
     button::{Button, ButtonVariants},
     h_flex,
+    input::{Input, InputEvent, InputState},
     label::Label,
+    menu::{ContextMenuExt, PopupMenuItem},
     text::TextView,
     v_flex, v_virtual_list,
 };
 
-use crate::chat::message::{Message, MessageId, MessageStatus, Role};
+use crate::chat::events::MessageDeleteRequested;
+use crate::chat::message::{Message, MessageId, MessageStatus, Role, unix_now_seconds};
 use crate::chat::scroll_manager::ScrollManager;
 
 const DEFAULT_CONTENT_WIDTH: Pixels = px(680.);
@@ -30,9 +34,50 @@ const STREAMING_INDICATOR_HEIGHT: Pixels = px(20.);
 const STREAMING_INDICATOR_GAP: Pixels = px(8.);
 const ERROR_ROW_HEIGHT: Pixels = px(20.);
 const ERROR_ROW_GAP: Pixels = px(8.);
+const TIMESTAMP_LABEL_HEIGHT: Pixels = px(16.);
+const TIMESTAMP_LABEL_GAP: Pixels = px(4.);
+const TIMESTAMP_REFRESH_INTERVAL_SECS: u64 = 60;
 const ESTIMATED_TEXT_LINE_HEIGHT: Pixels = px(18.);
 const ESTIMATED_CHAR_WIDTH: f32 = 7.0;
 const MARKDOWN_SAFE_FALLBACK_THRESHOLD_BYTES: usize = 128 * 1024;
+pub const HIGHLIGHT_DURATION: Duration = Duration::from_secs(2);
+
+gpui::actions!(message_list, [FindInConversation]);
+
+/// In-conversation find state; `match_indices` index into the flat message list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageSearchState {
+    pub query: String,
+    pub match_indices: Vec<usize>,
+    pub current_match: usize,
+}
+
+impl MessageSearchState {
+    pub fn new(query: impl Into<String>, match_indices: Vec<usize>) -> Self {
+        Self {
+            query: query.into(),
+            match_indices,
+            current_match: 0,
+        }
+    }
+
+    /// Returns the message index of the focused match, if any.
+    pub fn current_message_index(&self) -> Option<usize> {
+        self.match_indices.get(self.current_match).copied()
+    }
+
+    /// Moves to the next match, wrapping after the last one.
+    pub fn advance(&mut self) -> Option<usize> {
+        if self.match_indices.is_empty() {
+            return None;
+        }
+
+        self.current_match = (self.current_match + 1) % self.match_indices.len();
+        self.current_message_index()
+    }
+}
+
+impl EventEmitter<MessageDeleteRequested> for MessageList {}
 
 struct SizeCacheEntry {
     layout_hash: u64,
@@ -46,16 +91,42 @@ pub struct MessageList {
     scroll_manager: ScrollManager,
     size_cache: HashMap<MessageId, SizeCacheEntry>,
     content_width: Option<Pixels>,
+    scroll_at_bottom: bool,
+    highlighted_message: Option<(MessageId, Instant)>,
+    highlight_clear_task: Option<Task<()>>,
+    search_state: Option<MessageSearchState>,
+    // Created on first open because `InputState` needs a window.
+    search_input: Option<Entity<InputState>>,
+    _timestamp_refresh_task: Task<()>,
 }
 
 impl MessageList {
-    pub fn new(_cx: &mut Context<Self>) -> Self {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let timestamp_refresh_task = cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor()
+                    .timer(Duration::from_secs(TIMESTAMP_REFRESH_INTERVAL_SECS))
+                    .await;
+
+                // Relative labels drift with wall-clock time, so repaint even without new messages.
+                if this.update(cx, |_, cx| cx.notify()).is_err() {
+                    break;
+                }
+            }
+        });
+
         Self {
             messages: Vec::new(),
             item_sizes: Rc::new(Vec::new()),
             scroll_manager: ScrollManager::new(),
             size_cache: HashMap::new(),
             content_width: None,
+            scroll_at_bottom: true,
+            highlighted_message: None,
+            highlight_clear_task: None,
+            search_state: None,
+            search_input: None,
+            _timestamp_refresh_task: timestamp_refresh_task,
         }
     }
 
@@ -71,8 +142,11 @@ impl MessageList {
 
         self.messages = messages;
         self.rebuild_item_sizes();
+        self.refresh_search_matches();
 
-        if should_request_follow {
+        if self.scroll_at_bottom {
+            self.scroll_manager.request_scroll_to_bottom();
+        } else if should_request_follow {
             self.scroll_manager.request_scroll_to_bottom_if_following();
         }
 
@@ -86,7 +160,200 @@ impl MessageList {
 
     pub fn reset_scroll_tracking(&mut self, cx: &mut Context<Self>) {
         self.scroll_manager.reset();
+        self.scroll_at_bottom = true;
+        cx.notify();
+    }
+
+    /// Scrolls a message into view and highlights it for [`HIGHLIGHT_DURATION`].
+    pub fn scroll_to_message(&mut self, message_id: MessageId, cx: &mut Context<Self>) -> bool {
+        let Some(index) = self
+            .messages
+            .iter()
+            .position(|message| message.id == message_id)
+        else {
+            return false;
+        };
+
+        self.scroll_manager.scroll_to_item(index);
+        self.scroll_at_bottom = false;
+        self.highlighted_message = Some((message_id, Instant::now()));
+        self.highlight_clear_task = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(HIGHLIGHT_DURATION).await;
+            let _ = this.update(cx, |this, cx| {
+                this.clear_expired_highlight(Instant::now());
+                this.highlight_clear_task = None;
+                cx.notify();
+            });
+        }));
         cx.notify();
+        true
+    }
+
+    /// Returns indices of messages whose content contains `query`, ignoring ASCII case.
+    pub fn search_visible_messages(&self, query: &str) -> Vec<usize> {
+        find_matching_messages(&self.messages, query)
+    }
+
+    pub fn search_state(&self) -> Option<&MessageSearchState> {
+        self.search_state.as_ref()
+    }
+
+    /// Shows the find bar and focuses its input, keeping any previous query.
+    pub fn open_search(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let search_input = match self.search_input.clone() {
+            Some(search_input) => search_input,
+            None => {
+                let search_input =
+                    cx.new(|cx| InputState::new(window, cx).placeholder("Find in conversation"));
+                cx.subscribe_in(
+                    &search_input,
+                    window,
+                    |this, state, event: &InputEvent, _window, cx| match event {
+                        InputEvent::Change => {
+                            let query = state.read(cx).value().to_string();
+                            this.set_search_query(query, cx);
+                        }
+                        InputEvent::PressEnter { .. } => this.select_next_match(cx),
+                        _ => {}
+                    },
+                )
+                .detach();
+                self.search_input = Some(search_input.clone());
+                search_input
+            }
+        };
+
+        if self.search_state.is_none() {
+            let query = search_input.read(cx).value().to_string();
+            self.set_search_query(query, cx);
+        }
+        search_input.update(cx, |state, cx| state.focus(window, cx));
+        cx.notify();
+    }
+
+    pub fn close_search(&mut self, cx: &mut Context<Self>) {
+        self.search_state = None;
+        cx.notify();
+    }
+
+    fn set_search_query(&mut self, query: String, cx: &mut Context<Self>) {
+        let match_indices = find_matching_messages(&self.messages, &query);
+        self.search_state = Some(MessageSearchState::new(query, match_indices));
+        self.scroll_to_current_match();
+        cx.notify();
+    }
+
+    fn select_next_match(&mut self, cx: &mut Context<Self>) {
+        if let Some(search_state) = self.search_state.as_mut() {
+            search_state.advance();
+        }
+        self.scroll_to_current_match();
+        cx.notify();
+    }
+
+    fn scroll_to_current_match(&mut self) {
+        let Some(index) = self
+            .search_state
+            .as_ref()
+            .and_then(MessageSearchState::current_message_index)
+        else {
+            return;
+        };
+
+        self.scroll_manager.scroll_to_item(index);
+        self.scroll_at_bottom = false;
+    }
+
+    fn refresh_search_matches(&mut self) {
+        let Some(search_state) = self.search_state.as_mut() else {
+            return;
+        };
+
+        search_state.match_indices = find_matching_messages(&self.messages, &search_state.query);
+        if search_state.current_match >= search_state.match_indices.len() {
+            search_state.current_match = 0;
+        }
+    }
+
+    fn search_query_for_row(&self, index: usize) -> Option<(&str, bool)> {
+        let search_state = self.search_state.as_ref()?;
+        if !search_state.match_indices.contains(&index) {
+            return None;
+        }
+
+        let is_current = search_state.current_message_index() == Some(index);
+        Some((search_state.query.as_str(), is_current))
+    }
+
+    fn render_search_bar(&self, cx: &mut Context<Self>) -> Option<AnyElement> {
+        let search_state = self.search_state.as_ref()?;
+        let search_input = self.search_input.as_ref()?;
+        let theme = cx.theme();
+        let match_label = if search_state.match_indices.is_empty() {
+            "No matches".to_string()
+        } else {
+            format!(
+                "{}/{}",
+                search_state.current_match + 1,
+                search_state.match_indices.len()
+            )
+        };
+
+        Some(
+            h_flex()
+                .id("message-list-search-bar")
+                .absolute()
+                .top_2()
+                .right_4()
+                .w(px(320.))
+                .gap_2()
+                .px_2()
+                .py_1()
+                .items_center()
+                .rounded_md()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.background)
+                .shadow_md()
+                .on_key_down(cx.listener(|this, event: &KeyDownEvent, _window, cx| {
+                    if event.keystroke.key == "escape" {
+                        this.close_search(cx);
+                        cx.stop_propagation();
+                    }
+                }))
+                .child(
+                    div()
+                        .flex_1()
+                        .min_w_0()
+                        .child(Input::new(search_input).small()),
+                )
+                .child(
+                    Label::new(match_label)
+                        .text_xs()
+                        .text_color(theme.muted_foreground),
+                )
+                .child(
+                    Button::new("message-list-search-close")
+                        .ghost()
+                        .xsmall()
+                        .icon(IconName::Close)
+                        .on_click(cx.listener(|this, _, _window, cx| this.close_search(cx))),
+                )
+                .into_any_element(),
+        )
+    }
+
+    fn clear_expired_highlight(&mut self, now: Instant) {
+        if let Some((message_id, _)) = self.highlighted_message
+            && !highlight_is_active(self.highlighted_message, message_id, now)
+        {
+            self.highlighted_message = None;
+        }
+    }
+
+    fn jump_to_bottom(&mut self, _: &ClickEvent, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_at_bottom = true;
+        self.request_scroll_to_bottom(cx);
     }
 
     fn update_content_width(&mut self, cx: &mut Context<Self>) {
@@ -211,6 +478,45 @@ impl MessageList {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> AnyElement {
+        let row = self.render_message_content_row(message, index, window, cx);
+        // A streaming reply is still being written, so it cannot be deleted yet.
+        if matches!(message.status, MessageStatus::Streaming(_)) {
+            return row;
+        }
+
+        let list = cx.entity();
+        let message_id = message.id;
+        div()
+            .id(("message-row", message_id.0))
+            .w_full()
+            .child(row)
+            .context_menu(move |menu, _window, _cx| {
+                let list = list.clone();
+                menu.item(PopupMenuItem::new("Delete message").on_click(
+                    move |_event, _window, cx| {
+                        list.update(cx, |_this, cx| {
+                            cx.emit(MessageDeleteRequested { message_id });
+                        });
+                    },
+                ))
+            })
+            .into_any_element()
+    }
+
+    fn render_message_content_row(
+        &self,
+        message: &Message,
+        index: usize,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let relative_time =
+            format_relative_time(unix_now_seconds(), message.created_at_unix_seconds);
+        let is_highlighted =
+            highlight_is_active(self.highlighted_message, message.id, Instant::now());
+        let search_match = self.search_query_for_row(index);
+        let is_current_search_match = search_match.is_some_and(|(_, is_current)| is_current);
+
         if message.role == Role::User {
             let theme = cx.theme();
             let content = if message.content.is_empty() {
@@ -218,19 +524,34 @@ impl MessageList {
             } else {
                 message.content.clone()
             };
+            let content = match search_match {
+                Some((query, _)) => highlighted_text(content, query).into_any_element(),
+                None => Label::new(content).text_sm().into_any_element(),
+            };
 
             return v_flex()
                 .w_full()
                 .items_end()
+                .gap_1()
                 .child(
                     div()
                         .max_w(USER_BUBBLE_MAX_WIDTH)
                         .px(USER_BUBBLE_PADDING_X)
                         .py(USER_BUBBLE_PADDING_Y)
                         .rounded_lg()
-                        .bg(theme.accent)
+                        .bg(if is_highlighted || is_current_search_match {
+                            yellow().opacity(0.45)
+                        } else {
+                            theme.accent
+                        })
                         .text_color(theme.accent_foreground)
-                        .child(Label::new(content).text_sm()),
+                        .text_sm()
+                        .child(content),
+                )
+                .child(
+                    Label::new(relative_time)
+                        .text_xs()
+                        .text_color(theme.muted_foreground),
                 )
                 .into_any_element();
         }
@@ -241,7 +562,14 @@ impl MessageList {
             "Assistant"
         };
 
-        let content = self.render_assistant_content(message, index, window, cx);
+        let content = match search_match {
+            // Markdown rendering cannot carry match ranges, so matches show as plain text.
+            Some((query, _)) => div()
+                .text_sm()
+                .child(highlighted_text(message.content.clone(), query))
+                .into_any_element(),
+            None => self.render_assistant_content(message, index, window, cx),
+        };
         let theme = cx.theme();
         let error_message = if let MessageStatus::Error(error) = &message.status {
             Some(error.clone())
@@ -252,6 +580,10 @@ impl MessageList {
         v_flex()
             .w_full()
             .gap_2()
+            .rounded_lg()
+            .when(is_highlighted || is_current_search_match, |column| {
+                column.bg(yellow().opacity(0.2))
+            })
             .child(
                 Label::new(speaker_label)
                     .text_xs()
@@ -282,6 +614,11 @@ impl MessageList {
                         .text_color(theme.danger),
                 )
             })
+            .child(
+                Label::new(relative_time)
+                    .text_xs()
+                    .text_color(theme.muted_foreground),
+            )
             .into_any_element()
     }
 
@@ -341,33 +678,56 @@ impl Render for MessageList {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         self.update_content_width(cx);
         self.scroll_manager.update_follow_state();
+        self.scroll_at_bottom = self.scroll_manager.is_following_bottom();
         self.scroll_manager.apply_pending_scroll();
+        let search_bar = self.render_search_bar(cx);
 
-        v_flex().size_full().min_h_0().child(
-            v_virtual_list(
-                cx.entity().clone(),
-                "message-list",
-                self.item_sizes.clone(),
-                |this, visible_range, window, cx| {
-                    // Measure only visible rows so long histories keep O(visible) layout work.
-                    this.update_content_width(cx);
-                    this.measure_visible_items(visible_range.clone(), window, cx);
-                    visible_range
-                        .filter_map(|index| {
-                            this.messages
-                                .get(index)
-                                .cloned()
-                                .map(|message| this.render_message_row(&message, index, window, cx))
-                        })
-                        .collect::<Vec<_>>()
-                },
-            )
+        v_flex()
             .size_full()
-            .px_4()
-            .py_3()
-            .gap_4()
-            .track_scroll(self.scroll_manager.handle()),
-        )
+            .min_h_0()
+            .relative()
+            .child(
+                v_virtual_list(
+                    cx.entity().clone(),
+                    "message-list",
+                    self.item_sizes.clone(),
+                    |this, visible_range, window, cx| {
+                        // Measure only visible rows so long histories keep O(visible) layout work.
+                        this.update_content_width(cx);
+                        this.measure_visible_items(visible_range.clone(), window, cx);
+                        visible_range
+                            .filter_map(|index| {
+                                this.messages.get(index).cloned().map(|message| {
+                                    this.render_message_row(&message, index, window, cx)
+                                })
+                            })
+                            .collect::<Vec<_>>()
+                    },
+                )
+                .size_full()
+                .px_4()
+                .py_3()
+                .gap_4()
+                .track_scroll(self.scroll_manager.handle()),
+            )
+            .children(search_bar)
+            .when(!self.scroll_at_bottom, |list| {
+                list.child(
+                    h_flex()
+                        .absolute()
+                        .bottom_3()
+                        .left_0()
+                        .right_0()
+                        .justify_center()
+                        .child(
+                            Button::new("message-list-jump-to-bottom")
+                                .primary()
+                                .small()
+                                .child("↓ New message")
+                                .on_click(cx.listener(Self::jump_to_bottom)),
+                        ),
+                )
+            })
     }
 }
 
@@ -407,7 +767,7 @@ fn estimate_message_height(message: &Message, content_width: Pixels) -> Pixels {
             let bubble_width = min_pixels(content_width, USER_BUBBLE_MAX_WIDTH);
             let text_width = max_pixels(px(1.), bubble_width - USER_BUBBLE_PADDING_X * 2);
             let text_height = estimate_text_height(&message.content, text_width);
-            text_height + USER_BUBBLE_PADDING_Y * 2
+            text_height + USER_BUBBLE_PADDING_Y * 2 + TIMESTAMP_LABEL_GAP + TIMESTAMP_LABEL_HEIGHT
         }
         Role::System | Role::Assistant => {
             let text_height = if message.content.is_empty() {
@@ -423,12 +783,69 @@ fn estimate_message_height(message: &Message, content_width: Pixels) -> Pixels {
             if matches!(message.status, MessageStatus::Error(_)) {
                 total_height += ERROR_ROW_GAP + ERROR_ROW_HEIGHT;
             }
+            total_height += ASSISTANT_LABEL_GAP + TIMESTAMP_LABEL_HEIGHT;
 
             total_height
         }
     }
 }
 
+fn find_matching_messages(messages: &[Message], query: &str) -> Vec<usize> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    messages
+        .iter()
+        .enumerate()
+        .filter(|(_, message)| !text_highlight_ranges(&message.content, query).is_empty())
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Byte ranges of non-overlapping `query` occurrences in `content`, ignoring ASCII case.
+fn text_highlight_ranges(content: &str, query: &str) -> Vec<Range<usize>> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    // ASCII lowercasing keeps byte offsets aligned with the original text.
+    let haystack = content.to_ascii_lowercase();
+    let needle = query.to_ascii_lowercase();
+    haystack
+        .match_indices(&needle)
+        .map(|(start, matched)| start..start + matched.len())
+        .collect()
+}
+
+fn highlighted_text(content: String, query: &str) -> StyledText {
+    let highlights = text_highlight_ranges(&content, query)
+        .into_iter()
+        .map(|range| {
+            (
+                range,
+                HighlightStyle {
+                    background_color: Some(yellow().opacity(0.6)),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    StyledText::new(content).with_highlights(highlights)
+}
+
+fn highlight_is_active(
+    highlight: Option<(MessageId, Instant)>,
+    message_id: MessageId,
+    now: Instant,
+) -> bool {
+    highlight.is_some_and(|(highlighted_id, started_at)| {
+        highlighted_id == message_id
+            && now.saturating_duration_since(started_at) < HIGHLIGHT_DURATION
+    })
+}
+
 fn estimate_text_height(content: &str, width: Pixels) -> Pixels {
     if content.is_empty() {
         return ESTIMATED_TEXT_LINE_HEIGHT;
@@ -463,6 +880,54 @@ fn pixels_changed(a: Pixels, b: Pixels) -> bool {
     (f32::from(a) - f32::from(b)).abs() > 0.5
 }
 
+/// Formats a message timestamp as a coarse label relative to `now`.
+///
+/// Buckets use elapsed time only; the "Yesterday" label adds the local wall-clock time.
+pub fn format_relative_time(now: u64, ts: u64) -> String {
+    format_relative_time_in(now, ts, &chrono::Local)
+}
+
+/// Formats like [`format_relative_time`], rendering wall-clock times in `timezone`.
+fn format_relative_time_in<Tz: chrono::TimeZone>(now: u64, ts: u64, timezone: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    const MINUTE_SECONDS: u64 = 60;
+    const HOUR_SECONDS: u64 = 60 * MINUTE_SECONDS;
+    const DAY_SECONDS: u64 = 24 * HOUR_SECONDS;
+
+    // Clock skew can place a timestamp slightly in the future; treat it as fresh.
+    let elapsed_seconds = now.saturating_sub(ts);
+
+    if elapsed_seconds < MINUTE_SECONDS {
+        "just now".to_string()
+    } else if elapsed_seconds < HOUR_SECONDS {
+        let minutes = elapsed_seconds / MINUTE_SECONDS;
+        if minutes == 1 {
+            "1 minute ago".to_string()
+        } else {
+            format!("{minutes} minutes ago")
+        }
+    } else if elapsed_seconds < DAY_SECONDS {
+        let hours = elapsed_seconds / HOUR_SECONDS;
+        if hours == 1 {
+            "1 hour ago".to_string()
+        } else {
+            format!("{hours} hours ago")
+        }
+    } else if elapsed_seconds < DAY_SECONDS * 2 {
+        let Some(time) = i64::try_from(ts)
+            .ok()
+            .and_then(|ts| timezone.timestamp_opt(ts, 0).single())
+        else {
+            return "Yesterday".to_string();
+        };
+        format!("Yesterday at {}", time.format("%-I:%M %p"))
+    } else {
+        format!("{} days ago", elapsed_seconds / DAY_SECONDS)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct VirtualizationMetric {
     pub message_id: MessageId,
@@ -542,4 +1007,86 @@ mod tests {
         assert_eq!(hashes_before[..1_999], hashes_after[..1_999]);
         assert_ne!(hashes_before[1_999], hashes_after[1_999]);
     }
+
+    #[::core::prelude::v1::test]
+    fn highlight_expires_after_highlight_duration() {
+        let started_at = Instant::now();
+        let highlight = Some((MessageId::new(3), started_at));
+
+        assert!(highlight_is_active(
+            highlight,
+            MessageId::new(3),
+            started_at
+        ));
+        assert!(!highlight_is_active(
+            highlight,
+            MessageId::new(4),
+            started_at
+        ));
+        assert!(!highlight_is_active(
+            highlight,
+            MessageId::new(3),
+            started_at + HIGHLIGHT_DURATION
+        ));
+        assert!(!highlight_is_active(None, MessageId::new(3), started_at));
+    }
+
+    #[::core::prelude::v1::test]
+    fn relative_time_labels_cover_elapsed_buckets() {
+        let now = 1_700_000_000;
+
+        assert_eq!(format_relative_time(now, now), "just now");
+        assert_eq!(format_relative_time(now, now - 59), "just now");
+        assert_eq!(format_relative_time(now, now + 30), "just now");
+        assert_eq!(format_relative_time(now, now - 60), "1 minute ago");
+        assert_eq!(format_relative_time(now, now - 5 * 60), "5 minutes ago");
+        assert_eq!(format_relative_time(now, now - 3 * 60 * 60), "3 hours ago");
+        assert_eq!(
+            format_relative_time_in(now, now - 30 * 60 * 60, &chrono::Utc),
+            "Yesterday at 4:13 PM"
+        );
+        assert_eq!(
+            format_relative_time(now, now - 4 * 24 * 60 * 60),
+            "4 days ago"
+        );
+    }
+
+    #[::core::prelude::v1::test]
+    fn search_counts_matches_and_cycles_current_match() {
+        let messages = [
+            "Deploy the Rust service",
+            "Which region?",
+            "rust builds are cached",
+            "Use the eu region for RUST",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, content)| {
+            Message::new(
+                MessageId::new(index as u64 + 1),
+                Role::User,
+                content,
+                MessageStatus::Done,
+            )
+        })
+        .collect::<Vec<_>>();
+
+        let match_indices = find_matching_messages(&messages, "rust");
+        assert_eq!(match_indices, vec![0, 2, 3]);
+        assert!(find_matching_messages(&messages, "").is_empty());
+        assert_eq!(
+            text_highlight_ranges("Rust or rust", "RUST"),
+            vec![0..4, 8..12]
+        );
+
+        let mut search_state = MessageSearchState::new("rust", match_indices);
+        assert_eq!(search_state.current_message_index(), Some(0));
+        assert_eq!(search_state.advance(), Some(2));
+        assert_eq!(search_state.advance(), Some(3));
+        assert_eq!(search_state.advance(), Some(0));
+        assert_eq!(search_state.current_match, 0);
+
+        let mut empty_state = MessageSearchState::new("missing", Vec::new());
+        assert_eq!(empty_state.advance(), None);
+    }
 }
@@ -9,14 +9,17 @@ pub mod sidebar;
 pub mod view;
 
 pub use events::{
-    ConversationSelected, ModelChanged, Stop, StreamEventMapped, StreamEventPayload, Submit,
+    ConversationClearRequested, ConversationRenamed, ConversationSelected,
+    ConversationStatisticsRequested, DraftChanged, MessageDeleteRequested, ModelChanged, Stop,
+    StreamEventMapped, StreamEventPayload, Submit,
 };
 pub use message::{
-    Conversation, ConversationId, Message, MessageId, MessageStatus, Role, StreamSessionId,
-    StreamState, StreamTarget, StreamTransition, StreamTransitionRejection, StreamTransitionResult,
+    Conversation, ConversationId, ConversationStats, Message, MessageId, MessageStatus, Role,
+    StreamSessionId, StreamState, StreamTarget, StreamTransition, StreamTransitionRejection,
+    StreamTransitionResult,
 };
 pub use message_input::MessageInput;
-pub use message_list::MessageList;
+pub use message_list::{FindInConversation, MessageList, MessageSearchState};
 pub use scroll_manager::ScrollManager;
-pub use sidebar::{ChatSidebar, SidebarSettingsClicked, SidebarToggleClicked};
+pub use sidebar::{ChatSidebar, SearchResult, SidebarSettingsClicked, SidebarToggleClicked};
 pub use view::ChatView;
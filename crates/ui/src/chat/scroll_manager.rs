@@ -1,4 +1,4 @@
-use gpui::{Bounds, Pixels, Point, Size, point, px};
+use gpui::{Bounds, Pixels, Point, ScrollStrategy, Size, point, px};
 use gpui_component::VirtualListScrollHandle;
 
 /// Near-bottom distance used to resume follow mode deterministically.
@@ -45,6 +45,14 @@ impl ScrollManager {
         }
     }
 
+    /// Centers a row and pauses follow mode so the jump is not undone by the next bottom snap.
+    pub fn scroll_to_item(&mut self, index: usize) {
+        self.follow_bottom = false;
+        self.pending_scroll_to_bottom = false;
+        self.scroll_handle
+            .scroll_to_item(index, ScrollStrategy::Center);
+    }
+
     pub fn reset(&mut self) {
         self.last_scroll_offset = Pixels::ZERO;
         self.last_max_offset = Pixels::ZERO;
@@ -53,29 +61,23 @@ impl ScrollManager {
     }
 
     pub fn update_follow_state(&mut self) {
-        let offset = self.scroll_handle.offset().y;
-        let max_offset = self.scroll_handle.max_offset().height;
-        let offset_delta = f32::from(offset) - f32::from(self.last_scroll_offset);
-        let max_delta = (f32::from(max_offset) - f32::from(self.last_max_offset)).abs();
-        let content_size_changed = max_delta > SCROLL_DELTA_EPSILON;
-        let user_scrolled_up = offset_delta > SCROLL_DELTA_EPSILON && !content_size_changed;
-        let user_scrolled_down = offset_delta < -SCROLL_DELTA_EPSILON && !content_size_changed;
-
-        // Keep follow mode enabled while we are fulfilling an explicit follow request.
-        if self.pending_scroll_to_bottom || (content_size_changed && self.was_near_bottom()) {
-            self.follow_bottom = true;
-        } else if self.follow_bottom {
-            // Pause follow mode only when the user manually scrolls away from the tail.
-            if user_scrolled_up {
-                self.follow_bottom = false;
-            }
-        } else if user_scrolled_down && self.is_near_bottom() {
-            // Resume follow mode once user intentionally returns near the bottom boundary.
-            self.follow_bottom = true;
-        }
-
-        self.last_scroll_offset = offset;
-        self.last_max_offset = max_offset;
+        let current = ScrollSample {
+            offset: self.scroll_handle.offset().y,
+            max_offset: self.scroll_handle.max_offset().height,
+        };
+        let previous = ScrollSample {
+            offset: self.last_scroll_offset,
+            max_offset: self.last_max_offset,
+        };
+
+        self.follow_bottom = next_follow_bottom(
+            self.follow_bottom,
+            self.pending_scroll_to_bottom,
+            previous,
+            current,
+        );
+        self.last_scroll_offset = current.offset;
+        self.last_max_offset = current.max_offset;
     }
 
     pub fn apply_pending_scroll(&mut self) -> bool {
@@ -108,25 +110,54 @@ impl ScrollManager {
         self.scroll_handle.max_offset()
     }
 
-    fn is_near_bottom(&self) -> bool {
-        let max_offset = self.scroll_handle.max_offset().height;
-        if max_offset <= Pixels::ZERO {
-            return true;
+    fn was_near_bottom(&self) -> bool {
+        ScrollSample {
+            offset: self.last_scroll_offset,
+            max_offset: self.last_max_offset,
         }
-
-        // GPUI uses negative Y offsets for scrolling down, so `offset + max` approaches 0 at tail.
-        let offset = self.scroll_handle.offset().y;
-        (offset + max_offset).abs() <= AUTO_FOLLOW_RESUME_THRESHOLD
+        .is_near_bottom()
     }
+}
 
-    fn was_near_bottom(&self) -> bool {
-        let max_offset = self.last_max_offset;
-        if max_offset <= Pixels::ZERO {
+/// One observation of the vertical scroll position.
+#[derive(Debug, Clone, Copy)]
+struct ScrollSample {
+    offset: Pixels,
+    max_offset: Pixels,
+}
+
+impl ScrollSample {
+    fn is_near_bottom(self) -> bool {
+        if self.max_offset <= Pixels::ZERO {
             return true;
         }
 
-        let offset = self.last_scroll_offset;
-        (offset + max_offset).abs() <= AUTO_FOLLOW_RESUME_THRESHOLD
+        // GPUI uses negative Y offsets for scrolling down, so `offset + max` approaches 0 at tail.
+        (self.offset + self.max_offset).abs() <= AUTO_FOLLOW_RESUME_THRESHOLD
+    }
+}
+
+fn next_follow_bottom(
+    follow_bottom: bool,
+    pending_scroll_to_bottom: bool,
+    previous: ScrollSample,
+    current: ScrollSample,
+) -> bool {
+    let offset_delta = f32::from(current.offset) - f32::from(previous.offset);
+    let max_delta = (f32::from(current.max_offset) - f32::from(previous.max_offset)).abs();
+    let content_size_changed = max_delta > SCROLL_DELTA_EPSILON;
+    let user_scrolled_up = offset_delta > SCROLL_DELTA_EPSILON && !content_size_changed;
+    let user_scrolled_down = offset_delta < -SCROLL_DELTA_EPSILON && !content_size_changed;
+
+    // Keep follow mode enabled while we are fulfilling an explicit follow request.
+    if pending_scroll_to_bottom || (content_size_changed && previous.is_near_bottom()) {
+        true
+    } else if follow_bottom {
+        // Pause follow mode only when the user manually scrolls away from the tail.
+        !user_scrolled_up
+    } else {
+        // Resume follow mode once user intentionally returns near the bottom boundary.
+        user_scrolled_down && current.is_near_bottom()
     }
 }
 
@@ -135,3 +166,48 @@ impl Default for ScrollManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(offset: f32, max_offset: f32) -> ScrollSample {
+        ScrollSample {
+            offset: px(offset),
+            max_offset: px(max_offset),
+        }
+    }
+
+    #[::core::prelude::v1::test]
+    fn follow_state_pauses_on_scroll_up_and_resumes_at_bottom() {
+        let at_bottom = sample(-1_000., 1_000.);
+        let scrolled_up = sample(-400., 1_000.);
+
+        let follow_bottom = next_follow_bottom(true, false, at_bottom, scrolled_up);
+        assert!(!follow_bottom);
+
+        // Streaming growth must not drag a reader back down while they re-read history.
+        let grown_while_up = sample(-400., 1_200.);
+        let follow_bottom = next_follow_bottom(follow_bottom, false, scrolled_up, grown_while_up);
+        assert!(!follow_bottom);
+
+        let back_at_bottom = sample(-1_190., 1_200.);
+        let follow_bottom =
+            next_follow_bottom(follow_bottom, false, grown_while_up, back_at_bottom);
+        assert!(follow_bottom);
+
+        let grown_at_bottom = sample(-1_190., 1_400.);
+        assert!(next_follow_bottom(
+            follow_bottom,
+            false,
+            back_at_bottom,
+            grown_at_bottom
+        ));
+    }
+
+    #[::core::prelude::v1::test]
+    fn pending_scroll_request_forces_follow_state() {
+        let scrolled_up = sample(-400., 1_000.);
+        assert!(next_follow_bottom(false, true, scrolled_up, scrolled_up));
+    }
+}
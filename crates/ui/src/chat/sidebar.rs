@@ -1,32 +1,53 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
+use futures::StreamExt;
 use gpui::*;
 use gpui_component::{
-    ActiveTheme, IconName, Sizable, VirtualListScrollHandle,
+    ActiveTheme, Icon, IconName, Sizable, VirtualListScrollHandle,
     button::{Button, ButtonVariants},
     h_flex,
     input::{Input, InputEvent, InputState},
     label::Label,
     list::ListItem,
+    menu::{ContextMenuExt, PopupMenuItem},
+    progress::Progress,
     v_flex, v_virtual_list,
 };
 
-use crate::chat::events::ConversationSelected;
-use crate::chat::message::{ConversationId, Role};
+use crate::chat::events::{
+    ConversationClearRequested, ConversationRenamed, ConversationSelected,
+    ConversationStatisticsRequested,
+};
+use crate::chat::message::{ConversationId, ConversationStats, Role, unix_now_seconds};
 use crate::database::{ConversationRecord, DEFAULT_CONVERSATION_TITLE};
+mod export;
+
 use zova_storage::{
-    MessageId as StorageMessageId, MessagePatch, MessageRecord as StorageMessageRecord,
-    MessageRole as StorageMessageRole, MessageStore, NewMessage, NewSession, SessionId,
-    SessionStore, SqliteStorage,
+    BranchStore, DraftStore, MediaStore, MessageId as StorageMessageId, MessagePatch,
+    MessageRecord as StorageMessageRecord, MessageRole as StorageMessageRole, MessageSearchHit,
+    MessageStore, NewMessage, NewSession, SessionId, SessionPatch, SessionRecord, SessionStore,
+    SqliteStorage, SqliteStorageOptions, StorageError, StorageResult,
 };
 
 const GROUP_HEADER_HEIGHT: f32 = 26.0;
-const CONVERSATION_ROW_HEIGHT: f32 = 40.0;
+const CONVERSATION_ROW_HEIGHT: f32 = 52.0;
 const DAY_SECONDS: u64 = 60 * 60 * 24;
 const DEFAULT_STORAGE_DB_RELATIVE_PATH: &str = ".zova/storage.db";
+const SIDEBAR_SEARCH_RESULT_LIMIT: usize = 200;
+const GLOBAL_SEARCH_RESULT_LIMIT: usize = 50;
+/// Characters of context kept before a message match in search snippets.
+const SNIPPET_LEADING_CHARS: usize = 30;
+const SNIPPET_MAX_CHARS: usize = 120;
+const UNCATEGORIZED_TAG: &str = "Uncategorized";
+const TRANSIENT_RETRY_ATTEMPTS: usize = 3;
+const TRANSIENT_RETRY_BACKOFF: Duration = Duration::from_millis(100);
+/// Lets global search run its title and message queries side by side; WAL keeps the extra
+/// reader from blocking writes.
+const STORAGE_MAX_CONNECTIONS: u32 = 2;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ConversationAgeGroup {
@@ -35,28 +56,78 @@ enum ConversationAgeGroup {
     Older,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyboardStep {
+    Up,
+    Down,
+}
+
 #[derive(Debug, Clone)]
 enum SidebarListItem {
     GroupHeader(&'static str),
+    TagHeader(String),
+    Conversation(ConversationRecord),
+}
+
+/// A hit from [`ChatSidebar::global_search`]; each conversation appears at most once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchResult {
+    /// The conversation title matched the query.
     Conversation(ConversationRecord),
+    /// A message in the conversation matched the query.
+    Message {
+        conversation: ConversationRecord,
+        message_id: StorageMessageId,
+        message_snippet: String,
+    },
+}
+
+impl SearchResult {
+    pub fn conversation(&self) -> &ConversationRecord {
+        match self {
+            Self::Conversation(conversation) | Self::Message { conversation, .. } => conversation,
+        }
+    }
+}
+
+/// Storage-level hit backing a [`SearchResult`] before conversation ids are assigned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StorageSearchHit {
+    session: SessionRecord,
+    message: Option<(StorageMessageId, String)>,
 }
 
 pub struct ChatSidebar {
     search_input: Entity<InputState>,
     search_query: String,
+    rename_input: Entity<InputState>,
+    renaming_conversation: Option<ConversationId>,
     conversations: Vec<ConversationRecord>,
     selected_conversation: Option<ConversationId>,
     flat_items: Vec<SidebarListItem>,
     item_sizes: Rc<Vec<Size<Pixels>>>,
+    collapsed_tags: HashSet<String>,
     scroll_handle: VirtualListScrollHandle,
+    focus_handle: FocusHandle,
+    keyboard_focused_index: Option<usize>,
     storage: Option<Arc<SqliteStorage>>,
+    storage_error: Option<String>,
+    export_progress: Option<f32>,
+    export_status: Option<String>,
     conversation_to_session: HashMap<ConversationId, SessionId>,
     session_to_conversation: HashMap<SessionId, ConversationId>,
     next_conversation_id: u64,
+    refresh_retry_task: Option<Task<()>>,
 }
 
 impl EventEmitter<ConversationSelected> for ChatSidebar {}
 
+impl EventEmitter<ConversationRenamed> for ChatSidebar {}
+
+impl EventEmitter<ConversationClearRequested> for ChatSidebar {}
+
+impl EventEmitter<ConversationStatisticsRequested> for ChatSidebar {}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SidebarSettingsClicked;
 
@@ -71,33 +142,72 @@ impl ChatSidebar {
     pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
         let search_input =
             cx.new(|cx| InputState::new(window, cx).placeholder("Search conversations..."));
-        let storage = Self::open_storage();
+        let (storage, storage_error) = match Self::open_storage() {
+            Ok(storage) => (Some(storage), None),
+            Err(error) => (None, Some(error.display_message().to_string())),
+        };
 
         cx.subscribe_in(
             &search_input,
             window,
             |this, _, _event: &InputEvent, _window, cx| {
                 this.search_query = this.search_input.read(cx).value().to_string();
-                this.rebuild_flat_items();
+                this.refresh_from_store(cx);
                 cx.notify();
             },
         )
         .detach();
 
+        let rename_input = cx.new(|cx| InputState::new(window, cx));
+        cx.subscribe_in(
+            &rename_input,
+            window,
+            |this, _, event: &InputEvent, _window, cx| match event {
+                InputEvent::PressEnter { .. } => this.commit_rename(cx),
+                InputEvent::Blur => this.cancel_rename(cx),
+                InputEvent::Change | InputEvent::Focus => {}
+            },
+        )
+        .detach();
+
+        let focus_handle = cx.focus_handle();
+        cx.on_focus(&focus_handle, window, |this, _window, cx| {
+            // Start keyboard travel from the active conversation so focus never jumps unexpectedly.
+            if this.keyboard_focused_index.is_none() {
+                let selected_index = this.selected_conversation.and_then(|selected| {
+                    this.keyboard_conversation_ids()
+                        .iter()
+                        .position(|conversation_id| *conversation_id == selected)
+                });
+                this.keyboard_focused_index = selected_index.or(Some(0));
+                cx.notify();
+            }
+        })
+        .detach();
+
         let mut sidebar = Self {
             search_input,
             search_query: String::new(),
+            rename_input,
+            renaming_conversation: None,
             conversations: Vec::new(),
             selected_conversation: None,
             flat_items: Vec::new(),
             item_sizes: Rc::new(Vec::new()),
+            collapsed_tags: HashSet::new(),
             scroll_handle: VirtualListScrollHandle::new(),
+            focus_handle,
+            keyboard_focused_index: None,
             storage,
+            storage_error,
+            export_progress: None,
+            export_status: None,
             conversation_to_session: HashMap::new(),
             session_to_conversation: HashMap::new(),
             next_conversation_id: 1,
+            refresh_retry_task: None,
         };
-        sidebar.refresh_from_store();
+        sidebar.refresh_from_store(cx);
         sidebar
     }
 
@@ -116,8 +226,109 @@ impl ChatSidebar {
         self.conversation_to_session.get(&conversation_id).copied()
     }
 
+    pub fn storage(&self) -> Option<Arc<SqliteStorage>> {
+        self.storage.clone()
+    }
+
+    pub fn storage_error(&self) -> Option<&str> {
+        self.storage_error.as_deref()
+    }
+
+    /// Re-attempts opening storage after a failed start, e.g. once a locked database is freed.
+    pub fn retry_open_storage(&mut self, cx: &mut Context<Self>) {
+        match Self::open_storage() {
+            Ok(storage) => {
+                self.storage = Some(storage);
+                self.storage_error = None;
+            }
+            Err(error) => {
+                self.storage_error = Some(error.display_message().to_string());
+            }
+        }
+        self.refresh_from_store(cx);
+        cx.notify();
+    }
+
+    /// Writes every conversation as a JSON file into a zip archive at `path`.
+    ///
+    /// Runs off the UI thread and reports percent done through the sidebar progress bar.
+    pub fn export_all_conversations(
+        &mut self,
+        path: &Path,
+        include_deleted: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(storage) = self.storage.clone() else {
+            self.export_status = Some("Storage is unavailable".to_string());
+            cx.notify();
+            return;
+        };
+        if self.export_progress.is_some() {
+            return;
+        }
+
+        self.export_progress = Some(0.0);
+        self.export_status = None;
+        cx.notify();
+
+        let archive_path = path.to_path_buf();
+        let display_path = path.display().to_string();
+        let (progress_tx, mut progress_rx) = futures::channel::mpsc::unbounded();
+        let export = cx.background_executor().spawn(async move {
+            export::write_conversations_archive(
+                &storage,
+                &archive_path,
+                include_deleted,
+                |exported, total| {
+                    let _ = progress_tx.unbounded_send(export_percent(exported, total));
+                },
+            )
+        });
+        cx.spawn(async move |this, cx| {
+            while let Some(percent) = progress_rx.next().await {
+                let updated = this.update(cx, |this, cx| {
+                    this.export_progress = Some(percent);
+                    cx.notify();
+                });
+                if updated.is_err() {
+                    return;
+                }
+            }
+
+            let result = export.await;
+            let _ = this.update(cx, |this, cx| {
+                this.export_progress = None;
+                this.export_status = Some(match result {
+                    Ok(exported) => format!(
+                        "Exported {} to {display_path}",
+                        conversation_count_label(exported)
+                    ),
+                    Err(error) => format!("Failed to export conversations: {error}"),
+                });
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn prompt_export_all_conversations(&mut self, cx: &mut Context<Self>) {
+        let directory = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        let path_prompt =
+            cx.prompt_for_new_path(&directory, Some(export::CONVERSATIONS_EXPORT_FILE_NAME));
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(path))) = path_prompt.await else {
+                return;
+            };
+
+            let _ = this.update(cx, |this, cx| {
+                this.export_all_conversations(&path, false, cx);
+            });
+        })
+        .detach();
+    }
+
     pub fn reload_from_persistence(&mut self, cx: &mut Context<Self>) {
-        self.refresh_from_store();
+        self.refresh_from_store(cx);
         cx.notify();
     }
 
@@ -148,7 +359,7 @@ impl ChatSidebar {
             allocated
         };
 
-        self.refresh_from_store();
+        self.refresh_from_store(cx);
         self.select_conversation(conversation_id, cx);
         Some(conversation_id)
     }
@@ -158,11 +369,14 @@ impl ChatSidebar {
         let session_id = self.session_id_for_conversation(conversation_id)?;
 
         match storage.get_session(session_id) {
-            Ok(Some(session)) => Some(ConversationRecord::new(
-                conversation_id,
-                session.title,
-                session.updated_at_unix_seconds,
-            )),
+            Ok(Some(session)) => Some(
+                ConversationRecord::new(
+                    conversation_id,
+                    session.display_title().to_string(),
+                    session.updated_at_unix_seconds,
+                )
+                .with_pinned(session.pinned),
+            ),
             Ok(None) => None,
             Err(error) => {
                 tracing::error!("failed to load conversation {conversation_id:?}: {error}");
@@ -223,6 +437,61 @@ impl ChatSidebar {
         }
     }
 
+    /// Returns URIs of live image attachments on a persisted message, for multimodal requests.
+    pub fn image_urls_for_message(
+        &self,
+        conversation_id: ConversationId,
+        message_id: StorageMessageId,
+    ) -> Vec<String> {
+        let Some(storage) = self.storage.as_ref() else {
+            return Vec::new();
+        };
+        let Some(session_id) = self.session_id_for_conversation(conversation_id) else {
+            return Vec::new();
+        };
+
+        match storage.list_media(session_id, message_id, false) {
+            Ok(media_refs) => media_refs
+                .into_iter()
+                .filter(|media_ref| media_ref.mime_type.starts_with("image/"))
+                .map(|media_ref| media_ref.uri)
+                .collect(),
+            Err(error) => {
+                tracing::error!(
+                    "failed to list media for message {message_id} in {conversation_id:?}: {error}"
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    /// Persists unsent input for a conversation; empty text clears the stored draft.
+    pub fn save_draft(&self, conversation_id: ConversationId, content: &str) {
+        let Some(storage) = self.storage.as_ref() else {
+            return;
+        };
+        let Some(session_id) = self.session_id_for_conversation(conversation_id) else {
+            return;
+        };
+
+        if let Err(error) = storage.save_draft(session_id, content) {
+            tracing::error!("failed to save draft for {conversation_id:?}: {error}");
+        }
+    }
+
+    pub fn load_draft(&self, conversation_id: ConversationId) -> Option<String> {
+        let storage = self.storage.as_ref()?;
+        let session_id = self.session_id_for_conversation(conversation_id)?;
+
+        match storage.load_draft(session_id) {
+            Ok(draft) => draft,
+            Err(error) => {
+                tracing::error!("failed to load draft for {conversation_id:?}: {error}");
+                None
+            }
+        }
+    }
+
     pub fn update_persisted_message_content(
         &self,
         conversation_id: ConversationId,
@@ -253,14 +522,355 @@ impl ChatSidebar {
         }
     }
 
+    /// Soft-deletes one message on the conversation's active branch, returning whether it was
+    /// removed.
+    pub fn soft_delete_persisted_message(
+        &mut self,
+        conversation_id: ConversationId,
+        message_id: StorageMessageId,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(storage) = self.storage.as_ref() else {
+            tracing::error!("cannot delete message because storage is unavailable");
+            return false;
+        };
+        let Some(session_id) = self.session_id_for_conversation(conversation_id) else {
+            tracing::warn!("missing session mapping for conversation {conversation_id:?}");
+            return false;
+        };
+
+        if let Err(error) = storage.soft_delete_message(session_id, message_id) {
+            tracing::error!(
+                "failed to delete persisted message {message_id} for {conversation_id:?}: {error}"
+            );
+            return false;
+        }
+
+        // Message counts shown in the list are now stale.
+        self.refresh_from_store(cx);
+        cx.notify();
+        true
+    }
+
+    pub fn toggle_conversation_pinned(
+        &mut self,
+        conversation_id: ConversationId,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(storage) = self.storage.as_ref() else {
+            tracing::error!("cannot toggle pin because storage is unavailable");
+            return;
+        };
+        let Some(session_id) = self.session_id_for_conversation(conversation_id) else {
+            tracing::warn!("missing session mapping for conversation {conversation_id:?}");
+            return;
+        };
+        let pinned = self
+            .conversations
+            .iter()
+            .any(|conversation| conversation.id == conversation_id && conversation.pinned);
+
+        if let Err(error) = storage.update_session(
+            session_id,
+            SessionPatch {
+                title: None,
+                pinned: Some(!pinned),
+                metadata: None,
+            },
+        ) {
+            tracing::error!("failed to toggle pin for {conversation_id:?}: {error}");
+            return;
+        }
+
+        self.refresh_from_store(cx);
+        cx.notify();
+    }
+
+    /// Shows or hides the conversations grouped under `tag`.
+    pub fn toggle_tag_collapsed(&mut self, tag: &str, cx: &mut Context<Self>) {
+        if !self.collapsed_tags.remove(tag) {
+            self.collapsed_tags.insert(tag.to_string());
+        }
+        self.rebuild_flat_items();
+        cx.notify();
+    }
+
+    /// Replaces the conversation's tags while keeping any other metadata keys.
+    pub fn set_conversation_tags(
+        &mut self,
+        conversation_id: ConversationId,
+        tags: Vec<String>,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(storage) = self.storage.as_ref() else {
+            tracing::error!("cannot tag conversation because storage is unavailable");
+            return false;
+        };
+        let Some(session_id) = self.session_id_for_conversation(conversation_id) else {
+            tracing::warn!("missing session mapping for conversation {conversation_id:?}");
+            return false;
+        };
+
+        let result = storage.get_session(session_id).and_then(|session| {
+            let mut metadata = session
+                .and_then(|session| session.metadata_json)
+                .and_then(|raw| serde_json::from_str::<serde_json::Value>(&raw).ok())
+                .filter(serde_json::Value::is_object)
+                .unwrap_or_else(|| serde_json::json!({}));
+            metadata["tags"] = serde_json::json!(tags);
+            storage.update_session_metadata(session_id, metadata)
+        });
+        if let Err(error) = result {
+            tracing::error!("failed to tag {conversation_id:?}: {error}");
+            return false;
+        }
+
+        self.refresh_from_store(cx);
+        cx.notify();
+        true
+    }
+
+    /// Hard-deletes the conversation's active history and moves it onto an empty branch.
+    pub fn clear_persisted_messages(
+        &mut self,
+        conversation_id: ConversationId,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some(storage) = self.storage.as_ref() else {
+            tracing::error!("cannot clear history because storage is unavailable");
+            return false;
+        };
+        let Some(session_id) = self.session_id_for_conversation(conversation_id) else {
+            tracing::warn!("missing session mapping for conversation {conversation_id:?}");
+            return false;
+        };
+
+        let cleared = storage.get_session(session_id).and_then(|session| {
+            let Some(session) = session else {
+                return Err(StorageError::NotFound {
+                    stage: "sidebar-clear-history-load-session",
+                    entity: "session",
+                    id: session_id.to_string(),
+                });
+            };
+            storage.delete_all_messages(session_id, session.active_branch_id)
+        });
+        if let Err(error) = cleared {
+            tracing::error!("failed to clear history for {conversation_id:?}: {error}");
+            return false;
+        }
+
+        self.refresh_from_store(cx);
+        cx.notify();
+        true
+    }
+
+    /// Summarizes a conversation from storage, or `None` when it cannot be loaded.
+    pub fn conversation_statistics(
+        &self,
+        conversation_id: ConversationId,
+    ) -> Option<ConversationStats> {
+        let storage = self.storage.as_ref()?;
+        let Some(session_id) = self.session_id_for_conversation(conversation_id) else {
+            tracing::warn!("missing session mapping for conversation {conversation_id:?}");
+            return None;
+        };
+
+        match load_conversation_stats(storage, session_id) {
+            Ok(stats) => Some(stats),
+            Err(error) => {
+                tracing::error!("failed to load statistics for {conversation_id:?}: {error}");
+                None
+            }
+        }
+    }
+
+    /// Searches conversation titles and message content across every conversation, ignoring
+    /// the sidebar filter. Title matches come first; conversations matched only by content
+    /// follow with a snippet of their most recent matching message.
+    pub fn global_search(
+        &mut self,
+        query: &str,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<SearchResult>> {
+        let query = query.trim().to_string();
+        if query.is_empty() {
+            return Task::ready(Vec::new());
+        }
+        let Some(storage) = self.storage.clone() else {
+            return Task::ready(Vec::new());
+        };
+
+        let search = cx.background_executor().spawn(async move {
+            search_sessions_and_messages(&storage, &query, GLOBAL_SEARCH_RESULT_LIMIT)
+        });
+        cx.spawn(async move |this, cx| match search.await {
+            Ok(hits) => this
+                .update(cx, |this, _cx| this.search_results_for_hits(hits))
+                .unwrap_or_default(),
+            Err(error) => {
+                tracing::error!("failed to search conversations: {error}");
+                Vec::new()
+            }
+        })
+    }
+
+    fn search_results_for_hits(&mut self, hits: Vec<StorageSearchHit>) -> Vec<SearchResult> {
+        hits.into_iter()
+            .map(|hit| {
+                let conversation = self.conversation_record_for_session(hit.session);
+                match hit.message {
+                    Some((message_id, message_snippet)) => SearchResult::Message {
+                        conversation,
+                        message_id,
+                        message_snippet,
+                    },
+                    None => SearchResult::Conversation(conversation),
+                }
+            })
+            .collect()
+    }
+
+    fn conversation_record_for_session(&mut self, session: SessionRecord) -> ConversationRecord {
+        // Hits may be hidden by the sidebar filter, so register ids the refresh has not seen yet.
+        let conversation_id = match self.session_to_conversation.get(&session.id) {
+            Some(existing) => *existing,
+            None => {
+                let allocated = self.alloc_conversation_id();
+                self.session_to_conversation.insert(session.id, allocated);
+                self.conversation_to_session.insert(allocated, session.id);
+                allocated
+            }
+        };
+
+        ConversationRecord::new(
+            conversation_id,
+            session.display_title().to_string(),
+            session.updated_at_unix_seconds,
+        )
+        .with_pinned(session.pinned)
+        .with_tags(session.tags)
+    }
+
+    fn confirm_clear_history(
+        &mut self,
+        conversation_id: ConversationId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let confirmation = window.prompt(
+            PromptLevel::Warning,
+            "Clear this conversation's history?",
+            Some("All messages and their attachments will be permanently deleted."),
+            &["Clear History", "Cancel"],
+            cx,
+        );
+        cx.spawn(async move |this, cx| {
+            // Index 0 is "Clear History"; a dismissed prompt or Cancel leaves history intact.
+            let Ok(0) = confirmation.await else {
+                return;
+            };
+
+            let _ = this.update(cx, |_, cx| {
+                cx.emit(ConversationClearRequested { conversation_id });
+            });
+        })
+        .detach();
+    }
+
+    /// Persists a new title, showing it immediately and reverting if storage rejects it.
+    pub fn rename_conversation(
+        &mut self,
+        conversation_id: ConversationId,
+        new_title: String,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let new_title = new_title.trim().to_string();
+        if new_title.is_empty() {
+            return false;
+        }
+        let Some(session_id) = self.session_id_for_conversation(conversation_id) else {
+            tracing::warn!("missing session mapping for conversation {conversation_id:?}");
+            return false;
+        };
+        let Some(storage) = self.storage.clone() else {
+            tracing::error!("cannot rename conversation because storage is unavailable");
+            return false;
+        };
+        let Some(renamed) = persist_conversation_title(
+            &mut self.conversations,
+            &storage,
+            conversation_id,
+            session_id,
+            &new_title,
+        ) else {
+            return false;
+        };
+        self.rebuild_flat_items();
+        cx.notify();
+
+        if let Err(error) = renamed {
+            tracing::error!("failed to rename {conversation_id:?}: {error}");
+            return false;
+        }
+
+        self.refresh_from_store(cx);
+        cx.emit(ConversationRenamed {
+            conversation_id,
+            new_title,
+        });
+        cx.notify();
+        true
+    }
+
+    fn begin_rename(
+        &mut self,
+        conversation_id: ConversationId,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(title) = self
+            .conversations
+            .iter()
+            .find(|conversation| conversation.id == conversation_id)
+            .map(|conversation| conversation.title.clone())
+        else {
+            return;
+        };
+
+        self.renaming_conversation = Some(conversation_id);
+        self.rename_input.update(cx, |input_state, cx| {
+            input_state.set_value(title, window, cx);
+            input_state.focus(window, cx);
+        });
+        cx.notify();
+    }
+
+    fn commit_rename(&mut self, cx: &mut Context<Self>) {
+        let Some(conversation_id) = self.renaming_conversation.take() else {
+            return;
+        };
+        let new_title = self.rename_input.read(cx).value().to_string();
+        self.rename_conversation(conversation_id, new_title, cx);
+        cx.notify();
+    }
+
+    fn cancel_rename(&mut self, cx: &mut Context<Self>) {
+        if self.renaming_conversation.take().is_some() {
+            cx.notify();
+        }
+    }
+
     pub fn select_conversation(&mut self, conversation_id: ConversationId, cx: &mut Context<Self>) {
         self.selected_conversation = Some(conversation_id);
         cx.emit(ConversationSelected { conversation_id });
         cx.notify();
     }
 
-    fn refresh_from_store(&mut self) {
-        let Some(storage) = self.storage.as_ref() else {
+    fn refresh_from_store(&mut self, cx: &mut Context<Self>) {
+        // A newer load supersedes any background retry still waiting on a busy database.
+        self.refresh_retry_task = None;
+        let Some(storage) = self.storage.clone() else {
             tracing::error!("storage unavailable while refreshing sidebar");
             self.conversations.clear();
             self.conversation_to_session.clear();
@@ -270,7 +880,43 @@ impl ChatSidebar {
             return;
         };
 
-        match storage.list_sessions(false) {
+        let search_query = self.search_query.trim().to_string();
+        match load_sidebar_sessions(&storage, &search_query) {
+            Err(error) if error.is_transient() => {
+                tracing::warn!("sidebar storage call hit a transient error, retrying: {error}");
+                // Retries run off the UI thread, since each attempt can wait out the full
+                // sqlite busy timeout.
+                let executor = cx.background_executor().clone();
+                let retry = cx.background_executor().spawn({
+                    let search_query = search_query.clone();
+                    async move {
+                        retry_transient(
+                            || load_sidebar_sessions(&storage, &search_query),
+                            |backoff| executor.timer(backoff),
+                        )
+                        .await
+                    }
+                });
+                self.refresh_retry_task = Some(cx.spawn(async move |this, cx| {
+                    let sessions = retry.await;
+                    let _ = this.update(cx, |this, cx| {
+                        this.refresh_retry_task = None;
+                        this.apply_loaded_sessions(&search_query, sessions);
+                        cx.notify();
+                    });
+                }));
+            }
+            sessions => self.apply_loaded_sessions(&search_query, sessions),
+        }
+    }
+
+    fn apply_loaded_sessions(
+        &mut self,
+        search_query: &str,
+        sessions: StorageResult<Vec<SessionRecord>>,
+    ) {
+        let is_searching = !search_query.is_empty();
+        match sessions {
             Ok(sessions) => {
                 let mut conversations = Vec::with_capacity(sessions.len());
                 let mut conversation_to_session = HashMap::with_capacity(sessions.len());
@@ -286,23 +932,36 @@ impl ChatSidebar {
 
                     conversation_to_session.insert(conversation_id, session.id);
                     session_to_conversation.insert(session.id, conversation_id);
-                    conversations.push(ConversationRecord::new(
-                        conversation_id,
-                        session.title,
-                        session.updated_at_unix_seconds,
-                    ));
+                    conversations.push(
+                        ConversationRecord::new(
+                            conversation_id,
+                            session.display_title().to_string(),
+                            session.updated_at_unix_seconds,
+                        )
+                        .with_pinned(session.pinned)
+                        .with_message_count(session.message_count)
+                        .with_tags(session.tags),
+                    );
                 }
 
                 self.conversations = conversations;
-                self.conversation_to_session = conversation_to_session;
-                self.session_to_conversation = session_to_conversation;
+                if is_searching {
+                    // Keep ids of sessions hidden by the search stable for when the query clears.
+                    self.conversation_to_session.extend(conversation_to_session);
+                    self.session_to_conversation.extend(session_to_conversation);
+                } else {
+                    self.conversation_to_session = conversation_to_session;
+                    self.session_to_conversation = session_to_conversation;
+                }
 
-                if self.selected_conversation.is_some_and(|selected| {
-                    !self
-                        .conversations
-                        .iter()
-                        .any(|conversation| conversation.id == selected)
-                }) {
+                if !is_searching
+                    && self.selected_conversation.is_some_and(|selected| {
+                        !self
+                            .conversations
+                            .iter()
+                            .any(|conversation| conversation.id == selected)
+                    })
+                {
                     self.selected_conversation = None;
                 }
 
@@ -319,102 +978,107 @@ impl ChatSidebar {
         }
     }
 
-    fn alloc_conversation_id(&mut self) -> ConversationId {
-        let next = ConversationId::new(self.next_conversation_id);
-        self.next_conversation_id = self.next_conversation_id.saturating_add(1);
-        next
+    fn keyboard_conversation_ids(&self) -> Vec<ConversationId> {
+        self.flat_items
+            .iter()
+            .filter_map(|item| match item {
+                SidebarListItem::Conversation(conversation) => Some(conversation.id),
+                SidebarListItem::GroupHeader(_) | SidebarListItem::TagHeader(_) => None,
+            })
+            .collect()
     }
 
-    fn open_storage() -> Option<Arc<SqliteStorage>> {
-        // Sidebar constructor is sync, so storage bootstrap runs in a local current-thread runtime.
-        let runtime = match tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-        {
-            Ok(runtime) => runtime,
-            Err(error) => {
-                tracing::error!("failed to initialize runtime for sqlite storage: {error}");
-                return None;
-            }
-        };
+    fn flat_index_for_keyboard_index(&self, keyboard_index: usize) -> Option<usize> {
+        self.flat_items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| matches!(item, SidebarListItem::Conversation(_)))
+            .nth(keyboard_index)
+            .map(|(flat_index, _)| flat_index)
+    }
 
-        let storage = match runtime.block_on(SqliteStorage::open(DEFAULT_STORAGE_DB_RELATIVE_PATH))
-        {
-            Ok(storage) => storage,
-            Err(error) => {
-                tracing::error!("failed to open sqlite storage: {error}");
-                return None;
+    fn handle_key_down(
+        &mut self,
+        event: &KeyDownEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.renaming_conversation.is_some() {
+            // The inline rename input owns Enter/Escape while it is open.
+            if event.keystroke.key == "escape" {
+                self.cancel_rename(cx);
+                cx.stop_propagation();
             }
-        };
+            return;
+        }
 
-        match storage.import_legacy_conversations_from_default_path() {
-            Ok(report) if report.source_missing => {
-                tracing::debug!("legacy conversation TSV not found; skipping import");
+        let conversation_ids = self.keyboard_conversation_ids();
+
+        match event.keystroke.key.as_str() {
+            "down" | "up" => {
+                let step = if event.keystroke.key == "down" {
+                    KeyboardStep::Down
+                } else {
+                    KeyboardStep::Up
+                };
+                self.keyboard_focused_index =
+                    step_keyboard_index(self.keyboard_focused_index, step, conversation_ids.len());
+                if let Some(flat_index) = self
+                    .keyboard_focused_index
+                    .and_then(|keyboard_index| self.flat_index_for_keyboard_index(keyboard_index))
+                {
+                    self.scroll_handle
+                        .scroll_to_item(flat_index, ScrollStrategy::Center);
+                }
             }
-            Ok(report) if report.imported_sessions > 0 || report.skipped_rows > 0 => {
-                tracing::info!(
-                    "legacy import complete: imported_sessions={}, skipped_rows={}",
-                    report.imported_sessions,
-                    report.skipped_rows
-                );
+            "enter" => {
+                let Some(conversation_id) = self
+                    .keyboard_focused_index
+                    .and_then(|keyboard_index| conversation_ids.get(keyboard_index).copied())
+                else {
+                    return;
+                };
+                self.select_conversation(conversation_id, cx);
             }
-            Ok(_) => {}
-            Err(error) => {
-                tracing::error!("failed to import legacy conversations into sqlite: {error}");
+            "escape" => {
+                if self.keyboard_focused_index.is_none() {
+                    return;
+                }
+                self.keyboard_focused_index = None;
             }
+            _ => return,
         }
 
-        Some(Arc::new(storage))
+        cx.stop_propagation();
+        cx.notify();
     }
 
-    fn rebuild_flat_items(&mut self) {
-        let normalized_query = self.search_query.trim().to_ascii_lowercase();
-        let now_unix_seconds = unix_now_seconds();
-
-        let mut today_items = Vec::new();
-        let mut yesterday_items = Vec::new();
-        let mut older_items = Vec::new();
-
-        // Keep ordering deterministic by preserving the repository order within each group.
-        for conversation in self.conversations.iter().cloned() {
-            if !matches_query(&conversation, &normalized_query) {
-                continue;
-            }
-
-            match classify_group(conversation.updated_at_unix_seconds, now_unix_seconds) {
-                ConversationAgeGroup::Today => today_items.push(conversation),
-                ConversationAgeGroup::Yesterday => yesterday_items.push(conversation),
-                ConversationAgeGroup::Older => older_items.push(conversation),
-            }
-        }
+    fn alloc_conversation_id(&mut self) -> ConversationId {
+        let next = ConversationId::new(self.next_conversation_id);
+        self.next_conversation_id = self.next_conversation_id.saturating_add(1);
+        next
+    }
 
-        let mut flat_items = Vec::new();
-        let mut item_sizes = Vec::new();
+    fn open_storage() -> StorageResult<Arc<SqliteStorage>> {
+        open_storage_at(DEFAULT_STORAGE_DB_RELATIVE_PATH)
+    }
 
-        append_group(
-            &mut flat_items,
-            &mut item_sizes,
-            "Today",
-            today_items,
-            px(0.),
-        );
-        append_group(
-            &mut flat_items,
-            &mut item_sizes,
-            "Yesterday",
-            yesterday_items,
-            px(0.),
-        );
-        append_group(
-            &mut flat_items,
-            &mut item_sizes,
-            "Older",
-            older_items,
-            px(0.),
+    fn rebuild_flat_items(&mut self) {
+        let (flat_items, item_sizes) = build_flat_items(
+            &self.conversations,
+            &self.search_query,
+            unix_now_seconds(),
+            &self.collapsed_tags,
         );
-
         self.flat_items = flat_items;
         self.item_sizes = Rc::new(item_sizes);
+
+        // Filtering can shrink the list under the keyboard cursor, so keep it in range.
+        let conversation_count = self.keyboard_conversation_ids().len();
+        self.keyboard_focused_index = self
+            .keyboard_focused_index
+            .filter(|_| conversation_count > 0)
+            .map(|index| index.min(conversation_count.saturating_sub(1)));
     }
 
     fn render_toolbar(&mut self, cx: &mut Context<Self>) -> impl IntoElement {
@@ -426,6 +1090,16 @@ impl ChatSidebar {
             .pt(px(8.))
             .pb_2()
             .child(Input::new(&self.search_input).w_full().small())
+            .child(
+                Button::new("export-conversations")
+                    .small()
+                    .ghost()
+                    .icon(IconName::ArrowDown)
+                    .tooltip("Export all conversations")
+                    .on_click(cx.listener(|this, _, _window, cx| {
+                        this.prompt_export_all_conversations(cx);
+                    })),
+            )
             .child(
                 Button::new("new")
                     .small()
@@ -438,6 +1112,91 @@ impl ChatSidebar {
             )
     }
 
+    fn render_export_banner(&self, cx: &mut Context<Self>) -> Option<AnyElement> {
+        let theme = cx.theme();
+
+        if let Some(percent) = self.export_progress {
+            return Some(
+                v_flex()
+                    .mx_2()
+                    .mb_2()
+                    .px_3()
+                    .py_2()
+                    .gap_1()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.muted_foreground)
+                            .child(format!("Exporting conversations… {percent:.0}%")),
+                    )
+                    .child(Progress::new().value(percent))
+                    .into_any_element(),
+            );
+        }
+
+        let status = self.export_status.clone()?;
+        Some(
+            h_flex()
+                .mx_2()
+                .mb_2()
+                .px_3()
+                .py_2()
+                .gap_2()
+                .items_center()
+                .child(
+                    div()
+                        .flex_1()
+                        .min_w_0()
+                        .text_xs()
+                        .text_color(theme.muted_foreground)
+                        .child(status),
+                )
+                .child(
+                    Button::new("export-status-dismiss")
+                        .xsmall()
+                        .ghost()
+                        .icon(IconName::Close)
+                        .on_click(cx.listener(|this, _, _window, cx| {
+                            this.export_status = None;
+                            cx.notify();
+                        })),
+                )
+                .into_any_element(),
+        )
+    }
+
+    fn render_storage_error_banner(&self, error: String, cx: &mut Context<Self>) -> AnyElement {
+        let theme = cx.theme();
+
+        h_flex()
+            .mx_2()
+            .mb_2()
+            .px_3()
+            .py_2()
+            .gap_2()
+            .items_center()
+            .rounded_md()
+            .bg(theme.danger.opacity(0.12))
+            .child(
+                div()
+                    .flex_1()
+                    .min_w_0()
+                    .text_sm()
+                    .text_color(theme.danger)
+                    .child(error),
+            )
+            .child(
+                Button::new("storage-retry")
+                    .small()
+                    .ghost()
+                    .child("Retry")
+                    .on_click(cx.listener(|this, _, _window, cx| {
+                        this.retry_open_storage(cx);
+                    })),
+            )
+            .into_any_element()
+    }
+
     fn render_empty_state(&mut self, cx: &mut Context<Self>) -> AnyElement {
         let theme = cx.theme();
         let message = if self.conversations.is_empty() {
@@ -465,12 +1224,25 @@ impl ChatSidebar {
         }
 
         let selected = self.selected_conversation;
+        let keyboard_focused_flat_index = self
+            .keyboard_focused_index
+            .and_then(|keyboard_index| self.flat_index_for_keyboard_index(keyboard_index));
         let item_sizes = self.item_sizes.clone();
         let items = self.flat_items.clone();
+        let sidebar = cx.entity().downgrade();
+        let renaming_conversation = self.renaming_conversation;
+        let rename_input = self.rename_input.clone();
+        let collapsed_tags = self.collapsed_tags.clone();
 
         v_flex()
             .flex_1()
             .min_h_0()
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|this, _event: &MouseDownEvent, window, _cx| {
+                    window.focus(&this.focus_handle);
+                }),
+            )
             .child(
                 v_virtual_list(
                     cx.entity().clone(),
@@ -493,10 +1265,53 @@ impl ChatSidebar {
                                             .text_color(theme.foreground.opacity(0.5)),
                                     )
                                     .into_any_element(),
+                                SidebarListItem::TagHeader(tag) => {
+                                    let collapsed = collapsed_tags.contains(tag);
+                                    let toggled_tag = tag.clone();
+
+                                    h_flex()
+                                        .id(("tag-header", index))
+                                        .w_full()
+                                        .h(px(GROUP_HEADER_HEIGHT))
+                                        .px_3()
+                                        .gap_1()
+                                        .items_center()
+                                        .cursor_pointer()
+                                        .on_click(cx.listener(
+                                            move |this, _event: &ClickEvent, _window, cx| {
+                                                this.toggle_tag_collapsed(&toggled_tag, cx);
+                                            },
+                                        ))
+                                        .child(
+                                            Icon::new(if collapsed {
+                                                IconName::ChevronRight
+                                            } else {
+                                                IconName::ChevronDown
+                                            })
+                                            .xsmall()
+                                            .text_color(theme.foreground.opacity(0.5)),
+                                        )
+                                        .child(
+                                            Label::new(tag.clone())
+                                                .text_xs()
+                                                .text_color(theme.foreground.opacity(0.5)),
+                                        )
+                                        .into_any_element()
+                                }
                                 SidebarListItem::Conversation(conversation) => {
                                     let conversation_id = conversation.id;
                                     let title = conversation.title.clone();
+                                    let message_count_label =
+                                        conversation.message_count.map(message_count_label);
                                     let is_selected = selected == Some(conversation_id);
+                                    let is_keyboard_focused =
+                                        keyboard_focused_flat_index == Some(index);
+                                    let pin_label = if conversation.pinned {
+                                        "Unpin Conversation"
+                                    } else {
+                                        "Pin Conversation"
+                                    };
+                                    let sidebar = sidebar.clone();
 
                                     div()
                                         .w_full()
@@ -509,6 +1324,12 @@ impl ChatSidebar {
                                                 .px_3()
                                                 .py_2()
                                                 .rounded_md()
+                                                .border_1()
+                                                .border_color(if is_keyboard_focused {
+                                                    theme.ring
+                                                } else {
+                                                    transparent_black()
+                                                })
                                                 .selected(is_selected)
                                                 .on_click(cx.listener(
                                                     move |this, _event: &ClickEvent, _window, cx| {
@@ -517,12 +1338,104 @@ impl ChatSidebar {
                                                 ))
                                                 .child(
                                                     h_flex().w_full().items_center().child(
-                                                        div().flex_1().min_w_0().truncate().child(
-                                                            Label::new(title.clone()).text_sm(),
-                                                        ),
+                                                        if renaming_conversation
+                                                            == Some(conversation_id)
+                                                        {
+                                                            div()
+                                                                .flex_1()
+                                                                .min_w_0()
+                                                                .child(
+                                                                    Input::new(&rename_input)
+                                                                        .small(),
+                                                                )
+                                                        } else {
+                                                            v_flex()
+                                                                .flex_1()
+                                                                .min_w_0()
+                                                                .child(
+                                                                    div().truncate().child(
+                                                                        Label::new(title.clone())
+                                                                            .text_sm(),
+                                                                    ),
+                                                                )
+                                                                .children(
+                                                                    message_count_label.clone().map(
+                                                                        |label| {
+                                                                            Label::new(label)
+                                                                                .text_xs()
+                                                                                .text_color(
+                                                                                    theme
+                                                                                        .foreground
+                                                                                        .opacity(0.5),
+                                                                                )
+                                                                        },
+                                                                    ),
+                                                                )
+                                                        },
                                                     ),
                                                 ),
                                         )
+                                        .context_menu(move |menu, _window, _cx| {
+                                            let pin_sidebar = sidebar.clone();
+                                            let rename_sidebar = sidebar.clone();
+                                            let clear_sidebar = sidebar.clone();
+                                            let statistics_sidebar = sidebar.clone();
+                                            menu.item(PopupMenuItem::new(pin_label).on_click(
+                                                move |_event, _window, cx| {
+                                                    let _ = pin_sidebar.update(cx, |this, cx| {
+                                                        this.toggle_conversation_pinned(
+                                                            conversation_id,
+                                                            cx,
+                                                        );
+                                                    });
+                                                },
+                                            ))
+                                            .item(
+                                                PopupMenuItem::new("Rename Conversation").on_click(
+                                                    move |_event, window, cx| {
+                                                        let _ =
+                                                            rename_sidebar.update(cx, |this, cx| {
+                                                                this.begin_rename(
+                                                                    conversation_id,
+                                                                    window,
+                                                                    cx,
+                                                                );
+                                                            });
+                                                    },
+                                                ),
+                                            )
+                                            .item(
+                                                PopupMenuItem::new("Statistics").on_click(
+                                                    move |_event, _window, cx| {
+                                                        let _ = statistics_sidebar.update(
+                                                            cx,
+                                                            |_this, cx| {
+                                                                cx.emit(
+                                                                    ConversationStatisticsRequested {
+                                                                        conversation_id,
+                                                                    },
+                                                                );
+                                                            },
+                                                        );
+                                                    },
+                                                ),
+                                            )
+                                            .separator()
+                                            .item(
+                                                PopupMenuItem::new("Clear History").on_click(
+                                                    move |_event, window, cx| {
+                                                        let _ =
+                                                            clear_sidebar.update(cx, |this, cx| {
+                                                                this.confirm_clear_history(
+                                                                    conversation_id,
+                                                                    window,
+                                                                    cx,
+                                                                );
+                                                            });
+                                                    },
+                                                ),
+                                            )
+                                        })
                                         .into_any_element()
                                 }
                             })
@@ -537,20 +1450,163 @@ impl ChatSidebar {
     }
 }
 
+impl Focusable for ChatSidebar {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
 impl Render for ChatSidebar {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let storage_error_banner = self
+            .storage_error
+            .clone()
+            .map(|error| self.render_storage_error_banner(error, cx));
+        let export_banner = self.render_export_banner(cx);
         let theme = cx.theme();
 
         v_flex()
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(Self::handle_key_down))
             .size_full()
             .min_w_0()
             .overflow_hidden()
             .bg(theme.background)
             .child(self.render_toolbar(cx))
+            .children(storage_error_banner)
+            .children(export_banner)
             .child(self.render_history_list(cx))
     }
 }
 
+fn build_flat_items(
+    conversations: &[ConversationRecord],
+    search_query: &str,
+    now_unix_seconds: u64,
+    collapsed_tags: &HashSet<String>,
+) -> (Vec<SidebarListItem>, Vec<Size<Pixels>>) {
+    let normalized_query = search_query.trim().to_ascii_lowercase();
+
+    let mut pinned_items = Vec::new();
+    let mut unpinned_items = Vec::new();
+
+    // Keep ordering deterministic by preserving the repository order within each group.
+    for conversation in conversations.iter().cloned() {
+        if !matches_query(&conversation, &normalized_query) {
+            continue;
+        }
+
+        if conversation.pinned {
+            pinned_items.push(conversation);
+        } else {
+            unpinned_items.push(conversation);
+        }
+    }
+
+    let mut flat_items = Vec::new();
+    let mut item_sizes = Vec::new();
+
+    append_group(
+        &mut flat_items,
+        &mut item_sizes,
+        "Pinned",
+        pinned_items,
+        px(0.),
+    );
+
+    // Date buckets stay the default; tag sections only take over once something is tagged.
+    if unpinned_items
+        .iter()
+        .any(|conversation| !conversation.tags.is_empty())
+    {
+        append_tag_groups(
+            &mut flat_items,
+            &mut item_sizes,
+            unpinned_items,
+            collapsed_tags,
+            px(0.),
+        );
+    } else {
+        append_age_groups(
+            &mut flat_items,
+            &mut item_sizes,
+            unpinned_items,
+            now_unix_seconds,
+            px(0.),
+        );
+    }
+
+    (flat_items, item_sizes)
+}
+
+fn append_age_groups(
+    flat_items: &mut Vec<SidebarListItem>,
+    item_sizes: &mut Vec<Size<Pixels>>,
+    conversations: Vec<ConversationRecord>,
+    now_unix_seconds: u64,
+    item_width: Pixels,
+) {
+    let mut today_items = Vec::new();
+    let mut yesterday_items = Vec::new();
+    let mut older_items = Vec::new();
+
+    for conversation in conversations {
+        match classify_group(conversation.updated_at_unix_seconds, now_unix_seconds) {
+            ConversationAgeGroup::Today => today_items.push(conversation),
+            ConversationAgeGroup::Yesterday => yesterday_items.push(conversation),
+            ConversationAgeGroup::Older => older_items.push(conversation),
+        }
+    }
+
+    append_group(flat_items, item_sizes, "Today", today_items, item_width);
+    append_group(
+        flat_items,
+        item_sizes,
+        "Yesterday",
+        yesterday_items,
+        item_width,
+    );
+    append_group(flat_items, item_sizes, "Older", older_items, item_width);
+}
+
+fn append_tag_groups(
+    flat_items: &mut Vec<SidebarListItem>,
+    item_sizes: &mut Vec<Size<Pixels>>,
+    conversations: Vec<ConversationRecord>,
+    collapsed_tags: &HashSet<String>,
+    item_width: Pixels,
+) {
+    let mut tagged_groups: BTreeMap<String, Vec<ConversationRecord>> = BTreeMap::new();
+    let mut uncategorized = Vec::new();
+
+    for conversation in conversations {
+        match conversation.tags.first() {
+            Some(tag) => tagged_groups
+                .entry(tag.clone())
+                .or_default()
+                .push(conversation),
+            None => uncategorized.push(conversation),
+        }
+    }
+
+    let groups = tagged_groups
+        .into_iter()
+        .chain((!uncategorized.is_empty()).then(|| (UNCATEGORIZED_TAG.to_string(), uncategorized)));
+    for (tag, conversations) in groups {
+        let collapsed = collapsed_tags.contains(&tag);
+        flat_items.push(SidebarListItem::TagHeader(tag));
+        item_sizes.push(size(item_width, px(GROUP_HEADER_HEIGHT)));
+        if collapsed {
+            continue;
+        }
+
+        for conversation in conversations {
+            flat_items.push(SidebarListItem::Conversation(conversation));
+            item_sizes.push(size(item_width, px(CONVERSATION_ROW_HEIGHT)));
+        }
+    }
+}
+
 fn append_group(
     flat_items: &mut Vec<SidebarListItem>,
     item_sizes: &mut Vec<Size<Pixels>>,
@@ -571,6 +1627,67 @@ fn append_group(
     }
 }
 
+/// Swaps in a new title and returns the previous one so callers can revert it.
+fn replace_conversation_title(
+    conversations: &mut [ConversationRecord],
+    conversation_id: ConversationId,
+    title: String,
+) -> Option<String> {
+    conversations
+        .iter_mut()
+        .find(|conversation| conversation.id == conversation_id)
+        .map(|conversation| std::mem::replace(&mut conversation.title, title))
+}
+
+/// Shows `new_title` on the conversation and persists it, restoring the previous title when
+/// storage rejects the rename; `None` means no listed conversation matched.
+fn persist_conversation_title(
+    conversations: &mut [ConversationRecord],
+    storage: &SqliteStorage,
+    conversation_id: ConversationId,
+    session_id: SessionId,
+    new_title: &str,
+) -> Option<StorageResult<()>> {
+    let previous_title =
+        replace_conversation_title(conversations, conversation_id, new_title.to_string())?;
+    let renamed = storage.update_session(
+        session_id,
+        SessionPatch {
+            title: Some(new_title.to_string()),
+            pinned: None,
+            metadata: None,
+        },
+    );
+    if renamed.is_err() {
+        replace_conversation_title(conversations, conversation_id, previous_title);
+    }
+    Some(renamed.map(|_| ()))
+}
+
+fn message_count_label(message_count: usize) -> String {
+    if message_count == 1 {
+        "1 message".to_string()
+    } else {
+        format!("{message_count} messages")
+    }
+}
+
+fn conversation_count_label(conversation_count: usize) -> String {
+    if conversation_count == 1 {
+        "1 conversation".to_string()
+    } else {
+        format!("{conversation_count} conversations")
+    }
+}
+
+fn export_percent(exported: usize, total: usize) -> f32 {
+    if total == 0 {
+        return 100.0;
+    }
+
+    exported as f32 * 100.0 / total as f32
+}
+
 fn matches_query(conversation: &ConversationRecord, query: &str) -> bool {
     if query.is_empty() {
         return true;
@@ -592,11 +1709,207 @@ fn classify_group(updated_at_unix_seconds: u64, now_unix_seconds: u64) -> Conver
     }
 }
 
-fn unix_now_seconds() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or(Duration::from_secs(0))
-        .as_secs()
+fn step_keyboard_index(
+    current: Option<usize>,
+    step: KeyboardStep,
+    conversation_count: usize,
+) -> Option<usize> {
+    if conversation_count == 0 {
+        return None;
+    }
+
+    let last_index = conversation_count - 1;
+    let next_index = match (current, step) {
+        (None, _) => 0,
+        (Some(index), KeyboardStep::Down) => index.saturating_add(1).min(last_index),
+        (Some(index), KeyboardStep::Up) => index.saturating_sub(1).min(last_index),
+    };
+    Some(next_index)
+}
+
+/// Loads the sessions the sidebar lists, filtered by title when `search_query` is non-empty.
+fn load_sidebar_sessions(
+    storage: &SqliteStorage,
+    search_query: &str,
+) -> StorageResult<Vec<SessionRecord>> {
+    // Title search runs in sqlite so large histories are not loaded just to be filtered out.
+    if search_query.is_empty() {
+        storage.list_sessions_with_counts(false)
+    } else {
+        storage.search_sessions(search_query, SIDEBAR_SEARCH_RESULT_LIMIT)
+    }
+}
+
+/// Re-runs `load` after a failed first attempt, waiting `TRANSIENT_RETRY_BACKOFF` before each
+/// of up to `TRANSIENT_RETRY_ATTEMPTS` tries and stopping once one is not transient.
+async fn retry_transient<T, Wait: Future<Output = ()>>(
+    mut load: impl FnMut() -> StorageResult<T>,
+    mut wait: impl FnMut(Duration) -> Wait,
+) -> StorageResult<T> {
+    let mut attempt = 1;
+    loop {
+        wait(TRANSIENT_RETRY_BACKOFF).await;
+        match load() {
+            Err(error) if error.is_transient() && attempt < TRANSIENT_RETRY_ATTEMPTS => {
+                tracing::warn!("sidebar storage retry {attempt} hit a transient error: {error}");
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn search_sessions_and_messages(
+    storage: &SqliteStorage,
+    query: &str,
+    limit: usize,
+) -> StorageResult<Vec<StorageSearchHit>> {
+    // Each store call blocks on its own sqlite worker, so the two searches overlap only when
+    // issued from separate threads.
+    let (sessions, messages) = std::thread::scope(|scope| {
+        let sessions = scope.spawn(|| storage.search_sessions(query, limit));
+        let messages = storage.search_messages(query, limit);
+        (sessions.join(), messages)
+    });
+    let Ok(sessions) = sessions else {
+        return Err(StorageError::InvariantViolation {
+            stage: "sidebar-global-search-join",
+            details: "session title search thread panicked".to_string(),
+        });
+    };
+    let sessions = sessions?;
+    let messages = messages?;
+
+    let mut seen_sessions = sessions
+        .iter()
+        .map(|session| session.id)
+        .collect::<HashSet<_>>();
+    let mut hits = sessions
+        .into_iter()
+        .map(|session| StorageSearchHit {
+            session,
+            message: None,
+        })
+        .collect::<Vec<_>>();
+
+    // Messages arrive newest first, so the first hit per session is its most recent match.
+    for MessageSearchHit { session, message } in messages {
+        if !seen_sessions.insert(session.id) {
+            continue;
+        }
+        hits.push(StorageSearchHit {
+            session,
+            message: Some((message.id, message_snippet(&message.content, query))),
+        });
+    }
+
+    Ok(hits)
+}
+
+/// Returns a single-line excerpt of `content` around the first ASCII case-insensitive match
+/// of `query`, marking trimmed ends with an ellipsis.
+fn message_snippet(content: &str, query: &str) -> String {
+    // ASCII lowercasing keeps byte offsets aligned with `content`, matching sqlite LIKE.
+    let match_start = content
+        .to_ascii_lowercase()
+        .find(&query.to_ascii_lowercase())
+        .unwrap_or(0);
+    let start = content[..match_start]
+        .char_indices()
+        .rev()
+        .nth(SNIPPET_LEADING_CHARS.saturating_sub(1))
+        .map_or(0, |(index, _)| index);
+    let end = content[start..]
+        .char_indices()
+        .nth(SNIPPET_MAX_CHARS)
+        .map_or(content.len(), |(index, _)| start + index);
+
+    let excerpt = content[start..end]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < content.len() { "…" } else { "" };
+    format!("{prefix}{excerpt}{suffix}")
+}
+
+fn load_conversation_stats(
+    storage: &SqliteStorage,
+    session_id: SessionId,
+) -> StorageResult<ConversationStats> {
+    let session = storage
+        .get_session(session_id)?
+        .ok_or_else(|| StorageError::NotFound {
+            stage: "sidebar-statistics-load-session",
+            entity: "session",
+            id: session_id.to_string(),
+        })?;
+    let messages = storage.list_messages(session_id)?;
+    let branch_count = storage
+        .list_branches(session_id)?
+        .iter()
+        .filter(|branch| branch.deleted_at_unix_seconds.is_none())
+        .count();
+
+    let count_role = |role: StorageMessageRole| {
+        messages
+            .iter()
+            .filter(|message| message.role == role)
+            .count()
+    };
+    Ok(ConversationStats {
+        message_count: messages.len(),
+        user_message_count: count_role(StorageMessageRole::User),
+        assistant_message_count: count_role(StorageMessageRole::Assistant),
+        total_word_count: messages.iter().map(StorageMessageRecord::word_count).sum(),
+        created_at: session.created_at_unix_seconds,
+        last_updated_at: session.updated_at_unix_seconds,
+        branch_count,
+    })
+}
+
+fn open_storage_at(database_path: &str) -> StorageResult<Arc<SqliteStorage>> {
+    // Sidebar constructor is sync, so storage bootstrap runs in a local current-thread runtime.
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|error| StorageError::Unavailable {
+            stage: "sidebar-open-storage-runtime",
+            reason: format!("failed to initialize runtime for sqlite storage: {error}"),
+        })?;
+
+    let options = SqliteStorageOptions {
+        max_connections: STORAGE_MAX_CONNECTIONS,
+        ..SqliteStorageOptions::default()
+    };
+    let storage = runtime
+        .block_on(SqliteStorage::open_with_options(database_path, options))
+        .map_err(|error| {
+            tracing::error!("failed to open sqlite storage: {error}");
+            StorageError::Unavailable {
+                stage: "sidebar-open-storage",
+                reason: error.to_string(),
+            }
+        })?;
+
+    match storage.import_legacy_conversations_from_default_path() {
+        Ok(report) if report.source_missing => {
+            tracing::debug!("legacy conversation TSV not found; skipping import");
+        }
+        Ok(report) if report.imported_sessions > 0 || report.skipped_rows > 0 => {
+            tracing::info!(
+                "legacy import complete: imported_sessions={}, skipped_rows={}",
+                report.imported_sessions,
+                report.skipped_rows
+            );
+        }
+        Ok(_) => {}
+        Err(error) => {
+            tracing::error!("failed to import legacy conversations into sqlite: {error}");
+        }
+    }
+
+    Ok(Arc::new(storage))
 }
 
 fn chat_role_to_storage(role: Role) -> StorageMessageRole {
@@ -606,3 +1919,389 @@ fn chat_role_to_storage(role: Role) -> StorageMessageRole {
         Role::Assistant => StorageMessageRole::Assistant,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press_down_three_times(conversation_count: usize) -> Option<usize> {
+        // Sidebar focus seeds the cursor at the first conversation before arrow keys apply.
+        let focused = step_keyboard_index(None, KeyboardStep::Down, conversation_count);
+        (0..3).fold(focused, |index, _| {
+            step_keyboard_index(index, KeyboardStep::Down, conversation_count)
+        })
+    }
+
+    #[::core::prelude::v1::test]
+    fn arrow_down_moves_keyboard_focus_and_clamps_to_last_item() {
+        assert_eq!(press_down_three_times(10), Some(3));
+        assert_eq!(press_down_three_times(2), Some(1));
+        assert_eq!(press_down_three_times(0), None);
+    }
+
+    #[::core::prelude::v1::test]
+    fn arrow_up_stops_at_first_item() {
+        assert_eq!(step_keyboard_index(Some(1), KeyboardStep::Up, 5), Some(0));
+        assert_eq!(step_keyboard_index(Some(0), KeyboardStep::Up, 5), Some(0));
+    }
+
+    #[::core::prelude::v1::test]
+    fn optimistic_rename_can_be_reverted() {
+        let conversation_id = ConversationId::new(7);
+        let mut conversations = vec![ConversationRecord::new(conversation_id, "Draft", 0)];
+
+        let previous =
+            replace_conversation_title(&mut conversations, conversation_id, "Renamed".to_string());
+        assert_eq!(previous.as_deref(), Some("Draft"));
+        assert_eq!(conversations[0].title, "Renamed");
+
+        if let Some(previous) = previous {
+            replace_conversation_title(&mut conversations, conversation_id, previous);
+        }
+        assert_eq!(conversations[0].title, "Draft");
+        assert_eq!(
+            replace_conversation_title(
+                &mut conversations,
+                ConversationId::new(8),
+                "Missing".to_string()
+            ),
+            None
+        );
+    }
+
+    #[::core::prelude::v1::test]
+    fn rename_is_persisted_and_reverted_when_storage_rejects_it() {
+        let database_directory =
+            std::env::temp_dir().join(format!("zova-sidebar-rename-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&database_directory);
+        let database_path = database_directory.join("storage.db");
+        let storage = open_storage_at(&database_path.to_string_lossy());
+        let Ok(storage) = storage else {
+            panic!("failed to open rename storage: {storage:?}");
+        };
+        let session = storage.create_session(NewSession {
+            title: "Draft".to_string(),
+        });
+        let Ok(session) = session else {
+            panic!("failed to create session: {session:?}");
+        };
+        let conversation_id = ConversationId::new(7);
+        let mut conversations = vec![ConversationRecord::new(conversation_id, "Draft", 0)];
+
+        let renamed = persist_conversation_title(
+            &mut conversations,
+            &storage,
+            conversation_id,
+            session.id,
+            "Renamed",
+        );
+        assert!(
+            matches!(renamed, Some(Ok(()))),
+            "rename failed: {renamed:?}"
+        );
+        let sessions = storage.list_sessions(false);
+        let Ok(sessions) = sessions else {
+            panic!("failed to reload sessions: {sessions:?}");
+        };
+        let titles = sessions
+            .iter()
+            .map(|session| session.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(titles, ["Renamed"]);
+        assert_eq!(conversations[0].title, "Renamed");
+
+        // A session storage does not know rejects the rename, so the row shows the old title.
+        let rejected = persist_conversation_title(
+            &mut conversations,
+            &storage,
+            conversation_id,
+            SessionId::new_v7(),
+            "Lost",
+        );
+        let _ = std::fs::remove_dir_all(&database_directory);
+        assert!(
+            matches!(rejected, Some(Err(_))),
+            "rename should fail: {rejected:?}"
+        );
+        assert_eq!(conversations[0].title, "Renamed");
+    }
+
+    #[::core::prelude::v1::test]
+    fn unopenable_storage_path_reports_unavailable() {
+        let blocking_file = std::env::temp_dir().join(format!(
+            "zova-sidebar-storage-blocker-{}",
+            std::process::id()
+        ));
+        assert!(std::fs::write(&blocking_file, b"not a directory").is_ok());
+        let database_path = blocking_file.join("storage.db");
+
+        let result = open_storage_at(&database_path.to_string_lossy());
+        assert!(matches!(result, Err(StorageError::Unavailable { .. })));
+        let _ = std::fs::remove_file(&blocking_file);
+    }
+
+    #[::core::prelude::v1::test]
+    fn busy_session_loads_retry_with_backoff() {
+        let database_directory =
+            std::env::temp_dir().join(format!("zova-sidebar-retry-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&database_directory);
+        let database_path = database_directory.join("storage.db");
+        let storage = open_storage_at(&database_path.to_string_lossy());
+        let Ok(storage) = storage else {
+            panic!("failed to open retry storage: {storage:?}");
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build();
+        let Ok(runtime) = runtime else {
+            panic!("failed to build runtime: {runtime:?}");
+        };
+        // Pool connections return themselves through a spawned task when dropped.
+        let _runtime_context = runtime.enter();
+        // One connection holds the write lock; a second with a zero busy timeout reports
+        // SQLITE_BUSY immediately whenever it tries to take the lock too.
+        let connections = runtime.block_on(async {
+            let mut holder = storage.pool().acquire().await?;
+            sqlx::query("BEGIN IMMEDIATE").execute(&mut *holder).await?;
+            let options = sqlx::sqlite::SqliteConnectOptions::new()
+                .filename(&database_path)
+                .busy_timeout(Duration::ZERO);
+            let contender = sqlx::ConnectOptions::connect(&options).await?;
+            Ok::<_, sqlx::Error>((holder, contender))
+        });
+        let Ok((holder, mut contender)) = connections else {
+            panic!("failed to lock retry storage: {connections:?}");
+        };
+        let mut contend = |loads: &mut usize| {
+            *loads += 1;
+            runtime
+                .block_on(sqlx::query("BEGIN IMMEDIATE").execute(&mut contender))
+                .map(|_| ())
+                .map_err(|error| StorageError::from_sqlx("sidebar-retry-test-contend", error))
+        };
+
+        let mut loads = 0;
+        let mut waits = Vec::new();
+        let exhausted = futures::executor::block_on(retry_transient(
+            || contend(&mut loads),
+            |backoff| {
+                waits.push(backoff);
+                std::future::ready(())
+            },
+        ));
+        assert!(
+            matches!(&exhausted, Err(error) if error.is_transient()),
+            "expected the lock to outlast every retry: {exhausted:?}"
+        );
+        assert_eq!(loads, TRANSIENT_RETRY_ATTEMPTS);
+        assert_eq!(
+            waits,
+            vec![TRANSIENT_RETRY_BACKOFF; TRANSIENT_RETRY_ATTEMPTS]
+        );
+
+        // Releasing the lock during the first backoff lets the next attempt through.
+        let mut holder = Some(holder);
+        let mut loads = 0;
+        let recovered = futures::executor::block_on(retry_transient(
+            || contend(&mut loads),
+            |_| {
+                if let Some(mut holder) = holder.take() {
+                    let released = runtime.block_on(sqlx::query("ROLLBACK").execute(&mut *holder));
+                    assert!(released.is_ok(), "failed to release lock: {released:?}");
+                }
+                std::future::ready(())
+            },
+        ));
+        let _ = std::fs::remove_dir_all(&database_directory);
+        assert!(recovered.is_ok(), "retry did not recover: {recovered:?}");
+        assert_eq!(loads, 1);
+    }
+
+    #[::core::prelude::v1::test]
+    fn conversation_stats_count_messages_words_and_branches() {
+        let database_directory =
+            std::env::temp_dir().join(format!("zova-sidebar-statistics-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&database_directory);
+        let database_path = database_directory.join("storage.db");
+        let storage = open_storage_at(&database_path.to_string_lossy());
+        let Ok(storage) = storage else {
+            panic!("failed to open statistics storage: {storage:?}");
+        };
+        let session = storage.create_session(NewSession {
+            title: "statistics".to_string(),
+        });
+        let Ok(session) = session else {
+            panic!("failed to create session: {session:?}");
+        };
+        for (role, content) in [
+            (StorageMessageRole::System, "be brief"),
+            (StorageMessageRole::User, "what is rust"),
+            (
+                StorageMessageRole::Assistant,
+                "a systems programming language",
+            ),
+            (StorageMessageRole::User, "thanks"),
+        ] {
+            let appended = storage.append_message(
+                session.id,
+                NewMessage {
+                    role,
+                    content: content.to_string(),
+                },
+            );
+            assert!(appended.is_ok(), "failed to append message: {appended:?}");
+        }
+
+        let stats = load_conversation_stats(&storage, session.id);
+        let refreshed = storage.get_session(session.id);
+        let _ = std::fs::remove_dir_all(&database_directory);
+        let (Ok(stats), Ok(Some(refreshed))) = (stats, refreshed) else {
+            panic!("failed to load statistics for {:?}", session.id);
+        };
+
+        assert_eq!(
+            stats,
+            ConversationStats {
+                message_count: 4,
+                user_message_count: 2,
+                assistant_message_count: 1,
+                total_word_count: 10,
+                created_at: session.created_at_unix_seconds,
+                last_updated_at: refreshed.updated_at_unix_seconds,
+                branch_count: 1,
+            }
+        );
+    }
+
+    #[::core::prelude::v1::test]
+    fn global_search_lists_each_conversation_once() {
+        let database_directory =
+            std::env::temp_dir().join(format!("zova-sidebar-search-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&database_directory);
+        let database_path = database_directory.join("storage.db");
+        let storage = open_storage_at(&database_path.to_string_lossy());
+        let Ok(storage) = storage else {
+            panic!("failed to open search storage: {storage:?}");
+        };
+        let mut sessions = Vec::new();
+        for (title, content) in [
+            ("Rust lifetimes", "borrowing rules for rust references"),
+            ("Weekend plans", "maybe write some rust on sunday"),
+            ("Groceries", "milk and eggs"),
+        ] {
+            let session = storage.create_session(NewSession {
+                title: title.to_string(),
+            });
+            let Ok(session) = session else {
+                panic!("failed to create session: {session:?}");
+            };
+            let appended = storage.append_message(
+                session.id,
+                NewMessage {
+                    role: StorageMessageRole::User,
+                    content: content.to_string(),
+                },
+            );
+            let Ok(appended) = appended else {
+                panic!("failed to append message: {appended:?}");
+            };
+            sessions.push((session.id, appended.id));
+        }
+
+        let hits = search_sessions_and_messages(&storage, "rust", 10);
+        let _ = std::fs::remove_dir_all(&database_directory);
+        let Ok(hits) = hits else {
+            panic!("failed to search: {hits:?}");
+        };
+
+        let summarized = hits
+            .iter()
+            .map(|hit| {
+                (
+                    hit.session.id,
+                    hit.message.as_ref().map(|(message_id, _)| *message_id),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summarized,
+            vec![(sessions[0].0, None), (sessions[1].0, Some(sessions[1].1))]
+        );
+        assert_eq!(
+            hits[1]
+                .message
+                .as_ref()
+                .map(|(_, snippet)| snippet.as_str()),
+            Some("maybe write some rust on sunday")
+        );
+    }
+
+    #[::core::prelude::v1::test]
+    fn message_snippet_centers_match_and_marks_trimmed_ends() {
+        let content = format!(
+            "{}\nthe Needle sits here\n{}",
+            "lead ".repeat(20),
+            "tail ".repeat(40)
+        );
+        let snippet = message_snippet(&content, "needle");
+
+        assert!(snippet.starts_with('…'), "{snippet}");
+        assert!(snippet.ends_with('…'), "{snippet}");
+        assert!(
+            snippet.contains("lead the Needle sits here tail"),
+            "{snippet}"
+        );
+        assert!(!snippet.contains('\n'));
+        assert_eq!(message_snippet("short needle", "needle"), "short needle");
+    }
+
+    fn flat_conversation_ids(items: &[SidebarListItem]) -> Vec<u64> {
+        items
+            .iter()
+            .filter_map(|item| match item {
+                SidebarListItem::Conversation(conversation) => Some(conversation.id.0),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[::core::prelude::v1::test]
+    fn collapsing_a_tag_hides_its_conversations() {
+        let conversations = vec![
+            ConversationRecord::new(ConversationId::new(1), "Plan", 0)
+                .with_tags(vec!["work".to_string(), "rust".to_string()]),
+            ConversationRecord::new(ConversationId::new(2), "Recipes", 0),
+            ConversationRecord::new(ConversationId::new(3), "Review", 0)
+                .with_tags(vec!["work".to_string()]),
+        ];
+        let mut collapsed_tags = HashSet::new();
+
+        let (items, sizes) = build_flat_items(&conversations, "", 0, &collapsed_tags);
+        assert_eq!(items.len(), sizes.len());
+        let headers: Vec<_> = items
+            .iter()
+            .filter_map(|item| match item {
+                SidebarListItem::TagHeader(tag) => Some(tag.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(headers, ["work", UNCATEGORIZED_TAG]);
+        assert_eq!(flat_conversation_ids(&items), [1, 3, 2]);
+
+        collapsed_tags.insert("work".to_string());
+        let (items, _) = build_flat_items(&conversations, "", 0, &collapsed_tags);
+        assert_eq!(flat_conversation_ids(&items), [2]);
+        assert!(
+            items
+                .iter()
+                .any(|item| matches!(item, SidebarListItem::TagHeader(tag) if tag == "work"))
+        );
+    }
+
+    #[::core::prelude::v1::test]
+    fn message_count_label_pluralizes() {
+        assert_eq!(message_count_label(0), "0 messages");
+        assert_eq!(message_count_label(1), "1 message");
+        assert_eq!(message_count_label(12), "12 messages");
+    }
+}
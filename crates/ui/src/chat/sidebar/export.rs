@@ -0,0 +1,203 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use serde_json::json;
+use snafu::{ResultExt, Snafu};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+use zova_storage::{
+    MessageRecord, MessageRole, MessageStore, SessionRecord, SessionStore, SqliteStorage,
+    StorageError,
+};
+
+pub const CONVERSATIONS_EXPORT_FILE_NAME: &str = "zova-conversations.zip";
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum ExportError {
+    #[snafu(display("failed to read conversations for export on `{stage}`: {source}"))]
+    ReadStorage {
+        stage: &'static str,
+        source: StorageError,
+    },
+    #[snafu(display("failed to serialize conversation export on `{stage}`: {source}"))]
+    SerializeConversation {
+        stage: &'static str,
+        source: serde_json::Error,
+    },
+    #[snafu(display("failed to write export archive at {path} on `{stage}`: {source}"))]
+    WriteArchive {
+        stage: &'static str,
+        path: String,
+        source: std::io::Error,
+    },
+    #[snafu(display("failed to build export archive at {path} on `{stage}`: {source}"))]
+    BuildArchive {
+        stage: &'static str,
+        path: String,
+        source: zip::result::ZipError,
+    },
+}
+
+/// Serializes one session and its active-branch messages as a standalone JSON document.
+pub fn export_to_json(session: &SessionRecord, messages: &[MessageRecord]) -> serde_json::Value {
+    let messages = messages
+        .iter()
+        .map(|message| {
+            json!({
+                "id": message.id.to_string(),
+                "seq": message.seq,
+                "role": role_name(message.role),
+                "content": message.content,
+                "created_at": message.created_at_unix_seconds,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "id": session.id.to_string(),
+        "title": session.title,
+        "pinned": session.pinned,
+        "updated_at": session.updated_at_unix_seconds,
+        "deleted_at": session.deleted_at_unix_seconds,
+        "messages": messages,
+    })
+}
+
+/// Writes one `<session-id>.json` entry per session into a zip at `path`.
+///
+/// `on_progress` receives `(exported, total)` after each session so callers can report progress.
+pub fn write_conversations_archive(
+    storage: &SqliteStorage,
+    path: &Path,
+    include_deleted: bool,
+    mut on_progress: impl FnMut(usize, usize),
+) -> Result<usize, ExportError> {
+    let sessions = storage
+        .list_sessions(include_deleted)
+        .context(ReadStorageSnafu {
+            stage: "export-list-sessions",
+        })?;
+    let archive_path = path.display().to_string();
+    let file = File::create(path).context(WriteArchiveSnafu {
+        stage: "export-create-archive",
+        path: archive_path.clone(),
+    })?;
+    let mut archive = ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+
+    let total = sessions.len();
+    for (index, session) in sessions.iter().enumerate() {
+        // Message reads are scoped to live sessions, so trashed ones export metadata only.
+        let messages = if session.deleted_at_unix_seconds.is_some() {
+            Vec::new()
+        } else {
+            storage
+                .list_messages(session.id)
+                .context(ReadStorageSnafu {
+                    stage: "export-list-messages",
+                })?
+        };
+        let document = serde_json::to_vec_pretty(&export_to_json(session, &messages)).context(
+            SerializeConversationSnafu {
+                stage: "export-serialize-conversation",
+            },
+        )?;
+
+        archive
+            .start_file(format!("{}.json", session.id), options)
+            .context(BuildArchiveSnafu {
+                stage: "export-start-entry",
+                path: archive_path.clone(),
+            })?;
+        archive.write_all(&document).context(WriteArchiveSnafu {
+            stage: "export-write-entry",
+            path: archive_path.clone(),
+        })?;
+        on_progress(index + 1, total);
+    }
+
+    archive.finish().context(BuildArchiveSnafu {
+        stage: "export-finish-archive",
+        path: archive_path,
+    })?;
+    Ok(total)
+}
+
+fn role_name(role: MessageRole) -> &'static str {
+    match role {
+        MessageRole::System => "system",
+        MessageRole::User => "user",
+        MessageRole::Assistant => "assistant",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zova_storage::{NewMessage, NewSession};
+
+    #[::core::prelude::v1::test]
+    fn archive_contains_one_json_file_per_conversation() {
+        let directory =
+            std::env::temp_dir().join(format!("zova-sidebar-export-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        let storage =
+            super::super::open_storage_at(&directory.join("storage.db").to_string_lossy());
+        let Ok(storage) = storage else {
+            panic!("failed to open export fixture storage: {storage:?}");
+        };
+
+        let mut session_ids = Vec::new();
+        for index in 0..3 {
+            let session = storage.create_session(NewSession {
+                title: format!("export-{index}"),
+            });
+            let Ok(session) = session else {
+                panic!("failed to create export fixture session: {session:?}");
+            };
+            assert!(
+                storage
+                    .append_message(
+                        session.id,
+                        NewMessage {
+                            role: MessageRole::User,
+                            content: format!("hello {index}"),
+                        },
+                    )
+                    .is_ok()
+            );
+            session_ids.push(session.id);
+        }
+        assert!(storage.soft_delete_session(session_ids[0]).is_ok());
+
+        let count_entries = |include_deleted: bool, file_name: &str| {
+            let archive_path = directory.join(file_name);
+            let mut progress = Vec::new();
+            let exported = write_conversations_archive(
+                &storage,
+                &archive_path,
+                include_deleted,
+                |exported, total| progress.push((exported, total)),
+            );
+            assert!(exported.is_ok(), "export failed: {exported:?}");
+
+            let archive = File::open(&archive_path)
+                .ok()
+                .and_then(|file| zip::ZipArchive::new(file).ok());
+            let Some(archive) = archive else {
+                panic!("failed to reopen export archive at {archive_path:?}");
+            };
+            let json_entries = archive
+                .file_names()
+                .filter(|name| name.ends_with(".json"))
+                .count();
+            (json_entries, progress.last().copied())
+        };
+
+        assert_eq!(count_entries(false, "live.zip"), (2, Some((2, 2))));
+        assert_eq!(count_entries(true, "all.zip"), (3, Some((3, 3))));
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
@@ -3,31 +3,53 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use gpui::*;
-use gpui_component::{ActiveTheme, Root, v_flex};
+use gpui_component::{
+    ActiveTheme, IconName, Root, Sizable,
+    button::{Button, ButtonVariants},
+    h_flex, v_flex,
+};
 use gpui_tokio_bridge::Tokio;
 
-use crate::chat::events::{ConversationSelected, Stop, Submit};
+use crate::chat::events::{
+    ConversationClearRequested, ConversationRenamed, ConversationSelected,
+    ConversationStatisticsRequested, DraftChanged, MessageDeleteRequested, Stop, Submit,
+};
 use crate::chat::message::{
-    Conversation, ConversationId, Message, MessageId, MessageStatus, Role, StreamSessionId,
-    StreamTarget,
+    Conversation, ConversationId, ConversationStats, Message, MessageId, MessageStatus, Role,
+    StreamSessionId, StreamTarget,
 };
+use crate::chat::message_list::format_relative_time;
 use crate::chat::{
-    ChatSidebar, MessageInput, MessageList, SidebarSettingsClicked, SidebarToggleClicked,
+    ChatSidebar, FindInConversation, MessageInput, MessageList, SearchResult,
+    SidebarSettingsClicked, SidebarToggleClicked,
 };
+use crate::error::AppError;
 use crate::model_selector::{
     ModelSelected, ModelSelector, ModelSelectorSettingsClicked, ProviderModelGroup,
 };
 use crate::settings::{ConfiguredModelGroup, SettingsChanged, SettingsState, SettingsView};
 use zova_llm::{
-    DEFAULT_OPENAI_MODEL, LlmProvider, ProviderConfig, ProviderEventStream, ProviderMessage,
-    ProviderStreamHandle, ProviderWorker, Role as ProviderRole,
-    StreamEventMapped as ProviderStreamEventMapped,
+    AbortOnDropHandle, ContentPart, DEFAULT_OPENAI_MODEL, ImageDetail, LlmProvider, MessageContent,
+    Model, ProviderConfig, ProviderEventStream, ProviderMessage, ProviderStreamHandle,
+    ProviderWorker, Role as ProviderRole, StreamEventMapped as ProviderStreamEventMapped,
     StreamEventPayload as ProviderStreamEventPayload, StreamRequest,
     StreamTarget as ProviderStreamTarget, create_provider,
 };
 use zova_storage::{MessageId as StorageMessageId, MessageRole as StorageMessageRole};
 
 pub const STREAM_DEBOUNCE_MS: u64 = 50;
+pub const DRAFT_SAVE_DEBOUNCE_MS: u64 = 500;
+const RATE_LIMIT_DEFAULT_RETRY_SECONDS: u64 = 10;
+const RATE_LIMIT_MAX_RETRY_ATTEMPTS: u32 = 3;
+const RATE_LIMIT_EXHAUSTED_MESSAGE: &str = "Rate limited. Please try again later.";
+
+/// The assistant reply a rate-limit countdown will re-request, and how often it already has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct RateLimitRetry {
+    conversation_id: ConversationId,
+    assistant_message_id: MessageId,
+    attempts: u32,
+}
 
 struct ProviderBuildState {
     providers: HashMap<String, Arc<dyn LlmProvider>>,
@@ -41,6 +63,14 @@ struct ProviderInitState {
     provider_error: Option<String>,
 }
 
+/// A conversation's history token count for one provider and model.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct HistoryTokenCount {
+    provider_key: String,
+    model_id: String,
+    tokens: usize,
+}
+
 /// Coordinator-level stream metadata kept outside the domain model.
 #[derive(Debug, Clone, Copy)]
 struct ActiveStream {
@@ -67,9 +97,17 @@ pub struct ChatView {
     active_stream: Option<ActiveStream>,
     stream_worker_task: Option<Task<Result<(), gpui_tokio_bridge::JoinError>>>,
     stream_reader_task: Option<Task<()>>,
+    stream_abort_handle: Option<AbortOnDropHandle>,
     stream_debounce_task: Option<Task<()>>,
+    rate_limit_retry_task: Option<Task<()>>,
+    rate_limit_retry: Option<RateLimitRetry>,
     pending_stream_chunk: String,
     provider_error: Option<String>,
+    draft_content: HashMap<ConversationId, String>,
+    draft_save_task: Option<Task<()>>,
+    // Invalidated by `conversation_mut` whenever messages may change.
+    history_tokens: HashMap<ConversationId, HistoryTokenCount>,
+    statistics_popover: Option<(ConversationId, ConversationStats)>,
 }
 
 impl EventEmitter<SidebarToggleClicked> for ChatView {}
@@ -155,20 +193,63 @@ impl ChatView {
             active_stream: None,
             stream_worker_task: None,
             stream_reader_task: None,
+            stream_abort_handle: None,
             stream_debounce_task: None,
+            rate_limit_retry_task: None,
+            rate_limit_retry: None,
             pending_stream_chunk: String::new(),
             provider_error: provider_init_state.provider_error,
+            draft_content: HashMap::new(),
+            draft_save_task: None,
+            history_tokens: HashMap::new(),
+            statistics_popover: None,
         };
 
         if let Some(conversation_id) = initial_conversation_id {
             this.activate_conversation(conversation_id, cx);
         }
+        this.sync_input_context_window(cx);
+
+        let providers = this.providers.clone();
+        model_selector.update(cx, |selector, cx| selector.set_providers(providers, cx));
+
+        cx.subscribe(
+            &message_list,
+            |this, _, event: &MessageDeleteRequested, cx| {
+                this.delete_message(event.message_id, cx);
+            },
+        )
+        .detach();
 
         cx.subscribe(&sidebar, |this, _, event: &ConversationSelected, cx| {
             this.handle_conversation_selected(*event, cx);
         })
         .detach();
 
+        cx.subscribe(&sidebar, |this, _, event: &ConversationRenamed, cx| {
+            if let Some(conversation) = this.conversations.get_mut(&event.conversation_id) {
+                conversation.title = event.new_title.clone();
+                cx.notify();
+            }
+        })
+        .detach();
+
+        cx.subscribe(
+            &sidebar,
+            |this, _, event: &ConversationClearRequested, cx| {
+                this.clear_conversation(event.conversation_id, cx);
+            },
+        )
+        .detach();
+
+        cx.subscribe(
+            &sidebar,
+            |this, _, event: &ConversationStatisticsRequested, cx| {
+                this.show_conversation_statistics(event.conversation_id, cx);
+            },
+        )
+        .detach();
+
         cx.subscribe(&sidebar, |this, _, _event: &SidebarSettingsClicked, cx| {
             this.open_settings(cx);
         })
@@ -189,6 +270,11 @@ impl ChatView {
         })
         .detach();
 
+        cx.subscribe(&message_input, |this, _, event: &DraftChanged, cx| {
+            this.handle_draft_changed(event.content.clone(), cx);
+        })
+        .detach();
+
         cx.subscribe(&model_selector, |this, _, event: &ModelSelected, cx| {
             this.handle_model_selected(event.clone(), cx);
         })
@@ -218,6 +304,10 @@ impl ChatView {
         &self.model_selector
     }
 
+    pub fn settings_state(&self) -> &Entity<SettingsState> {
+        &self.settings_state
+    }
+
     pub fn resolved_provider_id(&self, cx: &App) -> String {
         let settings = self.settings_state.read(cx).settings();
         if let Some(provider) = settings.provider_by_key(&self.current_provider_key) {
@@ -254,6 +344,7 @@ impl ChatView {
         self.settings_window = None;
 
         let settings_state = self.settings_state.clone();
+        let storage = self.sidebar.read(cx).storage();
         let settings_bounds = Bounds::centered(None, size(px(860.), px(760.)), cx);
         let settings_window = cx.open_window(
             WindowOptions {
@@ -266,7 +357,8 @@ impl ChatView {
                 ..Default::default()
             },
             move |window, cx| {
-                let settings_view = cx.new(|cx| SettingsView::new(&settings_state, window, cx));
+                let settings_view =
+                    cx.new(|cx| SettingsView::new(&settings_state, storage, window, cx));
                 cx.new(|cx| Root::new(settings_view, window, cx))
             },
         );
@@ -315,8 +407,11 @@ impl ChatView {
         self.current_provider_key = current_provider_key.clone();
         self.current_model_id = current_model_id.clone();
         self.provider_error = active_provider_error;
+        self.sync_input_context_window(cx);
 
+        let providers = self.providers.clone();
         self.model_selector.update(cx, |selector, cx| {
+            selector.set_providers(providers, cx);
             selector.set_provider_model_groups(selector_groups, cx);
             selector.set_selection(current_provider_key.clone(), current_model_id.clone(), cx);
         });
@@ -333,6 +428,7 @@ impl ChatView {
     fn handle_model_selected(&mut self, event: ModelSelected, cx: &mut Context<Self>) {
         self.current_provider_key = event.provider_key;
         self.current_model_id = event.model_id;
+        self.sync_input_context_window(cx);
         if self.providers.contains_key(&self.current_provider_key) {
             self.provider_error = None;
         } else {
@@ -401,15 +497,15 @@ impl ChatView {
                     providers.insert(provider_profile.provider_key.clone(), provider);
                 }
                 Err(error) => {
-                    if provider_profile.provider_key == settings.active_provider_key() {
-                        active_provider_error = Some(error.to_string());
-                    }
                     tracing::error!(
                         provider_key = %provider_profile.provider_key,
                         provider_id = %provider_profile.provider_id,
                         error = %error,
                         "failed to create provider from settings profile"
                     );
+                    if provider_profile.provider_key == settings.active_provider_key() {
+                        active_provider_error = Some(AppError::from(error).display_message());
+                    }
                 }
             }
         }
@@ -445,7 +541,8 @@ impl ChatView {
             Ok(provider) => (Some(provider), model_id, None),
             Err(error) => {
                 tracing::error!("failed to initialize provider adapter: {error}");
-                (None, model_id, Some(format!("Provider error: {}", error)))
+                let message = AppError::from(error).display_message();
+                (None, model_id, Some(format!("Provider error: {message}")))
             }
         }
     }
@@ -481,14 +578,58 @@ impl ChatView {
             // MVP isolation rule: selecting another conversation cancels active streaming immediately.
             self.cancel_active_stream(cx);
         }
+        self.cancel_rate_limit_retry(cx);
 
         self.activate_conversation(event.conversation_id, cx);
     }
 
+    /// Activates the conversation behind a global search hit and, for message hits, scrolls
+    /// the matching message into view.
+    pub fn open_search_result(&mut self, result: &SearchResult, cx: &mut Context<Self>) {
+        let conversation_id = result.conversation().id;
+        // Activate directly so the scroll below sees hydrated messages; the sidebar event this
+        // selection emits is then a no-op.
+        self.handle_conversation_selected(ConversationSelected { conversation_id }, cx);
+        self.sidebar.update(cx, |sidebar, cx| {
+            sidebar.select_conversation(conversation_id, cx);
+        });
+
+        let SearchResult::Message {
+            message_id: storage_message_id,
+            ..
+        } = result
+        else {
+            return;
+        };
+        let message_id =
+            self.storage_message_ids
+                .get(&conversation_id)
+                .and_then(|storage_message_ids| {
+                    storage_message_ids
+                        .iter()
+                        .find(|(_, persisted_id)| *persisted_id == storage_message_id)
+                        .map(|(message_id, _)| *message_id)
+                });
+        if let Some(message_id) = message_id {
+            self.message_list.update(cx, |list, cx| {
+                list.scroll_to_message(message_id, cx);
+            });
+        }
+    }
+
     fn activate_conversation(&mut self, conversation_id: ConversationId, cx: &mut Context<Self>) {
+        // Flush the outgoing draft immediately; a pending debounce would attribute it to the
+        // newly selected conversation.
+        self.draft_save_task = None;
+        if let Some(previous_conversation_id) = self.active_conversation_id {
+            let content = self.message_input.read(cx).content(cx);
+            self.save_draft(previous_conversation_id, content, cx);
+        }
+
         self.ensure_conversation_exists(conversation_id, cx);
         self.hydrate_conversation_messages(conversation_id, cx);
         self.active_conversation_id = Some(conversation_id);
+        self.restore_draft(conversation_id, cx);
 
         self.message_input.update(cx, |input, cx| {
             input.set_streaming(false, cx);
@@ -499,12 +640,295 @@ impl ChatView {
         cx.notify();
     }
 
+    /// Soft-deletes a message of the active conversation from storage and drops it from the
+    /// transcript.
+    pub fn delete_message(&mut self, message_id: MessageId, cx: &mut Context<Self>) {
+        let Some(conversation_id) = self.active_conversation_id else {
+            return;
+        };
+        let Some(storage_message_id) = self
+            .storage_message_ids
+            .get(&conversation_id)
+            .and_then(|storage_message_ids| storage_message_ids.get(&message_id))
+            .copied()
+        else {
+            tracing::warn!("message {message_id:?} has no persisted record to delete");
+            return;
+        };
+
+        let deleted = self.sidebar.update(cx, |sidebar, cx| {
+            sidebar.soft_delete_persisted_message(conversation_id, storage_message_id, cx)
+        });
+        if !deleted {
+            return;
+        }
+
+        if let Some(storage_message_ids) = self.storage_message_ids.get_mut(&conversation_id) {
+            storage_message_ids.remove(&message_id);
+        }
+        if let Some(conversation) = self.conversation_mut(conversation_id) {
+            conversation
+                .messages
+                .retain(|message| message.id != message_id);
+        }
+        self.sync_active_conversation_messages(cx, false);
+        cx.notify();
+    }
+
+    /// Deletes every message in a conversation while keeping the conversation itself.
+    pub fn clear_conversation(&mut self, conversation_id: ConversationId, cx: &mut Context<Self>) {
+        if self
+            .active_stream
+            .is_some_and(|active_stream| active_stream.target.conversation_id == conversation_id)
+        {
+            self.cancel_active_stream(cx);
+        }
+        if self
+            .rate_limit_retry
+            .is_some_and(|retry| retry.conversation_id == conversation_id)
+        {
+            self.cancel_rate_limit_retry(cx);
+        }
+
+        let cleared = self.sidebar.update(cx, |sidebar, cx| {
+            sidebar.clear_persisted_messages(conversation_id, cx)
+        });
+        if !cleared {
+            return;
+        }
+
+        if let Some(conversation) = self.conversation_mut(conversation_id) {
+            conversation.messages.clear();
+        }
+        self.storage_message_ids.remove(&conversation_id);
+
+        if self.active_conversation_id == Some(conversation_id) {
+            self.sync_active_conversation_messages(cx, true);
+        }
+        cx.notify();
+    }
+
+    /// Summarizes a conversation from storage; every field is zero when it cannot be loaded.
+    pub fn conversation_statistics(
+        &self,
+        conversation_id: ConversationId,
+        cx: &App,
+    ) -> ConversationStats {
+        self.sidebar
+            .read(cx)
+            .conversation_statistics(conversation_id)
+            .unwrap_or_default()
+    }
+
+    fn show_conversation_statistics(
+        &mut self,
+        conversation_id: ConversationId,
+        cx: &mut Context<Self>,
+    ) {
+        let stats = self.conversation_statistics(conversation_id, cx);
+        self.statistics_popover = Some((conversation_id, stats));
+        cx.notify();
+    }
+
+    fn dismiss_conversation_statistics(
+        &mut self,
+        _event: &ClickEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.statistics_popover = None;
+        cx.notify();
+    }
+
+    fn render_statistics_popover(&self, cx: &mut Context<Self>) -> Option<AnyElement> {
+        let (conversation_id, stats) = self.statistics_popover?;
+        let title = self
+            .conversations
+            .get(&conversation_id)
+            .map(|conversation| conversation.title.clone())
+            .unwrap_or_else(|| format!("Conversation {}", conversation_id.0));
+        let now = crate::chat::message::unix_now_seconds();
+        let rows = [
+            ("Messages", stats.message_count.to_string()),
+            ("From you", stats.user_message_count.to_string()),
+            ("From assistant", stats.assistant_message_count.to_string()),
+            ("Words", stats.total_word_count.to_string()),
+            ("Branches", stats.branch_count.to_string()),
+            ("Created", format_relative_time(now, stats.created_at)),
+            (
+                "Last updated",
+                format_relative_time(now, stats.last_updated_at),
+            ),
+        ];
+        let theme = cx.theme();
+
+        Some(
+            v_flex()
+                .id("chat-view-statistics-popover")
+                .absolute()
+                .top_3()
+                .right_3()
+                .w(px(260.))
+                .gap_2()
+                .p_3()
+                .bg(theme.popover)
+                .border_1()
+                .border_color(theme.border)
+                .rounded_md()
+                .shadow_md()
+                .child(
+                    h_flex()
+                        .items_center()
+                        .justify_between()
+                        .gap_2()
+                        .child(
+                            div()
+                                .min_w_0()
+                                .truncate()
+                                .text_sm()
+                                .font_weight(FontWeight::SEMIBOLD)
+                                .text_color(theme.foreground)
+                                .child(title),
+                        )
+                        .child(
+                            Button::new("chat-view-statistics-close")
+                                .ghost()
+                                .xsmall()
+                                .icon(IconName::Close)
+                                .on_click(cx.listener(Self::dismiss_conversation_statistics)),
+                        ),
+                )
+                .children(rows.into_iter().map(|(label, value)| {
+                    h_flex()
+                        .justify_between()
+                        .text_xs()
+                        .child(div().text_color(theme.muted_foreground).child(label))
+                        .child(div().text_color(theme.foreground).child(value))
+                }))
+                .into_any_element(),
+        )
+    }
+
+    /// Returns true while input edits are waiting for the debounced draft save.
+    pub fn has_unsaved_draft(&self) -> bool {
+        self.draft_save_task.is_some()
+    }
+
+    fn handle_draft_changed(&mut self, content: String, cx: &mut Context<Self>) {
+        let Some(conversation_id) = self.active_conversation_id else {
+            return;
+        };
+
+        let estimate = self
+            .estimate_prompt_tokens(conversation_id, &content)
+            .unwrap_or(0);
+        self.message_input.update(cx, |input, cx| {
+            input.set_token_estimate(estimate, cx);
+        });
+
+        self.draft_save_task = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor()
+                .timer(Duration::from_millis(DRAFT_SAVE_DEBOUNCE_MS))
+                .await;
+
+            let _ = this.update(cx, |this, cx| {
+                this.draft_save_task = None;
+                this.save_draft(conversation_id, content, cx);
+            });
+        }));
+    }
+
+    /// Estimates the prompt that sending `draft` would produce, history included.
+    ///
+    /// The history count is cached per conversation, so each keystroke only tokenizes the draft.
+    fn estimate_prompt_tokens(
+        &mut self,
+        conversation_id: ConversationId,
+        draft: &str,
+    ) -> Option<usize> {
+        if draft.trim().is_empty() {
+            return None;
+        }
+        let provider = self.providers.get(&self.current_provider_key)?;
+        let conversation = self.conversations.get(&conversation_id)?;
+        let model_id = self.current_model_id.as_str();
+
+        let history_tokens = cached_history_tokens(
+            &mut self.history_tokens,
+            conversation,
+            &self.current_provider_key,
+            model_id,
+            |conversation| {
+                // Attachments are not tokenized text, so the estimate skips image URLs.
+                let history = Self::build_provider_messages(conversation, &HashMap::new());
+                provider.estimate_message_tokens(model_id, &history)
+            },
+        );
+        let draft = ProviderMessage::new(ProviderRole::User, draft);
+        Some(
+            history_tokens
+                + provider.estimate_prompt_tokens(model_id, std::slice::from_ref(&draft)),
+        )
+    }
+
+    /// Mutable access to a conversation; drops its cached history token count because the
+    /// caller may change its messages.
+    fn conversation_mut(&mut self, conversation_id: ConversationId) -> Option<&mut Conversation> {
+        self.history_tokens.remove(&conversation_id);
+        self.conversations.get_mut(&conversation_id)
+    }
+
+    fn sync_input_context_window(&mut self, cx: &mut Context<Self>) {
+        let max_context = Model::from_id(self.current_model_id.as_str()).context_window;
+        self.message_input.update(cx, |input, cx| {
+            input.set_max_context(max_context, cx);
+        });
+    }
+
+    fn save_draft(&mut self, conversation_id: ConversationId, content: String, cx: &App) {
+        if stash_draft(&mut self.draft_content, conversation_id, &content) {
+            self.sidebar.read(cx).save_draft(conversation_id, &content);
+        }
+    }
+
+    fn restore_draft(&mut self, conversation_id: ConversationId, cx: &mut Context<Self>) {
+        let draft = match self.draft_content.get(&conversation_id) {
+            Some(draft) => draft.clone(),
+            None => {
+                let draft = self
+                    .sidebar
+                    .read(cx)
+                    .load_draft(conversation_id)
+                    .unwrap_or_default();
+                stash_draft(&mut self.draft_content, conversation_id, &draft);
+                draft
+            }
+        };
+
+        self.message_input.update(cx, |input, cx| {
+            input.set_content(draft, cx);
+        });
+    }
+
     fn handle_submit(&mut self, event: Submit, cx: &mut Context<Self>) {
         let Some(active_conversation_id) = self.active_conversation_id else {
             return;
         };
 
-        if event.target.conversation_id != active_conversation_id {
+        // `MessageInput::clear` resets the text silently, so no `DraftChanged` arrives to
+        // supersede a pending debounced save of the message that was just sent.
+        let submitted_conversation_id = event.target.conversation_id;
+        if discard_sent_draft(
+            &mut self.draft_content,
+            &mut self.draft_save_task,
+            submitted_conversation_id,
+        ) {
+            self.sidebar
+                .read(cx)
+                .save_draft(submitted_conversation_id, "");
+        }
+
+        if submitted_conversation_id != active_conversation_id {
             return;
         }
 
@@ -513,6 +937,9 @@ impl ChatView {
             return;
         }
 
+        // A fresh prompt supersedes any pending rate-limit retry.
+        self.cancel_rate_limit_retry(cx);
+
         let Some(provider) = self.providers.get(&self.current_provider_key).cloned() else {
             self.push_provider_not_configured_error(active_conversation_id, cx);
             return;
@@ -522,9 +949,10 @@ impl ChatView {
 
         let user_message_id = self.alloc_message_id();
         let assistant_message_id = self.alloc_message_id();
+        let image_urls = self.image_urls_by_message(active_conversation_id, cx);
 
         let request_messages = {
-            let Some(conversation) = self.conversations.get_mut(&active_conversation_id) else {
+            let Some(conversation) = self.conversation_mut(active_conversation_id) else {
                 return;
             };
 
@@ -547,7 +975,7 @@ impl ChatView {
                 event.target.session_id,
             ));
 
-            Self::build_provider_messages(conversation)
+            Self::build_provider_messages(conversation, &image_urls)
         };
 
         // Persist user/assistant inserts after transition acceptance to keep stream lifecycle ordering unchanged.
@@ -566,8 +994,25 @@ impl ChatView {
             cx,
         );
 
+        self.start_assistant_stream(
+            provider,
+            event.target,
+            assistant_message_id,
+            request_messages,
+            cx,
+        );
+    }
+
+    fn start_assistant_stream(
+        &mut self,
+        provider: Arc<dyn LlmProvider>,
+        target: StreamTarget,
+        assistant_message_id: MessageId,
+        request_messages: Vec<ProviderMessage>,
+        cx: &mut Context<Self>,
+    ) {
         self.active_stream = Some(ActiveStream {
-            target: event.target,
+            target,
             assistant_message_id,
         });
 
@@ -590,7 +1035,7 @@ impl ChatView {
             .model_max_tokens(&self.current_provider_key, &self.current_model_id);
 
         let mut request = StreamRequest::new(
-            Self::chat_target_to_provider(event.target),
+            Self::chat_target_to_provider(target),
             self.current_model_id.clone(),
             request_messages,
         );
@@ -603,14 +1048,21 @@ impl ChatView {
         match stream_result {
             Ok(handle) => self.spawn_stream_pipeline(handle, cx),
             Err(error) => {
-                self.finish_stream_with_error(event.target, error.to_string(), cx);
+                tracing::error!("failed to start provider stream: {error}");
+                self.finish_stream_with_error(target, AppError::from(error).display_message(), cx);
             }
         }
     }
 
     fn spawn_stream_pipeline(&mut self, handle: ProviderStreamHandle, cx: &mut Context<Self>) {
-        self.spawn_stream_worker(handle.worker, cx);
-        self.spawn_stream_reader(handle.stream, cx);
+        let mut handle = handle.abort_on_drop();
+        if let Some(worker) = handle.take_worker() {
+            self.spawn_stream_worker(worker, cx);
+        }
+        if let Some(stream) = handle.take_stream() {
+            self.spawn_stream_reader(stream, cx);
+        }
+        self.stream_abort_handle = Some(handle);
     }
 
     fn spawn_stream_worker(&mut self, worker: ProviderWorker, cx: &mut Context<Self>) {
@@ -618,6 +1070,12 @@ impl ChatView {
     }
 
     fn handle_stop(&mut self, event: Stop, cx: &mut Context<Self>) {
+        if self.rate_limit_retry_task.is_some() {
+            // No stream is in flight during a rate-limit countdown; Stop abandons the retry.
+            self.cancel_rate_limit_retry(cx);
+            return;
+        }
+
         let Some(active_stream) = self.active_stream else {
             return;
         };
@@ -670,6 +1128,12 @@ impl ChatView {
                 self.flush_pending_stream_chunk(cx);
                 self.finish_stream_with_error(event_target, message, cx);
             }
+            ProviderStreamEventPayload::RateLimited {
+                retry_after_seconds,
+            } => {
+                self.flush_pending_stream_chunk(cx);
+                self.finish_stream_with_rate_limit(event_target, retry_after_seconds, cx);
+            }
         }
     }
 
@@ -681,6 +1145,7 @@ impl ChatView {
         let target = Self::provider_target_to_chat(target);
         self.stream_worker_task = None;
         self.stream_reader_task = None;
+        self.stream_abort_handle = None;
 
         if self.stream_event_is_current(target) {
             self.finish_stream_with_error(
@@ -725,10 +1190,7 @@ impl ChatView {
         }
 
         let chunk = std::mem::take(&mut self.pending_stream_chunk);
-        let Some(conversation) = self
-            .conversations
-            .get_mut(&active_stream.target.conversation_id)
-        else {
+        let Some(conversation) = self.conversation_mut(active_stream.target.conversation_id) else {
             return;
         };
 
@@ -796,13 +1258,183 @@ impl ChatView {
         );
     }
 
+    fn finish_stream_with_rate_limit(
+        &mut self,
+        target: StreamTarget,
+        retry_after_seconds: Option<u64>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(active_stream) = self.active_stream else {
+            return;
+        };
+
+        if active_stream.target != target {
+            return;
+        }
+
+        let conversation_id = target.conversation_id;
+        let assistant_message_id = active_stream.assistant_message_id;
+        let Some(attempts) = next_rate_limit_attempt(self.rate_limit_retry, assistant_message_id)
+        else {
+            self.rate_limit_retry = None;
+            let message = RATE_LIMIT_EXHAUSTED_MESSAGE.to_string();
+            self.finalize_stream(
+                target,
+                MessageStatus::Error(message.clone()),
+                crate::chat::StreamTransition::Fail { target, message },
+                cx,
+            );
+            return;
+        };
+
+        let delay_seconds = retry_after_seconds.unwrap_or(RATE_LIMIT_DEFAULT_RETRY_SECONDS);
+        let message = rate_limit_retry_message(delay_seconds);
+        self.finalize_stream(
+            target,
+            MessageStatus::Error(message.clone()),
+            crate::chat::StreamTransition::Fail { target, message },
+            cx,
+        );
+
+        self.rate_limit_retry = Some(RateLimitRetry {
+            conversation_id,
+            assistant_message_id,
+            attempts,
+        });
+        // Keep the Stop button available so the countdown can be abandoned.
+        self.message_input.update(cx, |input, cx| {
+            input.set_streaming(true, cx);
+        });
+        self.rate_limit_retry_task = Some(cx.spawn(async move |this, cx| {
+            for remaining_seconds in (1..delay_seconds).rev() {
+                cx.background_executor().timer(Duration::from_secs(1)).await;
+                let _ = this.update(cx, |this, cx| {
+                    this.set_assistant_message_status(
+                        conversation_id,
+                        assistant_message_id,
+                        MessageStatus::Error(rate_limit_retry_message(remaining_seconds)),
+                        cx,
+                    );
+                });
+            }
+
+            cx.background_executor().timer(Duration::from_secs(1)).await;
+            let _ = this.update(cx, |this, cx| {
+                this.rate_limit_retry_task = None;
+                this.retry_rate_limited_stream(conversation_id, assistant_message_id, cx);
+            });
+        }));
+    }
+
+    /// Abandons a pending rate-limit countdown, leaving its reply marked as cancelled.
+    fn cancel_rate_limit_retry(&mut self, cx: &mut Context<Self>) {
+        if self.rate_limit_retry_task.take().is_none() {
+            return;
+        }
+        let Some(retry) = self.rate_limit_retry.take() else {
+            return;
+        };
+
+        self.set_assistant_message_status(
+            retry.conversation_id,
+            retry.assistant_message_id,
+            MessageStatus::Cancelled,
+            cx,
+        );
+        self.message_input.update(cx, |input, cx| {
+            input.set_streaming(false, cx);
+        });
+        cx.notify();
+    }
+
+    fn retry_rate_limited_stream(
+        &mut self,
+        conversation_id: ConversationId,
+        assistant_message_id: MessageId,
+        cx: &mut Context<Self>,
+    ) {
+        self.message_input.update(cx, |input, cx| {
+            input.set_streaming(false, cx);
+        });
+
+        if self.active_stream.is_some() {
+            return;
+        }
+
+        let Some(provider) = self.providers.get(&self.current_provider_key).cloned() else {
+            return;
+        };
+
+        let target = StreamTarget::new(
+            conversation_id,
+            StreamSessionId::new(self.next_stream_session_id),
+        );
+
+        let image_urls = self.image_urls_by_message(conversation_id, cx);
+        let request_messages = {
+            let Some(conversation) = self.conversation_mut(conversation_id) else {
+                return;
+            };
+
+            if conversation
+                .apply_stream_transition(crate::chat::StreamTransition::Start(target))
+                .is_err()
+            {
+                return;
+            }
+
+            // Reuse the failed assistant row so the retry keeps its persisted storage mapping.
+            let Some(message) = conversation
+                .messages
+                .iter_mut()
+                .find(|message| message.id == assistant_message_id)
+            else {
+                return;
+            };
+            message.content.clear();
+            message.status = MessageStatus::Streaming(target.session_id);
+
+            Self::build_provider_messages(conversation, &image_urls)
+        };
+
+        self.start_assistant_stream(provider, target, assistant_message_id, request_messages, cx);
+    }
+
+    fn set_assistant_message_status(
+        &mut self,
+        conversation_id: ConversationId,
+        message_id: MessageId,
+        status: MessageStatus,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(message) = self
+            .conversations
+            .get_mut(&conversation_id)
+            .and_then(|conversation| {
+                conversation
+                    .messages
+                    .iter_mut()
+                    .find(|message| message.id == message_id)
+            })
+        else {
+            return;
+        };
+        message.status = status;
+
+        if self.active_conversation_id == Some(conversation_id) {
+            self.sync_active_conversation_messages(cx, false);
+        }
+    }
+
     fn cancel_active_stream(&mut self, cx: &mut Context<Self>) {
         let Some(active_stream) = self.active_stream else {
             return;
         };
 
         // Dropping the task cancels the stream reader and drops ProviderEventStream,
-        // which in turn signals cancellation to the provider worker.
+        // which in turn signals cancellation to the provider worker. The abort handle also
+        // closes the HTTP connection in case the worker outlives its task handle.
+        self.stream_abort_handle = None;
         self.stream_worker_task = None;
         self.stream_reader_task = None;
 
@@ -832,10 +1464,11 @@ impl ChatView {
         self.pending_stream_chunk.clear();
         self.stream_debounce_task = None;
         self.stream_worker_task = None;
+        self.stream_abort_handle = None;
 
         let mut persisted_assistant_content = None;
 
-        if let Some(conversation) = self.conversations.get_mut(&target.conversation_id) {
+        if let Some(conversation) = self.conversation_mut(target.conversation_id) {
             let _ = conversation.apply_stream_transition(transition);
 
             if let Some(message) = conversation
@@ -938,15 +1571,18 @@ impl ChatView {
         for persisted_message in persisted_messages {
             let message_id = self.alloc_message_id();
             storage_message_ids.insert(message_id, persisted_message.id);
-            hydrated_messages.push(Message::new(
-                message_id,
-                storage_role_to_chat(persisted_message.role),
-                persisted_message.content,
-                MessageStatus::Done,
-            ));
+            hydrated_messages.push(
+                Message::new(
+                    message_id,
+                    storage_role_to_chat(persisted_message.role),
+                    persisted_message.content,
+                    MessageStatus::Done,
+                )
+                .with_created_at(persisted_message.created_at_unix_seconds),
+            );
         }
 
-        if let Some(conversation) = self.conversations.get_mut(&conversation_id) {
+        if let Some(conversation) = self.conversation_mut(conversation_id) {
             conversation.messages = hydrated_messages;
         }
         self.storage_message_ids
@@ -999,7 +1635,34 @@ impl ChatView {
         );
     }
 
-    fn build_provider_messages(conversation: &Conversation) -> Vec<ProviderMessage> {
+    fn image_urls_by_message(
+        &self,
+        conversation_id: ConversationId,
+        cx: &App,
+    ) -> HashMap<MessageId, Vec<String>> {
+        // Models without vision reject image parts, so their turns are sent as text only.
+        if !model_accepts_images(self.current_model_id.as_str()) {
+            return HashMap::new();
+        }
+        let Some(storage_message_ids) = self.storage_message_ids.get(&conversation_id) else {
+            return HashMap::new();
+        };
+        let sidebar = self.sidebar.read(cx);
+
+        storage_message_ids
+            .iter()
+            .filter_map(|(message_id, storage_message_id)| {
+                let image_urls =
+                    sidebar.image_urls_for_message(conversation_id, *storage_message_id);
+                (!image_urls.is_empty()).then_some((*message_id, image_urls))
+            })
+            .collect()
+    }
+
+    fn build_provider_messages(
+        conversation: &Conversation,
+        image_urls: &HashMap<MessageId, Vec<String>>,
+    ) -> Vec<ProviderMessage> {
         conversation
             .messages
             .iter()
@@ -1008,10 +1671,21 @@ impl ChatView {
             .map(|message| {
                 // Keep role mapping explicit at the crate boundary so llm types stay
                 // decoupled from chat domain enums.
-                ProviderMessage::new(
-                    Self::chat_role_to_provider(message.role),
-                    message.content.clone(),
-                )
+                let role = Self::chat_role_to_provider(message.role);
+                match image_urls.get(&message.id) {
+                    Some(urls) if message.role == Role::User => {
+                        let mut parts = vec![ContentPart::Text(message.content.clone())];
+                        parts.extend(urls.iter().map(|url| ContentPart::ImageUrl {
+                            url: url.clone(),
+                            detail: ImageDetail::default(),
+                        }));
+                        ProviderMessage {
+                            role,
+                            content: MessageContent::Parts(parts),
+                        }
+                    }
+                    _ => ProviderMessage::new(role, message.content.clone()),
+                }
             })
             .collect()
     }
@@ -1058,7 +1732,7 @@ impl ChatView {
         };
         let persisted_error_text = error_text.clone();
 
-        if let Some(conversation) = self.conversations.get_mut(&conversation_id) {
+        if let Some(conversation) = self.conversation_mut(conversation_id) {
             conversation.messages.push(Message::new(
                 message_id,
                 Role::Assistant,
@@ -1095,6 +1769,21 @@ impl ChatView {
     }
 }
 
+fn rate_limit_retry_message(remaining_seconds: u64) -> String {
+    format!("Rate limited. Retrying in {remaining_seconds}s…")
+}
+
+/// Numbers the next automatic retry of a rate-limited reply, or `None` once the cap is spent.
+fn next_rate_limit_attempt(
+    previous: Option<RateLimitRetry>,
+    assistant_message_id: MessageId,
+) -> Option<u32> {
+    let attempts = previous
+        .filter(|retry| retry.assistant_message_id == assistant_message_id)
+        .map_or(0, |retry| retry.attempts);
+    (attempts < RATE_LIMIT_MAX_RETRY_ATTEMPTS).then_some(attempts + 1)
+}
+
 fn storage_role_to_chat(role: StorageMessageRole) -> Role {
     match role {
         StorageMessageRole::System => Role::System,
@@ -1103,12 +1792,79 @@ fn storage_role_to_chat(role: StorageMessageRole) -> Role {
     }
 }
 
+/// Whether `model_id` takes image inputs; unknown models are assumed not to.
+fn model_accepts_images(model_id: &str) -> bool {
+    Model::from_id(model_id).supports_vision
+}
+
+/// Returns the cached history token count for `conversation`, calling `count` only when
+/// nothing is cached for the current provider and model.
+fn cached_history_tokens(
+    cache: &mut HashMap<ConversationId, HistoryTokenCount>,
+    conversation: &Conversation,
+    provider_key: &str,
+    model_id: &str,
+    count: impl FnOnce(&Conversation) -> usize,
+) -> usize {
+    if let Some(cached) = cache.get(&conversation.id)
+        && cached.provider_key == provider_key
+        && cached.model_id == model_id
+    {
+        return cached.tokens;
+    }
+
+    let tokens = count(conversation);
+    cache.insert(
+        conversation.id,
+        HistoryTokenCount {
+            provider_key: provider_key.to_string(),
+            model_id: model_id.to_string(),
+            tokens,
+        },
+    );
+    tokens
+}
+
+/// Records a conversation draft, dropping empty ones; returns whether the stored value changed.
+fn stash_draft(
+    drafts: &mut HashMap<ConversationId, String>,
+    conversation_id: ConversationId,
+    content: &str,
+) -> bool {
+    if content.is_empty() {
+        return drafts.remove(&conversation_id).is_some();
+    }
+
+    if drafts.get(&conversation_id).map(String::as_str) == Some(content) {
+        return false;
+    }
+
+    drafts.insert(conversation_id, content.to_string());
+    true
+}
+
+/// Cancels any pending draft save and forgets the sent draft; returns whether a stored
+/// draft must also be cleared.
+fn discard_sent_draft(
+    drafts: &mut HashMap<ConversationId, String>,
+    pending_save: &mut Option<Task<()>>,
+    conversation_id: ConversationId,
+) -> bool {
+    *pending_save = None;
+    stash_draft(drafts, conversation_id, "")
+}
+
 impl Render for ChatView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let statistics_popover = self.render_statistics_popover(cx);
         let theme = cx.theme();
 
         v_flex()
             .id("chat-view")
+            .on_action(cx.listener(|this, _: &FindInConversation, window, cx| {
+                this.message_list
+                    .update(cx, |list, cx| list.open_search(window, cx));
+            }))
             .relative()
             .size_full()
             .min_h_0()
@@ -1130,5 +1886,140 @@ impl Render for ChatView {
                     .border_color(theme.border)
                     .child(self.message_input.clone()),
             )
+            .children(statistics_popover)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[::core::prelude::v1::test]
+    fn rate_limit_retries_stop_at_the_attempt_cap() {
+        let conversation_id = ConversationId::new(1);
+        let assistant_message_id = MessageId::new(7);
+        let mut retry = None;
+
+        for expected in 1..=RATE_LIMIT_MAX_RETRY_ATTEMPTS {
+            let Some(attempts) = next_rate_limit_attempt(retry, assistant_message_id) else {
+                panic!("attempt {expected} should still be allowed");
+            };
+            assert_eq!(attempts, expected);
+            retry = Some(RateLimitRetry {
+                conversation_id,
+                assistant_message_id,
+                attempts,
+            });
+        }
+        assert_eq!(next_rate_limit_attempt(retry, assistant_message_id), None);
+
+        // A different reply starts its own count.
+        assert_eq!(next_rate_limit_attempt(retry, MessageId::new(8)), Some(1));
+    }
+
+    #[::core::prelude::v1::test]
+    fn images_are_only_sent_to_vision_models() {
+        assert!(model_accepts_images("gpt-4o"));
+        assert!(!model_accepts_images("o3-mini"));
+        assert!(!model_accepts_images("gpt-3.5-turbo"));
+    }
+
+    #[::core::prelude::v1::test]
+    fn history_tokens_are_counted_once_per_model() {
+        let mut conversation = Conversation::new(ConversationId::new(1), "Tokens");
+        conversation.messages.push(Message::new(
+            MessageId::new(1),
+            Role::User,
+            "hello there",
+            MessageStatus::Done,
+        ));
+        let mut cache = HashMap::new();
+        let counts = std::cell::Cell::new(0);
+        let count = |conversation: &Conversation| {
+            counts.set(counts.get() + 1);
+            conversation.messages.len() * 10
+        };
+
+        assert_eq!(
+            cached_history_tokens(&mut cache, &conversation, "openai", "gpt-4o", count),
+            10
+        );
+        assert_eq!(
+            cached_history_tokens(&mut cache, &conversation, "openai", "gpt-4o", count),
+            10
+        );
+        assert_eq!(counts.get(), 1);
+
+        // Another model may tokenize differently, so its count is not reused.
+        cached_history_tokens(&mut cache, &conversation, "openai", "gpt-4", count);
+        assert_eq!(counts.get(), 2);
+
+        // `conversation_mut` drops the entry whenever messages may change.
+        conversation.messages.push(Message::new(
+            MessageId::new(2),
+            Role::Assistant,
+            "hi",
+            MessageStatus::Done,
+        ));
+        cache.remove(&conversation.id);
+        assert_eq!(
+            cached_history_tokens(&mut cache, &conversation, "openai", "gpt-4", count),
+            20
+        );
+        assert_eq!(counts.get(), 3);
+    }
+
+    #[::core::prelude::v1::test]
+    fn drafts_round_trip_across_conversation_switches() {
+        let first = ConversationId::new(1);
+        let second = ConversationId::new(2);
+        let mut drafts = HashMap::new();
+
+        // Leave the first conversation with text typed, then type into the second.
+        assert!(stash_draft(&mut drafts, first, "half-written question"));
+        assert_eq!(drafts.get(&second), None);
+        assert!(stash_draft(&mut drafts, second, "other thought"));
+
+        // Switching back restores each draft independently.
+        assert_eq!(
+            drafts.get(&first).map(String::as_str),
+            Some("half-written question")
+        );
+        assert!(!stash_draft(&mut drafts, first, "half-written question"));
+        assert_eq!(
+            drafts.get(&second).map(String::as_str),
+            Some("other thought")
+        );
+
+        // Emptying the input drops the draft.
+        assert!(stash_draft(&mut drafts, first, ""));
+        assert_eq!(drafts.get(&first), None);
+        assert!(!stash_draft(&mut drafts, first, ""));
+    }
+
+    #[::core::prelude::v1::test]
+    fn submitting_discards_the_sent_draft() {
+        let conversation_id = ConversationId::new(1);
+        let mut drafts = HashMap::new();
+
+        // The debounce already persisted the text, and a later save is still pending.
+        assert!(stash_draft(&mut drafts, conversation_id, "sent question"));
+        let mut pending_save = Some(Task::ready(()));
+        assert!(discard_sent_draft(
+            &mut drafts,
+            &mut pending_save,
+            conversation_id
+        ));
+        assert!(pending_save.is_none());
+        assert_eq!(drafts.get(&conversation_id), None);
+
+        // A send before the first debounce fired has nothing stored to clear.
+        let mut pending_save = Some(Task::ready(()));
+        assert!(!discard_sent_draft(
+            &mut drafts,
+            &mut pending_save,
+            conversation_id
+        ));
+        assert!(pending_save.is_none());
     }
 }
@@ -15,6 +15,9 @@ pub struct ConversationRecord {
     pub id: ConversationId,
     pub title: String,
     pub updated_at_unix_seconds: u64,
+    pub pinned: bool,
+    pub message_count: Option<usize>,
+    pub tags: Vec<String>,
 }
 
 impl ConversationRecord {
@@ -23,8 +26,26 @@ impl ConversationRecord {
             id,
             title: title.into(),
             updated_at_unix_seconds,
+            pinned: false,
+            message_count: None,
+            tags: Vec::new(),
         }
     }
+
+    pub fn with_pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    pub fn with_message_count(mut self, message_count: Option<usize>) -> Self {
+        self.message_count = message_count;
+        self
+    }
+
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,112 @@
+use snafu::Snafu;
+use zova_llm::ProviderError;
+use zova_storage::StorageError;
+
+/// Application-level failure covering every backend the chat UI talks to.
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)))]
+pub enum AppError {
+    #[snafu(context(false), display("{source}"))]
+    Provider { source: ProviderError },
+    #[snafu(context(false), display("{source}"))]
+    Storage { source: StorageError },
+}
+
+impl AppError {
+    /// Returns a short description suitable for showing to users, without internal stage names.
+    pub fn display_message(&self) -> String {
+        match self {
+            Self::Provider { source } => provider_display_message(source),
+            Self::Storage { source } => source.display_message().to_string(),
+        }
+    }
+}
+
+fn provider_display_message(error: &ProviderError) -> String {
+    match error {
+        ProviderError::MissingApiKey { .. } => "API key is missing".to_string(),
+        ProviderError::InvalidConfig { field, reason, .. } => {
+            format!("Provider setting '{field}' is invalid: {reason}")
+        }
+        ProviderError::UnsupportedProvider { provider_id, .. } => {
+            format!("Provider '{provider_id}' is not supported")
+        }
+        ProviderError::EmptyMessageSet { .. } => "There is nothing to send".to_string(),
+        ProviderError::HttpClient { .. } => "Could not reach the provider".to_string(),
+        ProviderError::BuildHttpRequestBody { .. } => {
+            "Could not build the provider request".to_string()
+        }
+        ProviderError::RateLimited {
+            retry_after_seconds: Some(seconds),
+            ..
+        } => format!("Rate limited by the provider, try again in {seconds}s"),
+        ProviderError::RateLimited { .. } => "Rate limited by the provider".to_string(),
+        ProviderError::ModelFetchStatus {
+            status: 401 | 403, ..
+        } => "The provider rejected the API key".to_string(),
+        ProviderError::ModelFetchStatus { status, .. } => {
+            format!("The provider returned HTTP {status}")
+        }
+        ProviderError::ModelPayloadParse { .. } => {
+            "The provider returned an unexpected model list".to_string()
+        }
+        ProviderError::UnknownStream { .. } => "The response stream already ended".to_string(),
+        ProviderError::CompletionsFailed { .. } => {
+            "The provider could not complete the request".to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider_failure() -> Result<(), ProviderError> {
+        Err(ProviderError::MissingApiKey {
+            stage: "app-error-test-provider",
+            provider_id: "openai".to_string(),
+        })
+    }
+
+    fn storage_failure() -> Result<(), StorageError> {
+        Err(StorageError::NotFound {
+            stage: "app-error-test-storage",
+            entity: "session",
+            id: "missing".to_string(),
+        })
+    }
+
+    fn run(step: fn() -> Result<(), AppError>) -> AppError {
+        match step() {
+            Err(error) => error,
+            Ok(()) => panic!("step should fail"),
+        }
+    }
+
+    #[::core::prelude::v1::test]
+    fn backend_errors_convert_and_keep_their_messages() {
+        let provider_error = run(|| Ok(provider_failure()?));
+        let storage_error = run(|| Ok(storage_failure()?));
+
+        assert!(matches!(provider_error, AppError::Provider { .. }));
+        assert_eq!(provider_error.display_message(), "API key is missing");
+        assert_eq!(
+            provider_error.to_string(),
+            "missing API key for provider 'openai'"
+        );
+        assert!(matches!(storage_error, AppError::Storage { .. }));
+        assert_eq!(storage_error.display_message(), "Conversation was deleted");
+        assert!(!storage_error.display_message().contains("stage"));
+    }
+
+    #[::core::prelude::v1::test]
+    fn rejected_credentials_read_as_an_api_key_problem() {
+        let error = AppError::from(ProviderError::ModelFetchStatus {
+            stage: "app-error-test-status",
+            status: 401,
+            body: "{\"error\":\"invalid_api_key\"}".to_string(),
+        });
+
+        assert_eq!(error.display_message(), "The provider rejected the API key");
+    }
+}
@@ -8,6 +8,8 @@ pub mod app;
 /// Chat domain contracts shared across UI modules.
 pub mod chat;
 pub mod database;
+/// Unified error type for failures surfaced to the user.
+pub mod error;
 /// Model selector component for changing LLM models.
 pub mod model_selector;
 /// Settings persistence and UI.
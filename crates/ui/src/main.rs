@@ -2,7 +2,8 @@ use gpui::*;
 use gpui_component::notification::NotificationList;
 use gpui_component::{Root, ThemeRegistry};
 
-use ui::app::{ChatAppShell, NewChat, Quit, ToggleSidebar, default_themes_path};
+use ui::app::{ChatAppShell, GlobalSearch, NewChat, Quit, ToggleSidebar, default_themes_path};
+use ui::chat::FindInConversation;
 use ui::settings::state::SettingsStore;
 
 /// Application entry point.
@@ -54,6 +55,8 @@ fn main() {
             KeyBinding::new("cmd-q", Quit, None),
             KeyBinding::new("cmd-n", NewChat, None),
             KeyBinding::new("cmd-b", ToggleSidebar, None),
+            KeyBinding::new("cmd-f", FindInConversation, None),
+            KeyBinding::new("cmd-p", GlobalSearch, None),
         ]);
 
         // Spawn async window creation to ensure all initialization is complete
@@ -1,11 +1,17 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::{
     ActiveTheme, IconName, Selectable, Sizable,
     button::{Button, ButtonVariants},
-    h_flex, v_flex,
+    h_flex,
+    spinner::Spinner,
+    v_flex,
 };
-use zova_llm::{Model, default_openai_models};
+use gpui_tokio_bridge::Tokio;
+use zova_llm::{LlmProvider, Model, ModelCatalog, ProviderResult, default_openai_models};
 
 use crate::chat::events::ModelChanged;
 
@@ -21,6 +27,10 @@ pub struct ModelSelector {
     current_model_id: String,
     is_open: bool,
     available_groups: Vec<ProviderModelGroup>,
+    providers: HashMap<String, Arc<dyn LlmProvider>>,
+    fetching_models: bool,
+    fetch_error: Option<String>,
+    fetch_task: Option<Task<()>>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -49,9 +59,63 @@ impl ModelSelector {
                 provider_id: "openai".to_string(),
                 models: default_openai_models(),
             }],
+            providers: HashMap::new(),
+            fetching_models: false,
+            fetch_error: None,
+            fetch_task: None,
         }
     }
 
+    /// Sets the runtime providers, keyed by provider key, that "Refresh" queries for models.
+    pub fn set_providers(
+        &mut self,
+        providers: HashMap<String, Arc<dyn LlmProvider>>,
+        cx: &mut Context<Self>,
+    ) {
+        self.providers = providers;
+        cx.notify();
+    }
+
+    pub fn fetching_models(&self) -> bool {
+        self.fetching_models
+    }
+
+    /// Re-queries every provider for its model list and swaps the results into the dropdown.
+    pub fn fetch_and_refresh_models(&mut self, cx: &mut Context<Self>) {
+        if self.fetching_models {
+            return;
+        }
+        if self.providers.is_empty() {
+            self.fetch_error = Some("No providers are configured".to_string());
+            cx.notify();
+            return;
+        }
+
+        let providers = self
+            .providers
+            .iter()
+            .map(|(provider_key, provider)| (provider_key.clone(), provider.clone()))
+            .collect::<Vec<_>>();
+        self.fetching_models = true;
+        self.fetch_error = None;
+        cx.notify();
+
+        let fetch = Tokio::spawn(cx, fetch_provider_catalogs(providers));
+        self.fetch_task = Some(cx.spawn(async move |this, cx| {
+            let result = fetch.await;
+            let _ = this.update(cx, |this, cx| {
+                this.fetching_models = false;
+                this.fetch_task = None;
+                this.fetch_error = match result {
+                    Ok(results) => apply_fetched_catalogs(&mut this.available_groups, results),
+                    Err(error) => Some(format!("Model refresh task failed: {error}")),
+                };
+                this.ensure_valid_selection();
+                cx.notify();
+            });
+        }));
+    }
+
     pub fn set_selection(
         &mut self,
         provider_key: impl Into<String>,
@@ -138,6 +202,15 @@ impl ModelSelector {
         cx.notify();
     }
 
+    fn refresh_models(
+        &mut self,
+        _event: &gpui::ClickEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.fetch_and_refresh_models(cx);
+    }
+
     fn open_settings(
         &mut self,
         _event: &gpui::ClickEvent,
@@ -171,6 +244,8 @@ impl Render for ModelSelector {
         let is_open = self.is_open;
         let current_provider_key = self.current_provider_key.clone();
         let current_model_id = self.current_model_id.clone();
+        let fetching_models = self.fetching_models;
+        let fetch_error = self.fetch_error.clone();
 
         let mut dropdown_items = Vec::new();
         for group in self.available_groups.clone() {
@@ -272,18 +347,282 @@ impl Render for ModelSelector {
                                         .child("Select Model"),
                                 )
                                 .child(
-                                    Button::new("model-selector-settings")
-                                        .ghost()
-                                        .xsmall()
-                                        .icon(IconName::Settings)
-                                        .child("Settings")
-                                        .on_click(cx.listener(Self::open_settings)),
+                                    h_flex()
+                                        .gap_1()
+                                        .child(
+                                            Button::new("model-selector-refresh")
+                                                .ghost()
+                                                .xsmall()
+                                                .child("Refresh")
+                                                .loading(fetching_models)
+                                                .when_some(fetch_error, |button, error| {
+                                                    button
+                                                        .icon(IconName::TriangleAlert)
+                                                        .tooltip(error)
+                                                })
+                                                .on_click(cx.listener(Self::refresh_models)),
+                                        )
+                                        .child(
+                                            Button::new("model-selector-settings")
+                                                .ghost()
+                                                .xsmall()
+                                                .icon(IconName::Settings)
+                                                .child("Settings")
+                                                .on_click(cx.listener(Self::open_settings)),
+                                        ),
                                 ),
                         )
-                        .children(dropdown_items),
+                        .when(fetching_models, |dropdown| {
+                            dropdown.child(
+                                h_flex()
+                                    .w_full()
+                                    .py_4()
+                                    .justify_center()
+                                    .child(Spinner::new()),
+                            )
+                        })
+                        .when(!fetching_models, |dropdown| {
+                            dropdown.children(dropdown_items)
+                        }),
                 )
             })
     }
 }
 
 impl EventEmitter<ModelChanged> for ModelSelector {}
+
+async fn fetch_provider_catalogs(
+    providers: Vec<(String, Arc<dyn LlmProvider>)>,
+) -> Vec<(String, ProviderResult<ModelCatalog>)> {
+    let mut results = Vec::with_capacity(providers.len());
+    for (provider_key, provider) in providers {
+        // An explicit refresh must reach the provider even while its cached list is fresh.
+        results.push((provider_key, provider.refresh_models().await));
+    }
+    results
+}
+
+/// Replaces each group's models with its fetched catalog and returns a summary of failures.
+///
+/// Empty catalogs keep the previous list so a flaky endpoint never blanks the dropdown.
+fn apply_fetched_catalogs(
+    groups: &mut [ProviderModelGroup],
+    results: Vec<(String, ProviderResult<ModelCatalog>)>,
+) -> Option<String> {
+    let mut problems = Vec::new();
+    for (provider_key, result) in results {
+        match result {
+            Ok(catalog) => {
+                if let Some(warning) = catalog.warning {
+                    problems.push(format!("{provider_key}: {warning}"));
+                }
+                if catalog.models.is_empty() {
+                    continue;
+                }
+                if let Some(group) = groups
+                    .iter_mut()
+                    .find(|group| group.provider_key == provider_key)
+                {
+                    group.models = catalog.models;
+                }
+            }
+            Err(error) => problems.push(format!("{provider_key}: {error}")),
+        }
+    }
+
+    if problems.is_empty() {
+        None
+    } else {
+        Some(problems.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use zova_llm::{
+        BoxFuture, ModelCatalogSource, ProviderError, ProviderStreamHandle, StreamRequest,
+    };
+
+    struct FixedCatalogProvider {
+        models: Vec<Model>,
+    }
+
+    impl LlmProvider for FixedCatalogProvider {
+        fn id(&self) -> &str {
+            "fixed"
+        }
+
+        fn name(&self) -> &str {
+            "Fixed"
+        }
+
+        fn default_model(&self) -> &str {
+            "fixed-small"
+        }
+
+        fn fallback_models(&self) -> &[Model] {
+            &self.models
+        }
+
+        fn fetch_models<'a>(&'a self) -> BoxFuture<'a, ProviderResult<ModelCatalog>> {
+            Box::pin(async move { Ok(ModelCatalog::from_provider_api(self.models.clone())) })
+        }
+
+        fn stream_chat(&self, _request: StreamRequest) -> ProviderResult<ProviderStreamHandle> {
+            Err(ProviderError::UnsupportedProvider {
+                stage: "fixed-provider-stream",
+                provider_id: self.id().to_string(),
+            })
+        }
+    }
+
+    #[::core::prelude::v1::test]
+    fn fetched_catalog_replaces_group_models() {
+        let fetched_models = vec![
+            Model::new("fixed-small", "Fixed Small"),
+            Model::new("fixed-large", "Fixed Large"),
+        ];
+        let provider: Arc<dyn LlmProvider> = Arc::new(FixedCatalogProvider {
+            models: fetched_models.clone(),
+        });
+        let runtime = tokio::runtime::Builder::new_current_thread().build();
+        let Ok(runtime) = runtime else {
+            panic!("failed to build test runtime: {runtime:?}");
+        };
+        let results = runtime.block_on(fetch_provider_catalogs(vec![(
+            "provider-1".to_string(),
+            provider,
+        )]));
+
+        let mut groups = vec![
+            ProviderModelGroup {
+                provider_key: "provider-1".to_string(),
+                provider_id: "openai".to_string(),
+                models: default_openai_models(),
+            },
+            ProviderModelGroup {
+                provider_key: "provider-2".to_string(),
+                provider_id: "openai".to_string(),
+                models: vec![Model::new("untouched", "Untouched")],
+            },
+        ];
+        let error = apply_fetched_catalogs(&mut groups, results);
+
+        assert_eq!(error, None);
+        assert_eq!(groups[0].models, fetched_models);
+        assert_eq!(groups[1].models, vec![Model::new("untouched", "Untouched")]);
+    }
+
+    /// Serves its first catalog from the network and every later plain fetch from cache.
+    struct CachingProvider {
+        network_fetches: AtomicUsize,
+    }
+
+    impl LlmProvider for CachingProvider {
+        fn id(&self) -> &str {
+            "caching"
+        }
+
+        fn name(&self) -> &str {
+            "Caching"
+        }
+
+        fn default_model(&self) -> &str {
+            "caching-1"
+        }
+
+        fn fallback_models(&self) -> &[Model] {
+            &[]
+        }
+
+        fn fetch_models<'a>(&'a self) -> BoxFuture<'a, ProviderResult<ModelCatalog>> {
+            Box::pin(async move {
+                match self.network_fetches.load(Ordering::SeqCst) {
+                    0 => self.refresh_models().await,
+                    fetches => Ok(ModelCatalog::from_cache_fresh(vec![Model::from_id(
+                        format!("caching-{fetches}"),
+                    )])),
+                }
+            })
+        }
+
+        fn refresh_models<'a>(&'a self) -> BoxFuture<'a, ProviderResult<ModelCatalog>> {
+            Box::pin(async move {
+                let fetch = self.network_fetches.fetch_add(1, Ordering::SeqCst) + 1;
+                Ok(ModelCatalog::from_provider_api(vec![Model::from_id(
+                    format!("caching-{fetch}"),
+                )]))
+            })
+        }
+
+        fn stream_chat(&self, _request: StreamRequest) -> ProviderResult<ProviderStreamHandle> {
+            Err(ProviderError::UnsupportedProvider {
+                stage: "caching-provider-stream",
+                provider_id: self.id().to_string(),
+            })
+        }
+    }
+
+    #[::core::prelude::v1::test]
+    fn every_refresh_reaches_the_provider() {
+        let provider = Arc::new(CachingProvider {
+            network_fetches: AtomicUsize::new(0),
+        });
+        let runtime = tokio::runtime::Builder::new_current_thread().build();
+        let Ok(runtime) = runtime else {
+            panic!("failed to build test runtime: {runtime:?}");
+        };
+
+        let mut groups = vec![ProviderModelGroup {
+            provider_key: "provider-1".to_string(),
+            provider_id: "caching".to_string(),
+            models: Vec::new(),
+        }];
+        for expected_fetches in 1..=2 {
+            let results = runtime.block_on(fetch_provider_catalogs(vec![(
+                "provider-1".to_string(),
+                provider.clone() as Arc<dyn LlmProvider>,
+            )]));
+            assert!(matches!(
+                results.as_slice(),
+                [(_, Ok(catalog))] if catalog.source == ModelCatalogSource::ProviderApi
+            ));
+            assert_eq!(apply_fetched_catalogs(&mut groups, results), None);
+            assert_eq!(
+                provider.network_fetches.load(Ordering::SeqCst),
+                expected_fetches
+            );
+            assert_eq!(
+                groups[0].models,
+                vec![Model::from_id(format!("caching-{expected_fetches}"))]
+            );
+        }
+    }
+
+    #[::core::prelude::v1::test]
+    fn failed_fetch_keeps_models_and_reports_error() {
+        let mut groups = vec![ProviderModelGroup {
+            provider_key: "provider-1".to_string(),
+            provider_id: "openai".to_string(),
+            models: default_openai_models(),
+        }];
+        let results = vec![(
+            "provider-1".to_string(),
+            Err(ProviderError::MissingApiKey {
+                stage: "fixed-provider-fetch",
+                provider_id: "openai".to_string(),
+            }),
+        )];
+
+        let error = apply_fetched_catalogs(&mut groups, results);
+
+        assert_eq!(
+            error.as_deref(),
+            Some("provider-1: missing API key for provider 'openai'")
+        );
+        assert_eq!(groups[0].models, default_openai_models());
+    }
+}
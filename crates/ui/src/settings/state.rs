@@ -18,6 +18,7 @@ pub const DEFAULT_ENDPOINT: &str = "https://api.openai.com/v1";
 pub const SETTINGS_DIRECTORY_NAME: &str = "zova";
 pub const SETTINGS_FILE_NAME: &str = "settings.json";
 pub const DEFAULT_PROVIDER_KEY: &str = "provider-1";
+pub const REDACTED_SECRET: &str = "***";
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ModelSettings {
@@ -220,6 +221,8 @@ pub struct ProviderSettings {
     pub theme_mode: ThemeMode,
     #[serde(default)]
     pub theme_name: String,
+    #[serde(default)]
+    pub sidebar_width: Option<u32>,
 }
 
 impl Default for ProviderSettings {
@@ -233,6 +236,7 @@ impl Default for ProviderSettings {
             models: Vec::new(),
             theme_mode: default_theme_mode(),
             theme_name: String::new(),
+            sidebar_width: None,
         }
     }
 }
@@ -387,6 +391,45 @@ impl ProviderSettings {
         self
     }
 
+    /// Serializes settings for export; `redact_secrets` masks every non-empty API key.
+    pub fn to_json(&self, redact_secrets: bool) -> Result<serde_json::Value, SettingsError> {
+        let mut settings = self.clone();
+        if redact_secrets {
+            for provider in &mut settings.providers {
+                if !provider.api_key.is_empty() {
+                    provider.api_key = REDACTED_SECRET.to_string();
+                }
+            }
+        }
+
+        serde_json::to_value(&settings).context(SerializeConfigSnafu {
+            stage: "serialize-settings-export",
+        })
+    }
+
+    /// Parses exported settings and normalizes them like a settings file load.
+    pub fn from_json(value: serde_json::Value) -> Result<Self, SettingsError> {
+        let settings =
+            serde_json::from_value::<ProviderSettings>(value).context(ParseConfigSnafu {
+                stage: "parse-settings-import",
+            })?;
+        Ok(settings.normalized())
+    }
+
+    /// Keeps current API keys for providers whose imported key was redacted on export.
+    pub fn restore_redacted_secrets(&mut self, current: &ProviderSettings) {
+        for provider in &mut self.providers {
+            if provider.api_key != REDACTED_SECRET {
+                continue;
+            }
+
+            provider.api_key = current
+                .provider_by_key(&provider.provider_key)
+                .map(|current_provider| current_provider.api_key.clone())
+                .unwrap_or_default();
+        }
+    }
+
     pub fn apply_theme(&self, window: Option<&mut Window>, cx: &mut App) {
         if let Some(theme_config) = ThemeRegistry::global(cx)
             .themes()
@@ -447,6 +490,10 @@ impl SettingsStore {
         Ok(())
     }
 
+    pub fn reset_to_defaults(&self) -> Result<(), SettingsError> {
+        self.update(ProviderSettings::default())
+    }
+
     fn load_from_disk(path: &PathBuf) -> ProviderSettings {
         if !path.exists() {
             tracing::info!("settings file not found at {:?}, using defaults", path);
@@ -512,6 +559,17 @@ pub enum SettingsError {
         stage: &'static str,
         source: serde_json::Error,
     },
+    #[snafu(display("failed to parse settings on `{stage}`: {source}"))]
+    ParseConfig {
+        stage: &'static str,
+        source: serde_json::Error,
+    },
+    #[snafu(display("failed to read settings file at {path:?} on `{stage}`: {source}"))]
+    ReadFile {
+        stage: &'static str,
+        path: PathBuf,
+        source: std::io::Error,
+    },
     #[snafu(display("failed to write settings file at {path:?} on `{stage}`: {source}"))]
     WriteFile {
         stage: &'static str,
@@ -551,6 +609,27 @@ impl SettingsState {
         self.store.settings()
     }
 
+    pub fn to_json(&self, redact_secrets: bool) -> Result<serde_json::Value, SettingsError> {
+        self.settings().to_json(redact_secrets)
+    }
+
+    pub fn from_json(value: serde_json::Value) -> Result<ProviderSettings, SettingsError> {
+        ProviderSettings::from_json(value)
+    }
+
+    /// Persists the sidebar width without emitting `SettingsChanged`.
+    ///
+    /// Layout tweaks must not trigger provider reloads, which would cancel an active stream.
+    pub fn update_sidebar_width(&mut self, sidebar_width: u32) -> Result<(), SettingsError> {
+        let mut settings = (*self.store.settings()).clone();
+        if settings.sidebar_width == Some(sidebar_width) {
+            return Ok(());
+        }
+
+        settings.sidebar_width = Some(sidebar_width);
+        self.store.update(settings)
+    }
+
     pub fn update_settings(
         &mut self,
         settings: ProviderSettings,
@@ -564,6 +643,16 @@ impl SettingsState {
         cx.notify();
         Ok(())
     }
+
+    /// Replaces every setting with its default so a misconfigured install can recover.
+    pub fn reset_to_defaults(&mut self, cx: &mut Context<Self>) -> Result<(), SettingsError> {
+        self.store.reset_to_defaults()?;
+        cx.emit(SettingsChanged {
+            settings: (*self.store.settings()).clone(),
+        });
+        cx.notify();
+        Ok(())
+    }
 }
 
 fn default_provider_key() -> String {
@@ -616,3 +705,104 @@ fn parse_theme_mode(value: &str) -> ThemeMode {
         ThemeMode::Light
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_settings() -> ProviderSettings {
+        ProviderSettings {
+            active_provider_key: "provider-2".to_string(),
+            providers: vec![
+                ProviderProfileSettings {
+                    api_key: "sk-first".to_string(),
+                    ..ProviderProfileSettings::default()
+                },
+                ProviderProfileSettings {
+                    provider_key: "provider-2".to_string(),
+                    provider_id: "rig-openai".to_string(),
+                    api_key: "sk-second".to_string(),
+                    endpoint: "https://example.invalid/v1".to_string(),
+                    models: vec![ModelSettings {
+                        model_name: "custom-model".to_string(),
+                        max_completion_tokens: Some(2_048),
+                        max_output_tokens: None,
+                        max_tokens: Some(4_096),
+                    }],
+                },
+            ],
+            theme_mode: ThemeMode::Dark,
+            theme_name: "Custom Theme".to_string(),
+            ..ProviderSettings::default()
+        }
+        .normalized()
+    }
+
+    #[::core::prelude::v1::test]
+    fn settings_json_roundtrip_preserves_all_fields() -> Result<(), SettingsError> {
+        let settings = fixture_settings();
+
+        let imported = ProviderSettings::from_json(settings.to_json(false)?)?;
+
+        assert_eq!(imported, settings);
+        Ok(())
+    }
+
+    #[::core::prelude::v1::test]
+    fn redacted_settings_export_omits_api_keys() -> Result<(), SettingsError> {
+        let settings = fixture_settings();
+
+        let exported = settings.to_json(true)?;
+        let exported_text = exported.to_string();
+        assert!(!exported_text.contains("sk-first"));
+        assert!(!exported_text.contains("sk-second"));
+
+        let mut imported = ProviderSettings::from_json(exported)?;
+        assert!(
+            imported
+                .providers
+                .iter()
+                .all(|provider| provider.api_key == REDACTED_SECRET)
+        );
+
+        imported.restore_redacted_secrets(&settings);
+        assert_eq!(imported, settings);
+        Ok(())
+    }
+
+    #[::core::prelude::v1::test]
+    fn reset_to_defaults_restores_default_provider() -> Result<(), SettingsError> {
+        let config_path = std::env::temp_dir()
+            .join(format!("zova-settings-reset-{}", std::process::id()))
+            .join(SETTINGS_FILE_NAME);
+        let store = SettingsStore::new(config_path.clone());
+        store.update(fixture_settings())?;
+
+        store.reset_to_defaults()?;
+
+        let settings = store.settings();
+        let active_provider = settings.active_provider();
+        assert_eq!(
+            active_provider.map(|provider| provider.provider_id.as_str()),
+            Some(DEFAULT_PROVIDER_ID)
+        );
+        assert_eq!(
+            active_provider.map(|provider| provider.api_key.as_str()),
+            Some("")
+        );
+        assert_eq!(
+            active_provider.map(|provider| provider.endpoint.as_str()),
+            Some(DEFAULT_ENDPOINT)
+        );
+        // The reset must survive a reload from disk, not just the in-memory swap.
+        assert_eq!(
+            *SettingsStore::new(config_path.clone()).settings(),
+            *settings
+        );
+
+        if let Some(parent) = config_path.parent() {
+            let _ = std::fs::remove_dir_all(parent);
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,6 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
 use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::{
@@ -9,12 +12,20 @@ use gpui_component::{
     select::SelectState,
     v_flex,
 };
+use gpui_tokio_bridge::Tokio;
+use snafu::ResultExt;
+use zova_llm::{
+    LlmProvider, ModelCatalog, ModelCatalogSource, ProviderConfig, ProviderResult, create_provider,
+};
+use zova_storage::SqliteStorage;
 
 use crate::settings::state::{
-    ModelSettings, ProviderProfileSettings, ProviderSettings, SettingsState,
+    ModelSettings, ParseConfigSnafu, ProviderProfileSettings, ProviderSettings, ReadFileSnafu,
+    SerializeConfigSnafu, SettingsError, SettingsState, WriteFileSnafu,
 };
 
 mod provider;
+mod storage;
 mod theme;
 
 struct ModelInputRow {
@@ -28,9 +39,18 @@ struct ModelInputRow {
 enum SettingsCategory {
     Provider,
     Theme,
+    Storage,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ConnectionTestStatus {
+    InProgress,
+    Succeeded { model_count: usize },
+    Failed(String),
 }
 
 const SETTINGS_TRAFFIC_LIGHT_SAFE_TOP: f32 = 44.0;
+const SETTINGS_EXPORT_FILE_NAME: &str = "zova-settings.json";
 
 pub struct SettingsView {
     state: Entity<SettingsState>,
@@ -45,6 +65,11 @@ pub struct SettingsView {
     theme_mode: ThemeMode,
     active_category: SettingsCategory,
     error_message: Option<String>,
+    storage: Option<Arc<SqliteStorage>>,
+    compaction_in_progress: bool,
+    compaction_status: Option<String>,
+    connection_test: Option<ConnectionTestStatus>,
+    connection_test_task: Option<Task<()>>,
 }
 
 impl SettingsView {
@@ -277,7 +302,12 @@ impl SettingsView {
         Ok(())
     }
 
-    pub fn new(state: &Entity<SettingsState>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+    pub fn new(
+        state: &Entity<SettingsState>,
+        storage: Option<Arc<SqliteStorage>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
         let settings = state.read(cx).settings();
 
         let provider_input =
@@ -329,6 +359,11 @@ impl SettingsView {
             theme_mode: settings.theme_mode,
             active_category: SettingsCategory::Provider,
             error_message: None,
+            storage,
+            compaction_in_progress: false,
+            compaction_status: None,
+            connection_test: None,
+            connection_test_task: None,
         }
     }
 
@@ -353,6 +388,7 @@ impl SettingsView {
         });
         self.theme_mode = settings.theme_mode;
         self.error_message = None;
+        self.reset_connection_test();
     }
 
     fn add_provider_profile(
@@ -442,6 +478,7 @@ impl SettingsView {
 
         self.active_provider_index = index;
         self.load_active_provider_into_inputs(window, cx);
+        self.reset_connection_test();
         self.expanded_provider_index = Some(index);
         self.error_message = None;
         cx.notify();
@@ -470,6 +507,51 @@ impl SettingsView {
         cx.notify();
     }
 
+    /// Fetches the model list with the credentials currently typed into the provider inputs,
+    /// without saving them, and reports whether the provider accepted them.
+    pub fn validate_connection(&mut self, cx: &mut Context<Self>) {
+        if self.connection_test == Some(ConnectionTestStatus::InProgress) {
+            return;
+        }
+
+        let provider_id = self.provider_input.read(cx).value().trim().to_string();
+        let api_key = self.api_key_input.read(cx).value().trim().to_string();
+        let endpoint = self.endpoint_input.read(cx).value().trim().to_string();
+        let endpoint = if endpoint.is_empty() {
+            crate::settings::state::DEFAULT_ENDPOINT.to_string()
+        } else {
+            endpoint
+        };
+        let provider = match create_provider(ProviderConfig::new(provider_id, api_key, endpoint)) {
+            Ok(provider) => provider,
+            Err(error) => {
+                self.connection_test = Some(ConnectionTestStatus::Failed(error.to_string()));
+                cx.notify();
+                return;
+            }
+        };
+
+        self.connection_test = Some(ConnectionTestStatus::InProgress);
+        cx.notify();
+
+        let probe = Tokio::spawn(cx, test_provider_connection(provider));
+        self.connection_test_task = Some(cx.spawn(async move |this, cx| {
+            let status = probe.await.unwrap_or_else(|error| {
+                ConnectionTestStatus::Failed(format!("Connection test task failed: {error}"))
+            });
+            let _ = this.update(cx, |this, cx| {
+                this.connection_test = Some(status);
+                this.connection_test_task = None;
+                cx.notify();
+            });
+        }));
+    }
+
+    fn reset_connection_test(&mut self) {
+        self.connection_test = None;
+        self.connection_test_task = None;
+    }
+
     fn select_light_mode(
         &mut self,
         _event: &gpui::ClickEvent,
@@ -508,6 +590,21 @@ impl SettingsView {
             return;
         };
 
+        // Surface malformed provider configs now instead of on the first stream request.
+        for provider in &self.provider_profiles {
+            if let Some(Err(error)) = provider
+                .to_provider_config()
+                .map(|config| config.validate())
+            {
+                self.error_message = Some(format!(
+                    "Provider '{}' is invalid: {error}",
+                    provider.provider_key
+                ));
+                cx.notify();
+                return;
+            }
+        }
+
         let theme_name = self
             .theme_preset_select
             .read(cx)
@@ -515,6 +612,7 @@ impl SettingsView {
             .map(|theme_name| theme_name.to_string())
             .unwrap_or_default();
 
+        let sidebar_width = self.state.read(cx).settings().sidebar_width;
         let new_settings = ProviderSettings {
             active_provider_key: active_provider.provider_key.clone(),
             providers: self.provider_profiles.clone(),
@@ -524,6 +622,7 @@ impl SettingsView {
             models: Vec::new(),
             theme_mode: self.theme_mode,
             theme_name: theme_name.trim().to_string(),
+            sidebar_width,
         };
 
         match self
@@ -548,6 +647,166 @@ impl SettingsView {
         cx.notify();
     }
 
+    fn export_settings(
+        &mut self,
+        _event: &gpui::ClickEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let settings_json = match self.state.read(cx).to_json(false) {
+            Ok(settings_json) => settings_json,
+            Err(error) => {
+                self.error_message = Some(format!("Failed to export settings: {error}"));
+                cx.notify();
+                return;
+            }
+        };
+
+        let directory = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        let path_prompt = cx.prompt_for_new_path(&directory, Some(SETTINGS_EXPORT_FILE_NAME));
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(path))) = path_prompt.await else {
+                return;
+            };
+
+            let write_result = write_settings_json(&path, &settings_json);
+            let _ = this.update(cx, |this, cx| {
+                this.error_message = write_result
+                    .err()
+                    .map(|error| format!("Failed to export settings: {error}"));
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn import_settings(
+        &mut self,
+        _event: &gpui::ClickEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let path_prompt = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Import Settings".into()),
+        });
+        cx.spawn_in(window, async move |this, cx| {
+            let Ok(Ok(Some(paths))) = path_prompt.await else {
+                return;
+            };
+            let Some(path) = paths.into_iter().next() else {
+                return;
+            };
+
+            let read_result = read_settings_json(&path).and_then(SettingsState::from_json);
+            let _ = this.update_in(cx, |this, window, cx| {
+                let update_result = read_result.and_then(|mut imported_settings| {
+                    let current_settings = this.state.read(cx).settings();
+                    imported_settings.restore_redacted_secrets(&current_settings);
+                    // Window layout is machine-local, so imports keep the current width.
+                    imported_settings.sidebar_width = current_settings.sidebar_width;
+                    this.state
+                        .update(cx, |state, cx| state.update_settings(imported_settings, cx))
+                });
+
+                match update_result {
+                    Ok(()) => this.reload_from_settings(window, cx),
+                    Err(error) => {
+                        this.error_message = Some(format!("Failed to import settings: {error}"));
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn reset_to_defaults(
+        &mut self,
+        _event: &gpui::ClickEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let confirmation = window.prompt(
+            PromptLevel::Warning,
+            "Reset all settings to defaults?",
+            Some("Provider profiles, API keys and theme choices will be replaced."),
+            &["Reset", "Cancel"],
+            cx,
+        );
+        cx.spawn_in(window, async move |this, cx| {
+            // Index 0 is the "Reset" answer; anything else, including a dismissed prompt, aborts.
+            let Ok(0) = confirmation.await else {
+                return;
+            };
+
+            let _ = this.update_in(cx, |this, window, cx| {
+                match this
+                    .state
+                    .update(cx, |state, cx| state.reset_to_defaults(cx))
+                {
+                    Ok(()) => {
+                        this.error_message = None;
+                        this.reload_from_settings(window, cx);
+                    }
+                    Err(error) => {
+                        this.error_message = Some(format!("Failed to reset settings: {error}"));
+                    }
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    fn compact_database(
+        &mut self,
+        _event: &gpui::ClickEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(storage) = self.storage.clone() else {
+            self.compaction_status = Some("Storage is unavailable".to_string());
+            cx.notify();
+            return;
+        };
+        if self.compaction_in_progress {
+            return;
+        }
+
+        self.compaction_in_progress = true;
+        self.compaction_status = Some("Compacting database…".to_string());
+        cx.notify();
+
+        // VACUUM rewrites the whole file, so keep it off the UI thread.
+        let compaction = cx.background_executor().spawn(async move {
+            let size_before = storage.database_size_bytes()?;
+            let freed_pages = storage.vacuum()?;
+            let size_after = storage.database_size_bytes()?;
+            Ok::<_, zova_storage::StorageError>((size_before, size_after, freed_pages))
+        });
+        cx.spawn(async move |this, cx| {
+            let result = compaction.await;
+            let _ = this.update(cx, |this, cx| {
+                this.compaction_in_progress = false;
+                this.compaction_status = Some(match result {
+                    Ok((size_before, size_after, freed_pages)) => format!(
+                        "Compacted from {} to {} ({freed_pages} pages freed)",
+                        format_byte_size(size_before),
+                        format_byte_size(size_after)
+                    ),
+                    Err(error) => {
+                        format!("Failed to compact database: {}", error.display_message())
+                    }
+                });
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
     fn select_provider_category(
         &mut self,
         _event: &gpui::ClickEvent,
@@ -567,15 +826,27 @@ impl SettingsView {
         self.active_category = SettingsCategory::Theme;
         cx.notify();
     }
+
+    fn select_storage_category(
+        &mut self,
+        _event: &gpui::ClickEvent,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.active_category = SettingsCategory::Storage;
+        cx.notify();
+    }
 }
 
 impl Render for SettingsView {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let provider_selected = self.active_category == SettingsCategory::Provider;
         let theme_selected = self.active_category == SettingsCategory::Theme;
+        let storage_selected = self.active_category == SettingsCategory::Storage;
         let category_content = match self.active_category {
             SettingsCategory::Provider => provider::render(self, cx),
             SettingsCategory::Theme => theme::render(self, cx),
+            SettingsCategory::Storage => storage::render(self, cx),
         };
         let theme = cx.theme();
 
@@ -624,6 +895,36 @@ impl Render for SettingsView {
                                     .when(!theme_selected, |button| button.ghost())
                                     .child("Theme")
                                     .on_click(cx.listener(Self::select_theme_category)),
+                            )
+                            .child(
+                                Button::new("settings-category-storage")
+                                    .small()
+                                    .when(storage_selected, |button| button.primary())
+                                    .when(!storage_selected, |button| button.ghost())
+                                    .child("Storage")
+                                    .on_click(cx.listener(Self::select_storage_category)),
+                            )
+                            .child(div().flex_1())
+                            .child(
+                                Button::new("settings-export")
+                                    .small()
+                                    .ghost()
+                                    .child("Export Settings")
+                                    .on_click(cx.listener(Self::export_settings)),
+                            )
+                            .child(
+                                Button::new("settings-import")
+                                    .small()
+                                    .ghost()
+                                    .child("Import Settings")
+                                    .on_click(cx.listener(Self::import_settings)),
+                            )
+                            .child(
+                                Button::new("settings-reset-defaults")
+                                    .small()
+                                    .ghost()
+                                    .child("Reset to Defaults")
+                                    .on_click(cx.listener(Self::reset_to_defaults)),
                             ),
                     )
                     .child(
@@ -640,3 +941,145 @@ impl Render for SettingsView {
             )
     }
 }
+
+async fn test_provider_connection(provider: Arc<dyn LlmProvider>) -> ConnectionTestStatus {
+    connection_test_status(provider.fetch_models().await)
+}
+
+fn connection_test_status(result: ProviderResult<ModelCatalog>) -> ConnectionTestStatus {
+    match result {
+        // A fresh cache entry is keyed by these exact credentials, so it proves they worked
+        // within the TTL; fallbacks mean the provider itself rejected or never answered.
+        Ok(catalog) => match catalog.source {
+            ModelCatalogSource::ProviderApi | ModelCatalogSource::CacheFresh => {
+                ConnectionTestStatus::Succeeded {
+                    model_count: catalog.models.len(),
+                }
+            }
+            _ => ConnectionTestStatus::Failed(
+                catalog
+                    .warning
+                    .unwrap_or_else(|| "Provider did not return a model list".to_string()),
+            ),
+        },
+        Err(error) => ConnectionTestStatus::Failed(error.to_string()),
+    }
+}
+
+fn format_byte_size(bytes: u64) -> String {
+    const KIB: f64 = 1024.0;
+    const MIB: f64 = KIB * 1024.0;
+
+    let bytes = bytes as f64;
+    if bytes >= MIB {
+        format!("{:.1} MiB", bytes / MIB)
+    } else {
+        format!("{:.1} KiB", bytes / KIB)
+    }
+}
+
+fn write_settings_json(
+    path: &Path,
+    settings_json: &serde_json::Value,
+) -> Result<(), SettingsError> {
+    let content = serde_json::to_string_pretty(settings_json).context(SerializeConfigSnafu {
+        stage: "serialize-settings-export-file",
+    })?;
+    std::fs::write(path, content).context(WriteFileSnafu {
+        stage: "write-settings-export-file",
+        path: path.to_path_buf(),
+    })
+}
+
+fn read_settings_json(path: &Path) -> Result<serde_json::Value, SettingsError> {
+    let content = std::fs::read_to_string(path).context(ReadFileSnafu {
+        stage: "read-settings-import-file",
+        path: path.to_path_buf(),
+    })?;
+    serde_json::from_str(&content).context(ParseConfigSnafu {
+        stage: "parse-settings-import-file",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zova_llm::{BoxFuture, Model, ProviderError, ProviderStreamHandle, StreamRequest};
+
+    struct ScriptedCatalogProvider {
+        catalog: Option<ModelCatalog>,
+    }
+
+    impl LlmProvider for ScriptedCatalogProvider {
+        fn id(&self) -> &str {
+            "scripted"
+        }
+
+        fn name(&self) -> &str {
+            "Scripted"
+        }
+
+        fn default_model(&self) -> &str {
+            "scripted-small"
+        }
+
+        fn fallback_models(&self) -> &[Model] {
+            &[]
+        }
+
+        fn fetch_models<'a>(&'a self) -> BoxFuture<'a, ProviderResult<ModelCatalog>> {
+            Box::pin(async move {
+                self.catalog
+                    .clone()
+                    .ok_or_else(|| ProviderError::MissingApiKey {
+                        stage: "scripted-provider-fetch",
+                        provider_id: "scripted".to_string(),
+                    })
+            })
+        }
+
+        fn stream_chat(&self, _request: StreamRequest) -> ProviderResult<ProviderStreamHandle> {
+            Err(ProviderError::UnsupportedProvider {
+                stage: "scripted-provider-stream",
+                provider_id: self.id().to_string(),
+            })
+        }
+    }
+
+    fn probe(catalog: Option<ModelCatalog>) -> ConnectionTestStatus {
+        let provider: Arc<dyn LlmProvider> = Arc::new(ScriptedCatalogProvider { catalog });
+        let runtime = tokio::runtime::Builder::new_current_thread().build();
+        let Ok(runtime) = runtime else {
+            panic!("failed to build test runtime: {runtime:?}");
+        };
+        runtime.block_on(test_provider_connection(provider))
+    }
+
+    #[::core::prelude::v1::test]
+    fn successful_model_fetch_reports_success() {
+        let status = probe(Some(ModelCatalog::from_provider_api(vec![
+            Model::from_id("scripted-small"),
+            Model::from_id("scripted-large"),
+        ])));
+
+        assert_eq!(status, ConnectionTestStatus::Succeeded { model_count: 2 });
+    }
+
+    #[::core::prelude::v1::test]
+    fn failed_model_fetch_reports_the_error() {
+        let fallback = probe(Some(ModelCatalog::from_static_fallback(
+            vec![Model::from_id("scripted-small")],
+            "model endpoint returned HTTP 401".to_string(),
+        )));
+        let error = probe(None);
+
+        assert_eq!(
+            fallback,
+            ConnectionTestStatus::Failed("model endpoint returned HTTP 401".to_string())
+        );
+        assert_eq!(
+            error,
+            ConnectionTestStatus::Failed("missing API key for provider 'scripted'".to_string())
+        );
+    }
+}
@@ -1,19 +1,22 @@
 use gpui::prelude::FluentBuilder;
 use gpui::*;
 use gpui_component::{
-    ActiveTheme, IconName, Sizable,
+    ActiveTheme, Disableable, Icon, IconName, Sizable,
     button::{Button, ButtonVariants},
     h_flex,
     input::Input,
+    spinner::Spinner,
     v_flex,
 };
 
-use super::SettingsView;
+use super::{ConnectionTestStatus, SettingsView};
 
 pub(super) fn render(view: &mut SettingsView, cx: &mut Context<SettingsView>) -> AnyElement {
     let theme = cx.theme();
     let can_remove_rows = view.model_rows.len() > 1;
     let can_remove_providers = view.provider_profiles.len() > 1;
+    let connection_test = view.connection_test.clone();
+    let connection_testing = connection_test == Some(ConnectionTestStatus::InProgress);
 
     v_flex()
         .id("settings-provider-category")
@@ -152,6 +155,66 @@ pub(super) fn render(view: &mut SettingsView, cx: &mut Context<SettingsView>) ->
                                             )
                                             .child(Input::new(&view.endpoint_input).w_full()),
                                     )
+                                    .child(
+                                        h_flex()
+                                            .gap_2()
+                                            .items_center()
+                                            .child(
+                                                Button::new("settings-test-connection")
+                                                    .ghost()
+                                                    .small()
+                                                    .disabled(connection_testing)
+                                                    .child("Test Connection")
+                                                    .on_click(cx.listener(
+                                                        |this, _event, _window, cx| {
+                                                            this.validate_connection(cx);
+                                                        },
+                                                    )),
+                                            )
+                                            .when_some(connection_test.clone(), |row, status| {
+                                                row.child(match status {
+                                                    ConnectionTestStatus::InProgress => h_flex()
+                                                        .gap_1()
+                                                        .items_center()
+                                                        .child(Spinner::new().small())
+                                                        .child(
+                                                            div()
+                                                                .text_xs()
+                                                                .text_color(theme.muted_foreground)
+                                                                .child("Testing…"),
+                                                        ),
+                                                    ConnectionTestStatus::Succeeded { model_count } => h_flex()
+                                                        .gap_1()
+                                                        .items_center()
+                                                        .child(
+                                                            Icon::new(IconName::CircleCheck)
+                                                                .small()
+                                                                .text_color(theme.success),
+                                                        )
+                                                        .child(
+                                                            div()
+                                                                .text_xs()
+                                                                .text_color(theme.success)
+                                                                .child(format!("Connected ({model_count} models)")),
+                                                        ),
+                                                    ConnectionTestStatus::Failed(error) => h_flex()
+                                                        .gap_1()
+                                                        .items_center()
+                                                        .min_w_0()
+                                                        .child(
+                                                            Icon::new(IconName::CircleX)
+                                                                .small()
+                                                                .text_color(theme.danger),
+                                                        )
+                                                        .child(
+                                                            div()
+                                                                .text_xs()
+                                                                .text_color(theme.danger)
+                                                                .child(error),
+                                                        ),
+                                                })
+                                            }),
+                                    )
                                     .child(
                                         v_flex()
                                             .gap_2()
@@ -0,0 +1,55 @@
+use gpui::prelude::FluentBuilder;
+use gpui::*;
+use gpui_component::{
+    ActiveTheme, Disableable, Sizable,
+    button::{Button, ButtonVariants},
+    h_flex, v_flex,
+};
+
+use super::SettingsView;
+
+pub(super) fn render(view: &mut SettingsView, cx: &mut Context<SettingsView>) -> AnyElement {
+    let theme = cx.theme();
+
+    v_flex()
+        .id("settings-storage-category")
+        .gap_4()
+        .p_4()
+        .child(
+            div()
+                .text_lg()
+                .font_weight(FontWeight::SEMIBOLD)
+                .text_color(theme.foreground)
+                .child("Storage Settings"),
+        )
+        .child(
+            v_flex()
+                .gap_1()
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(theme.foreground)
+                        .child("Compact Database"),
+                )
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(theme.muted_foreground)
+                        .child("Reclaim disk space left behind by deleted conversations."),
+                )
+                .child(
+                    h_flex().gap_2().items_center().child(
+                        Button::new("settings-compact-database")
+                            .small()
+                            .ghost()
+                            .disabled(view.compaction_in_progress || view.storage.is_none())
+                            .child("Compact Database")
+                            .on_click(cx.listener(SettingsView::compact_database)),
+                    ),
+                ),
+        )
+        .when_some(view.compaction_status.clone(), |el, status| {
+            el.child(div().text_sm().text_color(theme.foreground).child(status))
+        })
+        .into_any_element()
+}